@@ -0,0 +1,50 @@
+// This file is part of Acala.
+
+// Copyright (C) 2022 Acala Foundation.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Runtime API definition for AquaDao manager module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The `too_many_arguments` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::too_many_arguments)]
+// The `unnecessary_mut_passed` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::unnecessary_mut_passed)]
+
+use acala_primitives::{Balance, TradingPair};
+use codec::Codec;
+use sp_runtime::FixedU128;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait AdaoManagerApi<MintReason, StrategyKind, StrategyExecutionStats> where
+		MintReason: Codec,
+		StrategyKind: Codec,
+		StrategyExecutionStats: Codec,
+	{
+		/// Protocol owned liquidity positions in `(trading_pair, first_amount, second_amount)`.
+		fn pol_positions() -> Vec<(TradingPair, Balance, Balance)>;
+
+		/// Cumulative ADAO minted by the pallet, by reason.
+		fn mint_ledger() -> Vec<(MintReason, Balance)>;
+
+		/// Portfolio drift from target allocations. `None` if not available, for instance no price.
+		fn drift_score() -> Option<FixedU128>;
+
+		/// Cumulative execution stats of management strategies, by kind.
+		fn strategy_stats() -> Vec<(StrategyKind, StrategyExecutionStats)>;
+	}
+}
@@ -0,0 +1,326 @@
+// This file is part of Acala.
+
+// Copyright (C) 2022 Acala Foundation.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Pure allocation calculations, free of storage and price provider access.
+
+use super::*;
+use module_support::Price;
+
+/// `a - b` as a signed fixed point number, saturating at `FixedI128` bounds.
+fn signed_sub(a: FixedU128, b: FixedU128) -> FixedI128 {
+	if a > b {
+		let d_inner: i128 = a.saturating_sub(b).into_inner().unique_saturated_into();
+		FixedI128::from_inner(d_inner)
+	} else {
+		let d_inner: i128 = b.saturating_sub(a).into_inner().unique_saturated_into();
+		FixedI128::from_inner(d_inner.saturating_mul(-1))
+	}
+}
+
+/// Calculate the diff between current and target allocations.
+///
+/// `prices` must contain the price of every currency in `targets`, a missing or zero price
+/// results in `ArithmeticError::DivisionByZero`.
+pub(crate) fn compute_diffs(
+	current: &BTreeMap<CurrencyId, CurrentAllocation>,
+	targets: &BTreeMap<CurrencyId, AllocationPercent>,
+	prices: &BTreeMap<CurrencyId, Price>,
+	total_value: Balance,
+) -> Result<BTreeMap<CurrencyId, AllocationDiff>, ArithmeticError> {
+	let mut diff = BTreeMap::new();
+
+	for (currency_id, target_percent) in targets.iter() {
+		let target_value = target_percent.value.saturating_mul_int(total_value);
+		let price = prices.get(currency_id).copied().unwrap_or_default();
+		let target_amount = price
+			.reciprocal()
+			.ok_or(ArithmeticError::DivisionByZero)?
+			.saturating_mul_int(target_value);
+
+		if let Some(current) = current.get(currency_id) {
+			let range_diff = if current.percent < target_percent.min {
+				// current.percent - target.minPercent
+				signed_sub(current.percent, target_percent.min)
+			} else if current.percent > target_percent.max {
+				// current.percent - target.maxPercent
+				signed_sub(current.percent, target_percent.max)
+			} else {
+				FixedI128::zero()
+			};
+
+			// diff = current.percent - target_percent.value
+			let diff_percent = signed_sub(current.percent, target_percent.value);
+
+			// diff_amount = current.amount - target_amount
+			let diff_amount = if current.amount > target_amount {
+				current.amount.saturating_sub(target_amount).unique_saturated_into()
+			} else {
+				let a: i128 = target_amount.saturating_sub(current.amount).unique_saturated_into();
+				a.saturating_mul(-1)
+			};
+
+			diff.insert(
+				*currency_id,
+				AllocationDiff {
+					current: current.percent,
+					target: target_percent.value,
+					diff: diff_percent,
+					range_diff,
+					diff_amount,
+				},
+			);
+		} else {
+			// diff_percent = -target_percent.value
+			let diff_percent = {
+				let d_inner: i128 = target_percent.value.into_inner().unique_saturated_into();
+				FixedI128::from_inner(d_inner.saturating_mul(-1))
+			};
+			// range_diff = -target_percent.min
+			let range_diff = {
+				let d_inner: i128 = target_percent.min.into_inner().unique_saturated_into();
+				FixedI128::from_inner(d_inner.saturating_mul(-1))
+			};
+			// diff_amount = -target_amount
+			let diff_amount = {
+				let a: i128 = target_amount.unique_saturated_into();
+				a.saturating_mul(-1)
+			};
+			diff.insert(
+				*currency_id,
+				AllocationDiff {
+					current: FixedU128::zero(),
+					target: target_percent.value,
+					diff: diff_percent,
+					range_diff,
+					diff_amount,
+				},
+			);
+		}
+	}
+
+	for (currency_id, current) in current.iter() {
+		if !targets.contains_key(currency_id) {
+			diff.insert(
+				*currency_id,
+				AllocationDiff {
+					current: current.percent,
+					target: FixedU128::zero(),
+					diff: FixedI128::from_inner(current.percent.into_inner().unique_saturated_into()),
+					range_diff: FixedI128::from_inner(current.percent.into_inner().unique_saturated_into()),
+					diff_amount: current.amount.unique_saturated_into(),
+				},
+			);
+		}
+	}
+
+	Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::traits::One;
+
+	const ACA: CurrencyId = Token(TokenSymbol::ACA);
+	const AUSD: CurrencyId = Token(TokenSymbol::AUSD);
+	const DOT: CurrencyId = Token(TokenSymbol::DOT);
+
+	fn percent(n: u128, d: u128) -> FixedU128 {
+		FixedU128::saturating_from_rational(n, d)
+	}
+
+	fn target(value: FixedU128, min: FixedU128, max: FixedU128) -> AllocationPercent {
+		AllocationPercent { value, min, max }
+	}
+
+	fn current_allocation(amount: Balance, value: Balance, percent: FixedU128) -> CurrentAllocation {
+		CurrentAllocation { amount, value, percent }
+	}
+
+	fn unit_prices(currencies: &[CurrencyId]) -> BTreeMap<CurrencyId, Price> {
+		currencies.iter().map(|c| (*c, Price::one())).collect()
+	}
+
+	#[test]
+	fn no_diff_when_on_target() {
+		let current = BTreeMap::from([(ACA, current_allocation(500, 500, percent(1, 2)))]);
+		let targets = BTreeMap::from([(ACA, target(percent(1, 2), percent(2, 5), percent(3, 5)))]);
+
+		let diff = compute_diffs(&current, &targets, &unit_prices(&[ACA]), 1_000).unwrap();
+		assert_eq!(
+			diff.get(&ACA).unwrap(),
+			&AllocationDiff {
+				current: percent(1, 2),
+				target: percent(1, 2),
+				diff: FixedI128::zero(),
+				range_diff: FixedI128::zero(),
+				diff_amount: 0,
+			}
+		);
+	}
+
+	#[test]
+	fn over_allocation_is_positive() {
+		let current = BTreeMap::from([(ACA, current_allocation(800, 800, percent(4, 5)))]);
+		let targets = BTreeMap::from([(ACA, target(percent(1, 2), percent(2, 5), percent(3, 5)))]);
+
+		let diff = compute_diffs(&current, &targets, &unit_prices(&[ACA]), 1_000).unwrap();
+		assert_eq!(
+			diff.get(&ACA).unwrap(),
+			&AllocationDiff {
+				current: percent(4, 5),
+				target: percent(1, 2),
+				diff: FixedI128::saturating_from_rational(3, 10),
+				range_diff: FixedI128::saturating_from_rational(1, 5),
+				diff_amount: 300,
+			}
+		);
+	}
+
+	#[test]
+	fn under_allocation_is_negative() {
+		let current = BTreeMap::from([(ACA, current_allocation(200, 200, percent(1, 5)))]);
+		let targets = BTreeMap::from([(ACA, target(percent(1, 2), percent(2, 5), percent(3, 5)))]);
+
+		let diff = compute_diffs(&current, &targets, &unit_prices(&[ACA]), 1_000).unwrap();
+		assert_eq!(
+			diff.get(&ACA).unwrap(),
+			&AllocationDiff {
+				current: percent(1, 5),
+				target: percent(1, 2),
+				diff: FixedI128::saturating_from_rational(-3, 10),
+				range_diff: FixedI128::saturating_from_rational(-1, 5),
+				diff_amount: -300,
+			}
+		);
+	}
+
+	#[test]
+	fn within_range_has_zero_range_diff() {
+		let current = BTreeMap::from([(ACA, current_allocation(550, 550, percent(11, 20)))]);
+		let targets = BTreeMap::from([(ACA, target(percent(1, 2), percent(2, 5), percent(3, 5)))]);
+
+		let diff = compute_diffs(&current, &targets, &unit_prices(&[ACA]), 1_000).unwrap();
+		let aca_diff = diff.get(&ACA).unwrap();
+		assert_eq!(aca_diff.range_diff, FixedI128::zero());
+		assert_eq!(aca_diff.diff, FixedI128::saturating_from_rational(1, 20));
+		assert_eq!(aca_diff.diff_amount, 50);
+	}
+
+	#[test]
+	fn target_amount_uses_price() {
+		let current = BTreeMap::from([(ACA, current_allocation(100, 200, percent(1, 5)))]);
+		let targets = BTreeMap::from([(ACA, target(percent(1, 2), percent(2, 5), percent(3, 5)))]);
+		let prices = BTreeMap::from([(ACA, Price::saturating_from_integer(2))]);
+
+		// target_amount = 1_000 * 0.5 / 2 = 250
+		let diff = compute_diffs(&current, &targets, &prices, 1_000).unwrap();
+		assert_eq!(diff.get(&ACA).unwrap().diff_amount, -150);
+	}
+
+	#[test]
+	fn missing_current_allocation() {
+		let targets = BTreeMap::from([(DOT, target(percent(1, 2), percent(2, 5), percent(3, 5)))]);
+
+		let diff = compute_diffs(&BTreeMap::new(), &targets, &unit_prices(&[DOT]), 1_000).unwrap();
+		assert_eq!(
+			diff.get(&DOT).unwrap(),
+			&AllocationDiff {
+				current: FixedU128::zero(),
+				target: percent(1, 2),
+				diff: FixedI128::saturating_from_rational(-1, 2),
+				range_diff: FixedI128::saturating_from_rational(-2, 5),
+				diff_amount: -500,
+			}
+		);
+	}
+
+	#[test]
+	fn missing_target_allocation() {
+		let current = BTreeMap::from([(AUSD, current_allocation(300, 300, percent(3, 10)))]);
+
+		let diff = compute_diffs(&current, &BTreeMap::new(), &BTreeMap::new(), 1_000).unwrap();
+		assert_eq!(
+			diff.get(&AUSD).unwrap(),
+			&AllocationDiff {
+				current: percent(3, 10),
+				target: FixedU128::zero(),
+				diff: FixedI128::saturating_from_rational(3, 10),
+				range_diff: FixedI128::saturating_from_rational(3, 10),
+				diff_amount: 300,
+			}
+		);
+	}
+
+	#[test]
+	fn missing_or_zero_price_fails() {
+		let targets = BTreeMap::from([(ACA, target(percent(1, 2), percent(2, 5), percent(3, 5)))]);
+
+		assert_eq!(
+			compute_diffs(&BTreeMap::new(), &targets, &BTreeMap::new(), 1_000),
+			Err(ArithmeticError::DivisionByZero)
+		);
+		assert_eq!(
+			compute_diffs(
+				&BTreeMap::new(),
+				&targets,
+				&BTreeMap::from([(ACA, Price::zero())]),
+				1_000
+			),
+			Err(ArithmeticError::DivisionByZero)
+		);
+	}
+
+	#[test]
+	fn saturates_at_i128_bounds() {
+		let max = FixedU128::from_inner(u128::MAX);
+		let current = BTreeMap::from([
+			(ACA, current_allocation(u128::MAX, u128::MAX, max)),
+			(AUSD, current_allocation(0, 0, FixedU128::zero())),
+		]);
+		let targets = BTreeMap::from([
+			(ACA, target(FixedU128::zero(), FixedU128::zero(), FixedU128::zero())),
+			(AUSD, target(max, max, max)),
+			(DOT, target(max, max, max)),
+		]);
+		let prices = BTreeMap::from([(ACA, Price::one()), (AUSD, Price::one()), (DOT, Price::one())]);
+
+		let diff = compute_diffs(&current, &targets, &prices, u128::MAX).unwrap();
+
+		let aca_diff = diff.get(&ACA).unwrap();
+		assert_eq!(aca_diff.diff, FixedI128::from_inner(i128::MAX));
+		assert_eq!(aca_diff.range_diff, FixedI128::from_inner(i128::MAX));
+		assert_eq!(aca_diff.diff_amount, i128::MAX);
+
+		let ausd_diff = diff.get(&AUSD).unwrap();
+		assert_eq!(ausd_diff.diff, FixedI128::from_inner(-i128::MAX));
+		assert_eq!(ausd_diff.range_diff, FixedI128::from_inner(-i128::MAX));
+		assert_eq!(ausd_diff.diff_amount, -i128::MAX);
+
+		let dot_diff = diff.get(&DOT).unwrap();
+		assert_eq!(dot_diff.diff, FixedI128::from_inner(-i128::MAX));
+		assert_eq!(dot_diff.range_diff, FixedI128::from_inner(-i128::MAX));
+		assert_eq!(dot_diff.diff_amount, -i128::MAX);
+
+		let only_current = BTreeMap::from([(ACA, current_allocation(u128::MAX, u128::MAX, max))]);
+		let diff = compute_diffs(&only_current, &BTreeMap::new(), &BTreeMap::new(), u128::MAX).unwrap();
+		let aca_diff = diff.get(&ACA).unwrap();
+		assert_eq!(aca_diff.diff, FixedI128::from_inner(i128::MAX));
+		assert_eq!(aca_diff.diff_amount, i128::MAX);
+	}
+}
@@ -20,13 +20,22 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{log, pallet_prelude::*, require_transactional, traits::EnsureOrigin, transactional, PalletId};
+use frame_support::{
+	log, pallet_prelude::*, require_transactional, storage::with_transaction_result, traits::EnsureOrigin,
+	transactional, PalletId,
+};
 use frame_system::pallet_prelude::*;
 use sp_runtime::{
-	traits::{AccountIdConversion, SaturatedConversion, Saturating, UniqueSaturatedInto, Zero},
+	traits::{
+		AccountIdConversion, CheckedAdd, CheckedMul, One, SaturatedConversion, Saturating, UniqueSaturatedInto, Zero,
+	},
 	ArithmeticError, FixedI128, FixedPointNumber, FixedU128,
 };
-use sp_std::{collections::btree_map::BTreeMap, prelude::*, result::Result};
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	prelude::*,
+	result::Result,
+};
 
 use orml_traits::MultiCurrency;
 
@@ -36,21 +45,59 @@ use acala_primitives::{
 	TokenSymbol::{self, *},
 	TradingPair,
 };
-use module_support::{DEXManager, DEXPriceProvider, PriceProvider};
+use module_support::{DEXManager, DEXPriceProvider, PriceProvider, Rate, Ratio};
 
 pub use module::*;
 
+mod calc;
+pub mod migrations;
 mod mock;
 mod tests;
 
 mod weights;
 pub use weights::WeightInfo;
 
+/// The reason of protocol ADAO minting.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum MintReason {
+	/// Minted to add ADAO/AUSD liquidity on rebalance.
+	PolRebalance,
+	/// Minted to seed initial ADAO/AUSD liquidity.
+	PoolBootstrap,
+}
+
+/// The kind of a pending governance update.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum PendingUpdateKind {
+	TargetAllocations,
+	Strategies,
+}
+
 /// Allocation parameters.
-#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, Default, RuntimeDebug, TypeInfo)]
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub struct Allocation {
 	pub value: Balance,
 	pub range: Balance,
+	/// The ratio of the price the currency is valued at in allocation math, for instance to
+	/// discount illiquid assets. In `(0, 1]`.
+	pub haircut: Ratio,
+}
+
+impl Allocation {
+	/// Allocation without haircut.
+	pub fn new(value: Balance, range: Balance) -> Self {
+		Self {
+			value,
+			range,
+			haircut: Ratio::one(),
+		}
+	}
+}
+
+impl Default for Allocation {
+	fn default() -> Self {
+		Self::new(Zero::zero(), Zero::zero())
+	}
 }
 
 /// Allocation adjustment parameters.
@@ -93,19 +140,70 @@ pub struct Strategy {
 	pub percent_per_trade: FixedU128,
 	pub max_amount_per_trade: i128,
 	pub min_amount_per_trade: i128,
+	/// Skip the trade, instead of scaling it down, if it exceeds `MaxPoolImpactRatio`.
+	pub skip_on_thin_pool: bool,
 }
 
 impl Strategy {
 	fn trade_amount(&self, diff: i128, max: i128) -> i128 {
-		let diff_abs = diff.abs();
-		if (max <= self.min_amount_per_trade) || (diff_abs <= self.min_amount_per_trade) {
-			return Zero::zero();
-		}
-		let amount = self.percent_per_trade.saturating_mul_int(diff_abs);
-		i128::min(self.min_amount_per_trade.max(amount), self.max_amount_per_trade).min(max)
+		trade_amount(
+			self.percent_per_trade,
+			self.min_amount_per_trade,
+			self.max_amount_per_trade,
+			diff,
+			max,
+		)
+	}
+}
+
+/// Cumulative execution stats of a management strategy.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, Default, RuntimeDebug, TypeInfo)]
+pub struct StrategyExecutionStats {
+	/// Number of rebalances the strategy has added liquidity on.
+	pub times_executed: u32,
+	/// Total AUSD paired into liquidity.
+	pub total_stable_deployed: Balance,
+	/// Total LP tokens received.
+	pub total_lp_acquired: Balance,
+}
+
+/// The strategy to acquire a currency below its target range, by buying it from AUSD on DEX.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct AcquisitionStrategy {
+	pub percent_per_trade: FixedU128,
+	pub max_amount_per_trade: i128,
+	pub min_amount_per_trade: i128,
+}
+
+impl AcquisitionStrategy {
+	fn trade_amount(&self, diff: i128, max: i128) -> i128 {
+		trade_amount(
+			self.percent_per_trade,
+			self.min_amount_per_trade,
+			self.max_amount_per_trade,
+			diff,
+			max,
+		)
 	}
 }
 
+/// `percent_per_trade` of `diff`, bounded by `min_amount_per_trade` and `max_amount_per_trade`, and
+/// no more than `max`. Zero if `diff` or `max` is not above `min_amount_per_trade`.
+fn trade_amount(
+	percent_per_trade: FixedU128,
+	min_amount_per_trade: i128,
+	max_amount_per_trade: i128,
+	diff: i128,
+	max: i128,
+) -> i128 {
+	let diff_abs = diff.abs();
+	if (max <= min_amount_per_trade) || (diff_abs <= min_amount_per_trade) {
+		return Zero::zero();
+	}
+	let amount = percent_per_trade.saturating_mul_int(diff_abs);
+	i128::min(min_amount_per_trade.max(amount), max_amount_per_trade).min(max)
+}
+
 /// The management strategy kind.
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub enum StrategyKind {
@@ -113,6 +211,51 @@ pub enum StrategyKind {
 	LiquidityProvisionAusdAdao,
 	/// AUSD and other tokens LP.
 	LiquidityProvisionAusdOther(TokenSymbol),
+	/// AUSD and any other currency LP, including foreign assets.
+	LiquidityProvisionWith(CurrencyId),
+}
+
+impl StrategyKind {
+	/// The currency to provide liquidity with, paired with AUSD.
+	fn other_currency_id(&self) -> CurrencyId {
+		match self {
+			StrategyKind::LiquidityProvisionAusdAdao => Token(ADAO),
+			StrategyKind::LiquidityProvisionAusdOther(other) => Token(*other),
+			StrategyKind::LiquidityProvisionWith(other) => *other,
+		}
+	}
+
+	/// The trading pair to provide liquidity to.
+	fn trading_pair(&self) -> Option<TradingPair> {
+		TradingPair::from_currency_ids(Token(AUSD), self.other_currency_id())
+	}
+
+	/// The LP token of the trading pair to provide liquidity to.
+	fn lp_currency_id(&self) -> Option<CurrencyId> {
+		self.trading_pair().map(|trading_pair| trading_pair.dex_share_currency_id())
+	}
+}
+
+/// A liquidity provision trade planned by a strategy.
+#[derive(Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+struct PlannedTrade {
+	trading_pair: TradingPair,
+	/// The amount of non-stable currency to add.
+	other_amount: Balance,
+	/// The amount of stable currency to add.
+	stable_amount: Balance,
+}
+
+/// Trading pair status on DEX.
+pub trait DEXTradingPairStatus {
+	/// Returns `true` if liquidity can be added to `trading_pair`.
+	fn is_enabled(trading_pair: TradingPair) -> bool;
+}
+
+/// Decimals of currencies, including foreign assets.
+pub trait CurrencyDecimals {
+	/// Returns the decimals of `currency_id`, or `None` if unknown.
+	fn decimals(currency_id: CurrencyId) -> Option<u8>;
 }
 
 #[frame_support::pallet]
@@ -133,12 +276,21 @@ pub mod module {
 		/// Used for assets price except `ADAO`.
 		type AssetPriceProvider: PriceProvider<CurrencyId>;
 
+		/// The SDAO to ADAO exchange rate, used for `SDAO` price.
+		type StakedTokenRate: Get<Rate>;
+
 		/// Required origin for allocation and strategy params setting.
 		type UpdateOrigin: EnsureOrigin<Self::Origin>;
 
 		/// The DEX manager.
 		type DEX: DEXManager<Self::AccountId, CurrencyId, Balance>;
 
+		/// The DEX trading pair status.
+		type TradingPairStatus: DEXTradingPairStatus;
+
+		/// Decimals of foreign assets in target allocations.
+		type CurrencyDecimals: CurrencyDecimals;
+
 		/// The period of each rebalance.
 		#[pallet::constant]
 		type RebalancePeriod: Get<Self::BlockNumber>;
@@ -147,10 +299,43 @@ pub mod module {
 		#[pallet::constant]
 		type RebalanceOffset: Get<Self::BlockNumber>;
 
+		/// The maximum number of target allocations evaluated in one rebalance. If exceeded, a
+		/// rotating window of currencies is rebalanced each time, with the stable currency always
+		/// included.
+		#[pallet::constant]
+		type MaxAllocationsPerRebalance: Get<u32>;
+
+		/// The maximum number of targets in one `set_target_allocations` call.
+		#[pallet::constant]
+		type MaxTargetsPerCall: Get<u32>;
+
+		/// The delay of target allocations and strategies updates to take effect. Zero to apply
+		/// updates immediately.
+		#[pallet::constant]
+		type EnactmentDelay: Get<Self::BlockNumber>;
+
 		/// The DAO account.
 		#[pallet::constant]
 		type DaoAccount: Get<Self::AccountId>;
 
+		/// The minimum AUSD balance the DAO account retains, not to be used in rebalance.
+		#[pallet::constant]
+		type MinStableBuffer: Get<Balance>;
+
+		/// The maximum number of records kept in `PolMintHistory`. The oldest record is dropped if
+		/// exceeded.
+		#[pallet::constant]
+		type MaxPolMintHistory: Get<u32>;
+
+		/// Maximum ratio of pool reserves a single liquidity provision trade can add.
+		#[pallet::constant]
+		type MaxPoolImpactRatio: Get<Ratio>;
+
+		/// Number of rebalance periods a target allocation can stay at zero balance, before
+		/// `TargetUnserviced` is deposited.
+		#[pallet::constant]
+		type MaxUnservicedPeriods: Get<u32>;
+
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
 
@@ -167,17 +352,35 @@ pub mod module {
 		NoPrice,
 		/// Invalid trading pair for DEX.
 		InvalidTradingPair,
+		/// Cannot find strategy for a given index.
+		StrategyNotFound,
+		/// No pending update to cancel.
+		NoPendingUpdate,
+		/// Duplicate currency ID in input.
+		DuplicateCurrency,
+		/// Foreign asset without decimals info.
+		UnknownCurrencyDecimals,
+		/// Strategy not allowed, for instance trading SDAO.
+		InvalidStrategy,
+		/// Too many targets in one call.
+		TooManyTargets,
+		/// The LP token of a strategy has no target allocation.
+		StrategyWithoutAllocation,
+		/// Currency cannot be acquired from AUSD, for instance ADAO, SDAO or LP tokens.
+		InvalidAcquisition,
+		/// The pool already has liquidity.
+		PoolAlreadyBootstrapped,
+		/// Allocation haircut not in `(0, 1]`.
+		InvalidHaircut,
 	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(fn deposit_event)]
 	pub enum Event<T: Config> {
-		TargetAllocationSet {
-			currency_id: CurrencyId,
-			allocation: Allocation,
-		},
-		TargetAllocationRemoved {
-			currency_id: CurrencyId,
+		/// Target allocations of `set` currencies are set, and of `removed` currencies removed.
+		TargetAllocationsUpdated {
+			set: Vec<CurrencyId>,
+			removed: Vec<CurrencyId>,
 		},
 		TargetAllocationAdjusted {
 			currency_id: CurrencyId,
@@ -186,6 +389,83 @@ pub mod module {
 		StrategiesSet {
 			strategies: Vec<Strategy>,
 		},
+		/// Rebalance simulated, with `amounts` in `(other_amount, stable_amount)` of the trade.
+		RebalanceSimulated {
+			strategy: Strategy,
+			currency_id: CurrencyId,
+			amounts: (Balance, Balance),
+		},
+		/// `T::RebalancePeriod` or `T::RebalanceOffset` changed, rebalance schedule restarts from
+		/// block `since`.
+		RebalanceScheduleChanged {
+			period: T::BlockNumber,
+			offset: T::BlockNumber,
+			since: T::BlockNumber,
+		},
+		/// Rebalance skipped as the trading pair is not enabled on DEX.
+		TradingPairNotEnabled {
+			trading_pair: TradingPair,
+		},
+		/// Planned trade exceeded `T::MaxPoolImpactRatio` of pool reserves, and was scaled down to
+		/// `stable_amount`, or skipped if zero.
+		PoolImpactLimited {
+			trading_pair: TradingPair,
+			planned_stable_amount: Balance,
+			stable_amount: Balance,
+		},
+		/// An update is scheduled, to be applied at block `activate_at`.
+		PendingUpdateScheduled {
+			kind: PendingUpdateKind,
+			activate_at: T::BlockNumber,
+		},
+		PendingUpdateApplied {
+			kind: PendingUpdateKind,
+		},
+		PendingUpdateCancelled {
+			kind: PendingUpdateKind,
+		},
+		/// The target allocation of the LP token of `strategy` is removed, the strategy won't trade
+		/// until it is set again.
+		StrategyOrphaned {
+			strategy: Strategy,
+		},
+		/// ADAO/AUSD liquidity added on rebalance, with `adao_minted` ADAO and `ausd_paired` AUSD
+		/// from the DAO account, and `lp_received` LP token deposited into the DAO account.
+		Rebalanced {
+			adao_minted: Balance,
+			ausd_paired: Balance,
+			lp_received: Balance,
+		},
+		/// Acquisition strategy of `currency_id` is set, or removed if `None`.
+		AcquisitionStrategySet {
+			currency_id: CurrencyId,
+			strategy: Option<AcquisitionStrategy>,
+		},
+		/// `amount` of `currency_id` bought on DEX with `stable_amount` AUSD from the DAO account.
+		Acquired {
+			currency_id: CurrencyId,
+			stable_amount: Balance,
+			amount: Balance,
+		},
+		/// The DAO account has held zero `currency_id` for `periods` rebalance periods, despite its
+		/// target allocation. Deposited once when exceeding `T::MaxUnservicedPeriods`.
+		TargetUnserviced {
+			currency_id: CurrencyId,
+			periods: u32,
+		},
+		/// Initial liquidity of `trading_pair` added with `other_amount` of the non-stable currency
+		/// and `stable_amount` AUSD, and `lp_received` LP token deposited into the DAO account.
+		PoolBootstrapped {
+			trading_pair: TradingPair,
+			other_amount: Balance,
+			stable_amount: Balance,
+			lp_received: Balance,
+		},
+		/// Rebalance skipped as the pool of the trading pair has no liquidity yet, and needs
+		/// `bootstrap_pool` first.
+		PoolNotBootstrapped {
+			trading_pair: TradingPair,
+		},
 	}
 
 	/// Target allocation of a given currency ID.
@@ -206,38 +486,96 @@ pub mod module {
 	#[pallet::getter(fn strategies)]
 	pub type Strategies<T> = StorageValue<_, Vec<Strategy>, ValueQuery>;
 
+	/// Protocol owned liquidity positions, snapshot on rebalance.
+	/// PolPositionsSnapshot: value Vec<(TradingPair, Balance, Balance)>
+	#[pallet::storage]
+	#[pallet::getter(fn pol_positions_snapshot)]
+	pub type PolPositionsSnapshot<T> = StorageValue<_, Vec<(TradingPair, Balance, Balance)>, ValueQuery>;
+
+	/// The start position of next rebalance window, in non-stable target allocations.
+	/// RebalanceCursor: value u32
+	#[pallet::storage]
+	#[pallet::getter(fn rebalance_cursor)]
+	pub type RebalanceCursor<T> = StorageValue<_, u32, ValueQuery>;
+
+	/// The index of the strategy to run in next rebalance. If not set yet, it is initialized by
+	/// the number of periods since the rebalance schedule started, modulo `strategies.len()`.
+	/// NextStrategyCursor: value Option<u32>
+	#[pallet::storage]
+	#[pallet::getter(fn next_strategy_cursor)]
+	pub type NextStrategyCursor<T> = StorageValue<_, u32, OptionQuery>;
+
+	/// The rebalance schedule in effect, in `(period, offset, since)`. Rebalance happens when
+	/// `(now - since) % period == offset`. `since` is zero for the initial schedule, and the block a
+	/// change of `T::RebalancePeriod` or `T::RebalanceOffset` is detected at otherwise.
+	/// RebalanceSchedule: value Option<(BlockNumber, BlockNumber, BlockNumber)>
+	#[pallet::storage]
+	#[pallet::getter(fn rebalance_schedule)]
+	pub type RebalanceSchedule<T: Config> =
+		StorageValue<_, (T::BlockNumber, T::BlockNumber, T::BlockNumber), OptionQuery>;
+
+	/// Pending target allocations update, and the block to be applied at.
+	/// PendingTargetAllocations: value Option<(BlockNumber, Vec<(CurrencyId, Option<Allocation>)>)>
+	#[pallet::storage]
+	#[pallet::getter(fn pending_target_allocations)]
+	pub type PendingTargetAllocations<T: Config> =
+		StorageValue<_, (T::BlockNumber, Vec<(CurrencyId, Option<Allocation>)>), OptionQuery>;
+
+	/// Pending strategies update, and the block to be applied at.
+	/// PendingStrategies: value Option<(BlockNumber, Vec<Strategy>)>
+	#[pallet::storage]
+	#[pallet::getter(fn pending_strategies)]
+	pub type PendingStrategies<T: Config> = StorageValue<_, (T::BlockNumber, Vec<Strategy>), OptionQuery>;
+
+	/// Cumulative ADAO minted by this pallet, by reason.
+	/// MintLedger: map MintReason => Balance
+	#[pallet::storage]
+	#[pallet::getter(fn mint_ledger)]
+	pub type MintLedger<T> = StorageMap<_, Twox64Concat, MintReason, Balance, ValueQuery>;
+
+	/// Recent ADAO/AUSD rebalances, in `(block, adao_minted, ausd_paired, lp_received)`, oldest
+	/// first.
+	/// PolMintHistory: value BoundedVec<(BlockNumber, Balance, Balance, Balance)>
+	#[pallet::storage]
+	#[pallet::getter(fn pol_mint_history)]
+	pub type PolMintHistory<T: Config> =
+		StorageValue<_, BoundedVec<(T::BlockNumber, Balance, Balance, Balance), T::MaxPolMintHistory>, ValueQuery>;
+
+	/// Acquisition strategies of currencies, to buy them from AUSD when below target range.
+	/// AcquisitionStrategies: map CurrencyId => Option<AcquisitionStrategy>
+	#[pallet::storage]
+	#[pallet::getter(fn acquisition_strategies)]
+	pub type AcquisitionStrategies<T> = StorageMap<_, Twox64Concat, CurrencyId, AcquisitionStrategy, OptionQuery>;
+
+	/// Consecutive rebalance periods a target allocation has been at zero balance.
+	/// UnservicedPeriods: map CurrencyId => u32
+	#[pallet::storage]
+	#[pallet::getter(fn unserviced_periods)]
+	pub type UnservicedPeriods<T> = StorageMap<_, Twox64Concat, CurrencyId, u32, ValueQuery>;
+
+	/// Execution stats of management strategies, reset when the strategy is removed or replaced.
+	/// StrategyStats: map StrategyKind => StrategyExecutionStats
+	#[pallet::storage]
+	#[pallet::getter(fn strategy_stats)]
+	pub type StrategyStats<T> = StorageMap<_, Twox64Concat, StrategyKind, StrategyExecutionStats, ValueQuery>;
+
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
-		/// Rebalance periodically.
+		/// Apply due pending updates, and rebalance periodically.
 		fn on_initialize(now: T::BlockNumber) -> Weight {
-			// Checked arithmetic but not supported by `BlockNumber`. `T::RebalancePeriod`
-			// can't be zero in runtime config so it's safe.
-			if (now % T::RebalancePeriod::get()) == T::RebalanceOffset::get() {
-				let strategies = Strategies::<T>::get();
-				let index: u32 = (now / T::RebalancePeriod::get()).unique_saturated_into();
-				// Checked remainder to not panic
-				let strategy_index = index
-					.checked_rem(strategies.len().saturated_into::<u32>())
-					.unwrap_or_default();
-
-				if let Some(strategy) = strategies.get(strategy_index as usize) {
-					match Self::allocation_diff() {
-						Ok(diff) => {
-							if let Err(e) = Self::rebalance(strategy, diff) {
-								log::error!(target: "adao-manager", "Rebalance failed: {:?}", e);
-							}
-							return <T as Config>::WeightInfo::on_initialize_with_rebalance();
-						}
-						Err(e) => log::error!(target: "adao-manager", "Getting allocation diff failed: {:?}", e),
-					}
-				}
-			}
+			let pending_weight = Self::apply_pending_updates(now);
+			Self::do_rebalance(now).saturating_add(pending_weight)
+		}
 
-			<T as Config>::WeightInfo::on_initialize_without_rebalance()
+		fn on_runtime_upgrade() -> Weight {
+			migrations::v1::migrate::<T>().saturating_add(migrations::v2::migrate::<T>())
 		}
 
 		// Ensure `T::RebalancePeriod` is not zero
@@ -247,10 +585,18 @@ pub mod module {
 		}
 	}
 
+	// Call indices follow declaration order, and are pinned by the `call_indices_are_stable` test.
+	// Append new calls at the end, never insert or reorder.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Set target allocations. Target allocation percentages will be updated. Requires
 		/// `T::UpdateOrigin`.
+		///
+		/// Currency IDs in `targets` must be unique, and no more than `T::MaxTargetsPerCall` of them.
+		/// Currency IDs in the event are in the order of `targets`.
+		///
+		/// If `T::EnactmentDelay` is not zero, the update is scheduled and replaces any pending
+		/// target allocations update.
 		#[pallet::weight(<T as Config>::WeightInfo::set_target_allocations(targets.len().saturated_into()))]
 		#[transactional]
 		pub fn set_target_allocations(
@@ -258,23 +604,44 @@ pub mod module {
 			targets: Vec<(CurrencyId, Option<Allocation>)>,
 		) -> DispatchResult {
 			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(
+				targets.len() <= T::MaxTargetsPerCall::get() as usize,
+				Error::<T>::TooManyTargets
+			);
 
-			TargetAllocations::<T>::mutate(|allocations| {
-				targets.into_iter().for_each(|(currency_id, maybe_allocation)| {
-					if let Some(allocation) = maybe_allocation {
-						allocations.insert(currency_id, allocation);
-						Self::deposit_event(Event::<T>::TargetAllocationSet {
-							currency_id,
-							allocation,
-						});
-					} else {
-						allocations.remove(&currency_id);
-						Self::deposit_event(Event::<T>::TargetAllocationRemoved { currency_id });
-					}
-				});
-			});
+			let mut currency_ids = BTreeSet::new();
+			ensure!(
+				targets.iter().all(|(currency_id, _)| currency_ids.insert(*currency_id)),
+				Error::<T>::DuplicateCurrency
+			);
+			ensure!(
+				targets.iter().all(|(currency_id, maybe_allocation)| {
+					!matches!(currency_id, CurrencyId::ForeignAsset(_))
+						|| maybe_allocation.is_none()
+						|| T::CurrencyDecimals::decimals(*currency_id).is_some()
+				}),
+				Error::<T>::UnknownCurrencyDecimals
+			);
+			ensure!(
+				targets
+					.iter()
+					.filter_map(|(_, maybe_allocation)| maybe_allocation.as_ref())
+					.all(|allocation| !allocation.haircut.is_zero() && allocation.haircut <= Ratio::one()),
+				Error::<T>::InvalidHaircut
+			);
 
-			Self::update_target_allocation_percents()
+			let delay = T::EnactmentDelay::get();
+			if delay.is_zero() {
+				return Self::do_set_target_allocations(targets);
+			}
+
+			let activate_at = frame_system::Pallet::<T>::block_number().saturating_add(delay);
+			PendingTargetAllocations::<T>::put((activate_at, targets));
+			Self::deposit_event(Event::<T>::PendingUpdateScheduled {
+				kind: PendingUpdateKind::TargetAllocations,
+				activate_at,
+			});
+			Ok(())
 		}
 
 		/// Make adjustments to target allocations. Will update target allocation percentages.
@@ -317,13 +684,181 @@ pub mod module {
 		}
 
 		/// Set management strategies.
+		///
+		/// If `T::EnactmentDelay` is not zero, the update is scheduled and replaces any pending
+		/// strategies update.
 		#[pallet::weight(<T as Config>::WeightInfo::set_strategies())]
 		#[transactional]
 		pub fn set_strategies(origin: OriginFor<T>, strategies: Vec<Strategy>) -> DispatchResult {
 			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(
+				strategies
+					.iter()
+					.all(|strategy| strategy.kind.other_currency_id() != Token(SDAO)),
+				Error::<T>::InvalidStrategy
+			);
+			ensure!(
+				strategies.iter().all(Self::has_target_allocation),
+				Error::<T>::StrategyWithoutAllocation
+			);
 
-			Strategies::<T>::set(strategies.clone());
-			Self::deposit_event(Event::<T>::StrategiesSet { strategies });
+			let delay = T::EnactmentDelay::get();
+			if delay.is_zero() {
+				Self::do_set_strategies(strategies);
+				return Ok(());
+			}
+
+			let activate_at = frame_system::Pallet::<T>::block_number().saturating_add(delay);
+			PendingStrategies::<T>::put((activate_at, strategies));
+			Self::deposit_event(Event::<T>::PendingUpdateScheduled {
+				kind: PendingUpdateKind::Strategies,
+				activate_at,
+			});
+			Ok(())
+		}
+
+		/// Cancel all pending target allocations and strategies updates. Requires
+		/// `T::UpdateOrigin`.
+		#[pallet::weight(<T as Config>::WeightInfo::cancel_pending_update())]
+		#[transactional]
+		pub fn cancel_pending_update(origin: OriginFor<T>) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+
+			let target_allocations = PendingTargetAllocations::<T>::take();
+			let strategies = PendingStrategies::<T>::take();
+			ensure!(
+				target_allocations.is_some() || strategies.is_some(),
+				Error::<T>::NoPendingUpdate
+			);
+
+			if target_allocations.is_some() {
+				Self::deposit_event(Event::<T>::PendingUpdateCancelled {
+					kind: PendingUpdateKind::TargetAllocations,
+				});
+			}
+			if strategies.is_some() {
+				Self::deposit_event(Event::<T>::PendingUpdateCancelled {
+					kind: PendingUpdateKind::Strategies,
+				});
+			}
+			Ok(())
+		}
+
+		/// Simulate a rebalance with the strategy of `strategy_index`, based on current state and the
+		/// next rebalance window. No trade would be made, result is deposited as `RebalanceSimulated`
+		/// event only. Requires `T::UpdateOrigin`.
+		///
+		/// Weighted as a rebalance over a full window.
+		#[pallet::weight(<T as Config>::WeightInfo::on_initialize_with_rebalance(
+			T::MaxAllocationsPerRebalance::get()
+		))]
+		pub fn simulate_rebalance(origin: OriginFor<T>, strategy_index: u32) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+
+			let strategy = Self::strategies()
+				.get(strategy_index as usize)
+				.copied()
+				.ok_or(Error::<T>::StrategyNotFound)?;
+			let trading_pair = strategy.kind.trading_pair().ok_or(Error::<T>::InvalidTradingPair)?;
+			let diff = Self::allocation_diff_in(&Self::rebalance_window())?;
+			let amounts = Self::executable_trade(&strategy, &diff)?
+				.map(|trade| (trade.other_amount, trade.stable_amount))
+				.unwrap_or_default();
+
+			Self::deposit_event(Event::<T>::RebalanceSimulated {
+				strategy,
+				currency_id: trading_pair.dex_share_currency_id(),
+				amounts,
+			});
+			Ok(())
+		}
+
+		/// Set the acquisition strategy of `currency_id`, or remove it if `None`. Requires
+		/// `T::UpdateOrigin`.
+		///
+		/// On rebalance, a currency below its target range is bought from AUSD on DEX, sized by
+		/// `strategy`, unless it's the LP token of a management strategy.
+		#[pallet::weight(<T as Config>::WeightInfo::set_acquisition_strategy())]
+		#[transactional]
+		pub fn set_acquisition_strategy(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			strategy: Option<AcquisitionStrategy>,
+		) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(
+				currency_id != Token(ADAO)
+					&& currency_id != Token(SDAO)
+					&& currency_id != T::StableCurrencyId::get()
+					&& !currency_id.is_dex_share_currency_id(),
+				Error::<T>::InvalidAcquisition
+			);
+
+			AcquisitionStrategies::<T>::set(currency_id, strategy);
+			Self::deposit_event(Event::<T>::AcquisitionStrategySet { currency_id, strategy });
+			Ok(())
+		}
+
+		/// Seed the empty AUSD/`currency_id` pool with `other_amount` of `currency_id` and
+		/// `stable_amount` AUSD from the DAO account, setting its initial price. ADAO is minted.
+		/// Requires `T::UpdateOrigin`.
+		///
+		/// Rebalance doesn't add liquidity to a pool until it's bootstrapped.
+		#[pallet::weight(<T as Config>::WeightInfo::bootstrap_pool())]
+		#[transactional]
+		pub fn bootstrap_pool(
+			origin: OriginFor<T>,
+			currency_id: CurrencyId,
+			other_amount: Balance,
+			stable_amount: Balance,
+		) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(currency_id != Token(SDAO), Error::<T>::InvalidTradingPair);
+			let trading_pair =
+				TradingPair::from_currency_ids(Token(AUSD), currency_id).ok_or(Error::<T>::InvalidTradingPair)?;
+			ensure!(
+				!Self::is_pool_bootstrapped(trading_pair),
+				Error::<T>::PoolAlreadyBootstrapped
+			);
+
+			let lp = trading_pair.dex_share_currency_id();
+			let dao_account = T::DaoAccount::get();
+			let lp_received = if currency_id == Token(ADAO) {
+				let pallet_account = Self::account_id();
+				Self::mint_adao(MintReason::PoolBootstrap, &pallet_account, other_amount)?;
+				T::Currency::transfer(Token(AUSD), &dao_account, &pallet_account, stable_amount)?;
+				T::DEX::add_liquidity(
+					&pallet_account,
+					Token(ADAO),
+					Token(AUSD),
+					other_amount,
+					stable_amount,
+					Zero::zero(),
+					false,
+				)?;
+				let lp_share = T::Currency::free_balance(lp, &pallet_account);
+				T::Currency::transfer(lp, &pallet_account, &dao_account, lp_share)?;
+				lp_share
+			} else {
+				let lp_before = T::Currency::free_balance(lp, &dao_account);
+				T::DEX::add_liquidity(
+					&dao_account,
+					currency_id,
+					Token(AUSD),
+					other_amount,
+					stable_amount,
+					Zero::zero(),
+					false,
+				)?;
+				T::Currency::free_balance(lp, &dao_account).saturating_sub(lp_before)
+			};
+
+			Self::deposit_event(Event::<T>::PoolBootstrapped {
+				trading_pair,
+				other_amount,
+				stable_amount,
+				lp_received,
+			});
 			Ok(())
 		}
 	}
@@ -334,181 +869,392 @@ impl<T: Config> Pallet<T> {
 		T::PalletId::get().into_account()
 	}
 
+	/// Rebalance with the strategy of current period, if `now` is a rebalance block.
+	fn do_rebalance(now: T::BlockNumber) -> Weight {
+		let period = T::RebalancePeriod::get();
+		let elapsed = now.saturating_sub(Self::rebalance_schedule_since(now));
+		// Checked arithmetic but not supported by `BlockNumber`. `T::RebalancePeriod`
+		// can't be zero in runtime config so it's safe.
+		if (elapsed % period) == T::RebalanceOffset::get() {
+			let strategies = Strategies::<T>::get();
+			let len: u32 = strategies.len().saturated_into();
+			let index = Self::next_strategy_cursor().unwrap_or_else(|| (elapsed / period).unique_saturated_into());
+			// Checked remainder to not panic
+			let strategy_index = index.checked_rem(len).unwrap_or_default();
+
+			let strategy = strategies.get(strategy_index as usize);
+			// acquisition strategies are consulted on every rebalance, even without management strategies
+			if strategy.is_some() || AcquisitionStrategies::<T>::iter_keys().next().is_some() {
+				let window = Self::rebalance_window();
+				let n = window.len().saturated_into();
+				match Self::allocation_diff_in(&window) {
+					Ok(diff) => {
+						if let Some(strategy) = strategy {
+							match Self::rebalance(strategy, diff.clone()) {
+								Ok(Some((stable_deployed, lp_acquired))) => {
+									StrategyStats::<T>::mutate(strategy.kind, |stats| {
+										stats.times_executed = stats.times_executed.saturating_add(1);
+										stats.total_stable_deployed =
+											stats.total_stable_deployed.saturating_add(stable_deployed);
+										stats.total_lp_acquired = stats.total_lp_acquired.saturating_add(lp_acquired);
+									});
+								}
+								Ok(None) => {}
+								Err(e) => log::error!(target: "adao-manager", "Rebalance failed: {:?}", e),
+							}
+							NextStrategyCursor::<T>::put(strategy_index.saturating_add(1) % len);
+						}
+						Self::acquire(&diff);
+						Self::track_unserviced(&diff);
+						Self::advance_rebalance_cursor(&window);
+						PolPositionsSnapshot::<T>::put(Self::pol_positions());
+						return <T as Config>::WeightInfo::on_initialize_with_rebalance(n);
+					}
+					Err(e) => log::error!(target: "adao-manager", "Getting allocation diff failed: {:?}", e),
+				}
+			}
+		}
+
+		<T as Config>::WeightInfo::on_initialize_without_rebalance()
+	}
+
+	/// The block the rebalance schedule is relative to. If `T::RebalancePeriod` or
+	/// `T::RebalanceOffset` changed, the schedule restarts from `now`, so the transition doesn't
+	/// run or skip a rebalance due to the discontinuous `now % period`.
+	fn rebalance_schedule_since(now: T::BlockNumber) -> T::BlockNumber {
+		let period = T::RebalancePeriod::get();
+		let offset = T::RebalanceOffset::get();
+		match Self::rebalance_schedule() {
+			Some((p, o, since)) if p == period && o == offset => since,
+			Some(_) => {
+				RebalanceSchedule::<T>::put((period, offset, now));
+				Self::deposit_event(Event::<T>::RebalanceScheduleChanged {
+					period,
+					offset,
+					since: now,
+				});
+				now
+			}
+			None => {
+				// aligned to block zero, as before the schedule is stored
+				RebalanceSchedule::<T>::put((period, offset, T::BlockNumber::zero()));
+				Zero::zero()
+			}
+		}
+	}
+
+	fn do_set_target_allocations(targets: Vec<(CurrencyId, Option<Allocation>)>) -> DispatchResult {
+		let mut set = vec![];
+		let mut removed = vec![];
+		TargetAllocations::<T>::mutate(|allocations| {
+			targets.into_iter().for_each(|(currency_id, maybe_allocation)| {
+				if let Some(allocation) = maybe_allocation {
+					allocations.insert(currency_id, allocation);
+					set.push(currency_id);
+				} else {
+					allocations.remove(&currency_id);
+					removed.push(currency_id);
+				}
+			});
+		});
+		Self::strategies()
+			.into_iter()
+			.filter(|strategy| strategy.kind.lp_currency_id().map_or(false, |lp| removed.contains(&lp)))
+			.for_each(|strategy| Self::deposit_event(Event::<T>::StrategyOrphaned { strategy }));
+		removed
+			.iter()
+			.for_each(|currency_id| UnservicedPeriods::<T>::remove(currency_id));
+		Self::deposit_event(Event::<T>::TargetAllocationsUpdated { set, removed });
+
+		Self::update_target_allocation_percents()
+	}
+
+	/// Returns `true` if the LP token of `strategy` has a target allocation, either set or pending.
+	fn has_target_allocation(strategy: &Strategy) -> bool {
+		let lp = match strategy.kind.lp_currency_id() {
+			Some(lp) => lp,
+			None => return false,
+		};
+		Self::target_allocations().contains_key(&lp)
+			|| Self::pending_target_allocations().map_or(false, |(_, targets)| {
+				targets
+					.iter()
+					.any(|(currency_id, maybe_allocation)| *currency_id == lp && maybe_allocation.is_some())
+			})
+	}
+
+	fn do_set_strategies(strategies: Vec<Strategy>) {
+		// keep the next strategy to run, if it's still in the new strategies
+		let next = Self::next_strategy_cursor().and_then(|cursor| Self::strategies().get(cursor as usize).copied());
+		if let Some(position) = next.and_then(|next| strategies.iter().position(|s| *s == next)) {
+			NextStrategyCursor::<T>::put(position as u32);
+		}
+		// stats of removed or replaced strategies don't apply to the new ones
+		Self::strategies()
+			.into_iter()
+			.filter(|strategy| !strategies.contains(strategy))
+			.for_each(|strategy| StrategyStats::<T>::remove(strategy.kind));
+		Strategies::<T>::set(strategies.clone());
+		Self::deposit_event(Event::<T>::StrategiesSet { strategies });
+	}
+
+	/// Apply pending updates that are due at `now`.
+	fn apply_pending_updates(now: T::BlockNumber) -> Weight {
+		let mut applied = false;
+
+		if let Some((activate_at, targets)) = Self::pending_target_allocations() {
+			if activate_at <= now {
+				PendingTargetAllocations::<T>::kill();
+				applied = true;
+				match with_transaction_result(|| Self::do_set_target_allocations(targets)) {
+					Ok(()) => Self::deposit_event(Event::<T>::PendingUpdateApplied {
+						kind: PendingUpdateKind::TargetAllocations,
+					}),
+					Err(e) => log::error!(target: "adao-manager", "Applying target allocations failed: {:?}", e),
+				}
+			}
+		}
+
+		if let Some((activate_at, strategies)) = Self::pending_strategies() {
+			if activate_at <= now {
+				PendingStrategies::<T>::kill();
+				applied = true;
+				Self::do_set_strategies(strategies);
+				Self::deposit_event(Event::<T>::PendingUpdateApplied {
+					kind: PendingUpdateKind::Strategies,
+				});
+			}
+		}
+
+		if applied {
+			<T as Config>::WeightInfo::apply_pending_updates()
+		} else {
+			T::DbWeight::get().reads(2)
+		}
+	}
+
+	/// Mint ADAO to `to` and record it in the mint ledger. All protocol ADAO mints must go through
+	/// here.
+	fn mint_adao(reason: MintReason, to: &T::AccountId, amount: Balance) -> DispatchResult {
+		T::Currency::deposit(Token(ADAO), to, amount)?;
+		MintLedger::<T>::mutate(reason, |total| *total = total.saturating_add(amount));
+		Ok(())
+	}
+
+	/// Total ADAO minted by this pallet.
+	pub fn total_minted() -> Balance {
+		MintLedger::<T>::iter_values().fold(Zero::zero(), |acc: Balance, x| acc.saturating_add(x))
+	}
+
+	/// Portfolio drift from target allocations, in `Σ |range_diff| * target` over all allocations.
+	/// Zero if all current allocations are within range.
+	pub fn drift_score() -> Result<FixedU128, DispatchError> {
+		Self::allocation_diff()?.values().try_fold(FixedU128::zero(), |acc, diff| {
+			FixedU128::from_inner(diff.range_diff.into_inner().abs() as u128)
+				.checked_mul(&diff.target)
+				.and_then(|weighted| acc.checked_add(&weighted))
+				.ok_or_else(|| ArithmeticError::Overflow.into())
+		})
+	}
+
 	fn price(currency_id: CurrencyId) -> Result<FixedU128, DispatchError> {
 		if currency_id == Token(ADAO) {
 			T::AdaoPriceProvider::get_relative_price(Token(ADAO), T::StableCurrencyId::get())
 				.ok_or_else(|| Error::<T>::NoPrice.into())
+		} else if currency_id == Token(SDAO) {
+			// SDAO price = ADAO price * exchange rate
+			Self::price(Token(ADAO))?
+				.checked_mul(&T::StakedTokenRate::get())
+				.ok_or_else(|| ArithmeticError::Overflow.into())
 		} else {
 			T::AssetPriceProvider::get_relative_price(currency_id, T::StableCurrencyId::get())
 				.ok_or_else(|| Error::<T>::NoPrice.into())
 		}
 	}
 
+	/// Price of `currency_id` discounted by the haircut of its target allocation in `targets`.
+	fn valuation_price(
+		currency_id: CurrencyId,
+		targets: &BTreeMap<CurrencyId, Allocation>,
+	) -> Result<FixedU128, DispatchError> {
+		let haircut = targets.get(&currency_id).map_or_else(Ratio::one, |allocation| allocation.haircut);
+		Ok(Self::price(currency_id)?.saturating_mul(haircut))
+	}
+
+	/// Protocol owned liquidity positions of target allocations, in `(trading_pair,
+	/// first_amount, second_amount)`.
+	///
+	/// The DAO account's LP balance is decomposed into underlying amounts by pool reserves and
+	/// LP issuance.
+	pub fn pol_positions() -> Vec<(TradingPair, Balance, Balance)> {
+		Self::target_allocations()
+			.keys()
+			.filter_map(|currency_id| {
+				let (a, b) = currency_id.split_dex_share_currency_id()?;
+				let trading_pair = TradingPair::from_currency_ids(a, b)?;
+				let lp = trading_pair.dex_share_currency_id();
+
+				let lp_balance = T::Currency::total_balance(lp, &T::DaoAccount::get());
+				let lp_issuance = T::Currency::total_issuance(lp);
+				let share = FixedU128::checked_from_rational(lp_balance, lp_issuance).unwrap_or_default();
+				let (reserve_0, reserve_1) = T::DEX::get_liquidity_pool(trading_pair.first(), trading_pair.second());
+
+				Some((
+					trading_pair,
+					share.saturating_mul_int(reserve_0),
+					share.saturating_mul_int(reserve_1),
+				))
+			})
+			.collect()
+	}
+
 	fn update_target_allocation_percents() -> DispatchResult {
-		let target_total = Self::target_allocations()
-			.values()
-			.fold(Zero::zero(), |acc: u128, allocation| {
-				acc.saturating_add(allocation.value)
-			});
+		let percents = Self::allocation_percents(Self::target_allocations())?;
+		TargetAllocationPercents::<T>::mutate(|allocation_percents| allocation_percents.extend(percents));
+
+		Ok(())
+	}
+
+	/// Allocation percentages of `allocations`, relative to their total value.
+	fn allocation_percents(
+		allocations: BTreeMap<CurrencyId, Allocation>,
+	) -> Result<BTreeMap<CurrencyId, AllocationPercent>, DispatchError> {
+		let target_total = allocations.values().fold(Zero::zero(), |acc: u128, allocation| {
+			acc.saturating_add(allocation.value)
+		});
 		if target_total.is_zero() {
 			return Err(Error::<T>::ZeroTargetAllocation.into());
 		}
 
-		TargetAllocationPercents::<T>::mutate(|allocation_percents| {
-			Self::target_allocations()
-				.into_iter()
-				.for_each(|(currency_id, allocation)| {
-					// Checked that total value is not zero above, qed.
-					let percent = FixedU128::saturating_from_rational(allocation.value, target_total);
-					let min = FixedU128::saturating_from_rational(
-						allocation.value.saturating_sub(allocation.range),
-						target_total,
-					);
-					let max = FixedU128::saturating_from_rational(
-						allocation.value.saturating_add(allocation.range),
-						target_total,
-					);
-					allocation_percents.insert(
-						currency_id,
-						AllocationPercent {
-							value: percent,
-							min,
-							max,
-						},
-					);
-				});
-		});
+		Ok(allocations
+			.into_iter()
+			.map(|(currency_id, allocation)| {
+				// Checked that total value is not zero above, qed.
+				let percent = FixedU128::saturating_from_rational(allocation.value, target_total);
+				let min = FixedU128::saturating_from_rational(
+					allocation.value.saturating_sub(allocation.range),
+					target_total,
+				);
+				let max = FixedU128::saturating_from_rational(
+					allocation.value.saturating_add(allocation.range),
+					target_total,
+				);
+				(
+					currency_id,
+					AllocationPercent {
+						value: percent,
+						min,
+						max,
+					},
+				)
+			})
+			.collect())
+	}
 
-		Ok(())
+	/// Currencies to be evaluated in next rebalance. All target allocations if not exceeding
+	/// `T::MaxAllocationsPerRebalance`, otherwise a window of target currencies other than the
+	/// stable currency starting from `RebalanceCursor`, and the stable currency if it's a target.
+	pub fn rebalance_window() -> Vec<CurrencyId> {
+		let targets = Self::target_allocations();
+		let max = T::MaxAllocationsPerRebalance::get() as usize;
+		if targets.len() <= max {
+			return targets.into_keys().collect();
+		}
+
+		let stable = T::StableCurrencyId::get();
+		let stable_is_target = targets.contains_key(&stable);
+		let others = Self::rotating_targets();
+		if others.is_empty() {
+			return targets.into_keys().collect();
+		}
+		// `others` is longer than `size`, as targets exceed `max`
+		let size = if stable_is_target { max.saturating_sub(1) } else { max }.max(1);
+		let cursor = (Self::rebalance_cursor() as usize) % others.len();
+		let mut window: Vec<CurrencyId> = others.iter().cycle().skip(cursor).take(size).copied().collect();
+		if stable_is_target {
+			window.push(stable);
+		}
+		window
 	}
 
-	fn allocation_diff() -> Result<BTreeMap<CurrencyId, AllocationDiff>, DispatchError> {
-		let (current_allocations, total_value) = Self::current_allocations()?;
-		let target_allocation_percents = Self::target_allocation_percents();
-
-		let mut diff = BTreeMap::new();
-
-		for (currency_id, target_percent) in target_allocation_percents.iter() {
-			let target_value = target_percent.value.saturating_mul_int(total_value);
-			let price = Self::price(*currency_id)?;
-			let target_amount = price
-				.reciprocal()
-				.ok_or(ArithmeticError::DivisionByZero)?
-				.saturating_mul_int(target_value);
-
-			if let Some(current) = current_allocations.get(currency_id) {
-				let range_diff = if current.percent < target_percent.min {
-					// current.percent - target.minPercent
-					let mut d_inner: i128 = target_percent
-						.min
-						.saturating_sub(current.percent)
-						.into_inner()
-						.unique_saturated_into();
-					d_inner = d_inner.saturating_mul(-1);
-					FixedI128::from_inner(d_inner)
-				} else if current.percent > target_percent.max {
-					// current.percent - target.maxPercent
-					let d_inner: i128 = current
-						.percent
-						.saturating_sub(target_percent.max)
-						.into_inner()
-						.unique_saturated_into();
-					FixedI128::from_inner(d_inner)
-				} else {
-					FixedI128::zero()
-				};
-
-				// diff = current.percent - target_percent.value
-				let diff_percent = if current.percent > target_percent.value {
-					let d_inner: i128 = current
-						.percent
-						.saturating_sub(target_percent.value)
-						.into_inner()
-						.unique_saturated_into();
-					FixedI128::from_inner(d_inner)
-				} else {
-					let mut d_inner: i128 = target_percent
-						.value
-						.saturating_sub(current.percent)
-						.into_inner()
-						.unique_saturated_into();
-					d_inner = d_inner.saturating_mul(-1);
-					FixedI128::from_inner(d_inner)
-				};
-
-				// diff_amount = current.amount - target_amount
-				let diff_amount = if current.amount > target_amount {
-					current.amount.saturating_sub(target_amount).unique_saturated_into()
-				} else {
-					let a: i128 = target_amount.saturating_sub(current.amount).unique_saturated_into();
-					a.saturating_mul(-1)
-				};
-
-				diff.insert(
-					*currency_id,
-					AllocationDiff {
-						current: current.percent,
-						target: target_percent.value,
-						diff: diff_percent,
-						range_diff,
-						diff_amount,
-					},
-				);
-			} else {
-				// diff_percent = -target_percent.value
-				let diff_percent = {
-					let d_inner: i128 = target_percent.value.into_inner().unique_saturated_into();
-					FixedI128::from_inner(d_inner.saturating_mul(-1))
-				};
-				// range_diff = -target_percent.min
-				let range_diff = {
-					let d_inner: i128 = target_percent.min.into_inner().unique_saturated_into();
-					FixedI128::from_inner(d_inner.saturating_mul(-1))
-				};
-				// diff_amount = -target_amount
-				let diff_amount = {
-					let a: i128 = target_amount.unique_saturated_into();
-					a.saturating_mul(-1)
-				};
-				diff.insert(
-					*currency_id,
-					AllocationDiff {
-						current: FixedU128::zero(),
-						target: target_percent.value,
-						diff: diff_percent,
-						range_diff,
-						diff_amount,
-					},
-				);
-			}
+	/// Target currencies other than the stable currency, which the rebalance window rotates over.
+	fn rotating_targets() -> Vec<CurrencyId> {
+		let stable = T::StableCurrencyId::get();
+		Self::target_allocations()
+			.into_keys()
+			.filter(|currency_id| *currency_id != stable)
+			.collect()
+	}
+
+	/// Move `RebalanceCursor` forward past the rotating currencies evaluated in `window`.
+	fn advance_rebalance_cursor(window: &[CurrencyId]) {
+		if Self::target_allocations().len() <= T::MaxAllocationsPerRebalance::get() as usize {
+			return;
 		}
+		let others = Self::rotating_targets().len() as u32;
+		if others.is_zero() {
+			return;
+		}
+		let stable = T::StableCurrencyId::get();
+		let step = window.iter().filter(|currency_id| **currency_id != stable).count() as u32;
+		RebalanceCursor::<T>::mutate(|cursor| *cursor = cursor.saturating_add(step) % others);
+	}
 
-		for (currency_id, current) in current_allocations.into_iter() {
-			if !target_allocation_percents.contains_key(&currency_id) {
-				diff.insert(
-					currency_id,
-					AllocationDiff {
-						current: current.percent,
-						target: FixedU128::zero(),
-						diff: FixedI128::from_inner(current.percent.into_inner().unique_saturated_into()),
-						range_diff: FixedI128::from_inner(current.percent.into_inner().unique_saturated_into()),
-						diff_amount: current.amount.unique_saturated_into(),
-					},
-				);
-			}
+	/// Allocation diff of the currencies in `window`, relative to each other.
+	fn allocation_diff_in(window: &[CurrencyId]) -> Result<BTreeMap<CurrencyId, AllocationDiff>, DispatchError> {
+		if window.len() == Self::target_allocations().len() {
+			return Self::allocation_diff();
 		}
 
-		Ok(diff)
+		let window_targets = Self::target_allocations()
+			.into_iter()
+			.filter(|(currency_id, _)| window.contains(currency_id))
+			.collect();
+		let target_allocation_percents = Self::allocation_percents(window_targets)?;
+		Self::compute_allocation_diff(target_allocation_percents)
+	}
+
+	fn allocation_diff() -> Result<BTreeMap<CurrencyId, AllocationDiff>, DispatchError> {
+		Self::compute_allocation_diff(Self::target_allocation_percents())
+	}
+
+	fn compute_allocation_diff(
+		target_allocation_percents: BTreeMap<CurrencyId, AllocationPercent>,
+	) -> Result<BTreeMap<CurrencyId, AllocationDiff>, DispatchError> {
+		let (current_allocations, total_value) = Self::current_allocations_of(target_allocation_percents.keys())?;
+
+		let targets = Self::target_allocations();
+		let mut prices = BTreeMap::new();
+		for currency_id in target_allocation_percents.keys() {
+			prices.insert(*currency_id, Self::valuation_price(*currency_id, &targets)?);
+		}
+
+		calc::compute_diffs(
+			&current_allocations,
+			&target_allocation_percents,
+			&prices,
+			total_value,
+		)
+		.map_err(Into::into)
 	}
 
 	// Returns `(current_allocations, current_total_value)` if Ok.
 	fn current_allocations() -> Result<(BTreeMap<CurrencyId, CurrentAllocation>, Balance), DispatchError> {
+		Self::current_allocations_of(Self::target_allocations().keys())
+	}
+
+	// Returns `(current_allocations, current_total_value)` of `currency_ids` if Ok.
+	fn current_allocations_of<'a>(
+		currency_ids: impl Iterator<Item = &'a CurrencyId>,
+	) -> Result<(BTreeMap<CurrencyId, CurrentAllocation>, Balance), DispatchError> {
+		let targets = Self::target_allocations();
 		let mut total_value: Balance = Zero::zero();
 		let mut allocations: BTreeMap<CurrencyId, CurrentAllocation> = BTreeMap::new();
-		for currency_id in Self::target_allocations().keys().cloned().filter(|currency_id| {
-			(*currency_id != Token(TokenSymbol::ADAO)) && (*currency_id != Token(TokenSymbol::SDAO))
-		}) {
-			let price = Self::price(currency_id)?;
+		for currency_id in currency_ids
+			.cloned()
+			.filter(|currency_id| *currency_id != Token(TokenSymbol::ADAO))
+		{
+			let price = Self::valuation_price(currency_id, &targets)?;
 			let amount = T::Currency::total_balance(currency_id, &T::DaoAccount::get());
 			let value = price.saturating_mul_int(amount);
 			total_value = total_value.saturating_add(value);
@@ -535,100 +1281,330 @@ impl<T: Config> Pallet<T> {
 		Ok((allocations, total_value))
 	}
 
+	/// Returns `true` if `trading_pair` is enabled, or deposits `TradingPairNotEnabled` event
+	/// and returns `false`.
+	fn ensure_trading_pair_enabled(trading_pair: TradingPair) -> bool {
+		let enabled = T::TradingPairStatus::is_enabled(trading_pair);
+		if !enabled {
+			Self::deposit_event(Event::<T>::TradingPairNotEnabled { trading_pair });
+		}
+		enabled
+	}
+
+	/// Returns `true` if the pool of `trading_pair` has liquidity.
+	fn is_pool_bootstrapped(trading_pair: TradingPair) -> bool {
+		let (reserve_0, reserve_1) = T::DEX::get_liquidity_pool(trading_pair.first(), trading_pair.second());
+		!reserve_0.is_zero() && !reserve_1.is_zero()
+	}
+
+	/// Returns `true` if the pool of `trading_pair` has liquidity, or deposits
+	/// `PoolNotBootstrapped` event and returns `false`.
+	fn ensure_pool_bootstrapped(trading_pair: TradingPair) -> bool {
+		let bootstrapped = Self::is_pool_bootstrapped(trading_pair);
+		if !bootstrapped {
+			Self::deposit_event(Event::<T>::PoolNotBootstrapped { trading_pair });
+		}
+		bootstrapped
+	}
+
+	/// Rebalance by `strategy`. Returns `(stable_deployed, lp_acquired)` if liquidity is added.
 	#[transactional]
-	fn rebalance(strategy: &Strategy, diff: BTreeMap<CurrencyId, AllocationDiff>) -> DispatchResult {
-		match strategy.kind {
-			StrategyKind::LiquidityProvisionAusdAdao => Self::rebalance_ausd_adao(strategy, diff),
-			StrategyKind::LiquidityProvisionAusdOther(token) => Self::rebalance_ausd_other(strategy, token, diff),
+	fn rebalance(
+		strategy: &Strategy,
+		diff: BTreeMap<CurrencyId, AllocationDiff>,
+	) -> Result<Option<(Balance, Balance)>, DispatchError> {
+		let trade = match Self::executable_trade(strategy, &diff)? {
+			Some(trade) => trade,
+			None => return Ok(None),
+		};
+
+		let lp_acquired = match strategy.kind {
+			StrategyKind::LiquidityProvisionAusdAdao => Self::rebalance_ausd_adao(trade)?,
+			StrategyKind::LiquidityProvisionAusdOther(_) | StrategyKind::LiquidityProvisionWith(_) => {
+				Self::rebalance_ausd_other(strategy.kind.other_currency_id(), trade)?
+			}
+		};
+		Ok(Some((trade.stable_amount, lp_acquired)))
+	}
+
+	/// The trade `strategy` would execute based on `diff`, after the trading pair, pool bootstrap
+	/// and pool impact checks. Returns `None` if no trade would be made.
+	fn executable_trade(
+		strategy: &Strategy,
+		diff: &BTreeMap<CurrencyId, AllocationDiff>,
+	) -> Result<Option<PlannedTrade>, DispatchError> {
+		let trade = match Self::plan_trade(strategy, diff)? {
+			Some(trade) => trade,
+			None => return Ok(None),
+		};
+		if !Self::ensure_trading_pair_enabled(trade.trading_pair)
+			|| !Self::ensure_pool_bootstrapped(trade.trading_pair)
+		{
+			return Ok(None);
 		}
+		let limited = Self::limit_pool_impact(strategy, trade);
+		if limited != Some(trade) {
+			Self::deposit_event(Event::<T>::PoolImpactLimited {
+				trading_pair: trade.trading_pair,
+				planned_stable_amount: trade.stable_amount,
+				stable_amount: limited.map_or(Zero::zero(), |limited| limited.stable_amount),
+			});
+		}
+		Ok(limited)
 	}
 
-	#[require_transactional]
-	fn rebalance_ausd_adao(strategy: &Strategy, diff: BTreeMap<CurrencyId, AllocationDiff>) -> DispatchResult {
-		let trading_pair = TradingPair::from_currency_ids(
-			CurrencyId::Token(TokenSymbol::AUSD),
-			CurrencyId::Token(TokenSymbol::ADAO),
-		)
-		.ok_or(Error::<T>::InvalidTradingPair)?;
+	/// The trade `strategy` would make based on `diff`. Returns `None` if no trade needed.
+	fn plan_trade(
+		strategy: &Strategy,
+		diff: &BTreeMap<CurrencyId, AllocationDiff>,
+	) -> Result<Option<PlannedTrade>, DispatchError> {
+		let trading_pair = strategy.kind.trading_pair().ok_or(Error::<T>::InvalidTradingPair)?;
 		let lp = trading_pair.dex_share_currency_id();
 		let lp_diff = match diff.get(&lp) {
 			Some(d) => d,
-			None => return Ok(()),
+			None => return Ok(None),
 		};
 		if lp_diff.range_diff >= FixedI128::zero() {
-			return Ok(());
+			return Ok(None);
+		}
+
+		// AUSD above the buffer can be used
+		let usable_stable = T::Currency::free_balance(Token(AUSD), &T::DaoAccount::get())
+			.saturating_sub(T::MinStableBuffer::get());
+		if usable_stable.is_zero() {
+			return Ok(None);
+		}
+		let max_amount = diff
+			.get(&Token(AUSD))
+			.map(|d| d.diff_amount)
+			.unwrap_or_default()
+			.min(usable_stable.unique_saturated_into());
+		match strategy.kind {
+			StrategyKind::LiquidityProvisionAusdAdao => {
+				let amount = strategy.trade_amount(lp_diff.diff_amount, max_amount).saturating_div(2);
+				if amount <= 0 {
+					return Ok(None);
+				}
+
+				let adao_price = Self::price(Token(ADAO))?;
+				let adao_to_mint = adao_price.saturating_mul_int(amount);
+				Ok(Some(PlannedTrade {
+					trading_pair,
+					other_amount: adao_to_mint.unique_saturated_into(),
+					stable_amount: amount.unique_saturated_into(),
+				}))
+			}
+			StrategyKind::LiquidityProvisionAusdOther(_) | StrategyKind::LiquidityProvisionWith(_) => {
+				let other = strategy.kind.other_currency_id();
+				let other_price = Self::price(other)?;
+				let max_other_to_add = T::Currency::free_balance(other, &T::DaoAccount::get());
+				let max_other_to_add_amount = other_price.saturating_mul_int(max_other_to_add);
+
+				let amount = strategy
+					.trade_amount(
+						lp_diff.diff_amount,
+						max_amount.min(max_other_to_add_amount.unique_saturated_into()),
+					)
+					.saturating_div(2);
+				let other_to_add = other_price.saturating_mul_int(amount);
+				if amount <= 0 || other_to_add <= 0 {
+					return Ok(None);
+				}
+
+				Ok(Some(PlannedTrade {
+					trading_pair,
+					other_amount: other_to_add.unique_saturated_into(),
+					stable_amount: amount.unique_saturated_into(),
+				}))
+			}
+		}
+	}
+
+	/// Scale `trade` down to add at most `T::MaxPoolImpactRatio` of the pool reserves. Returns
+	/// `None` if skipped by `strategy`, or nothing left to add. Trades to an empty pool are not
+	/// limited, as there is no price to move, but rebalance requires the pool to be bootstrapped.
+	fn limit_pool_impact(strategy: &Strategy, trade: PlannedTrade) -> Option<PlannedTrade> {
+		let other = strategy.kind.other_currency_id();
+		let (reserve_other, reserve_stable) = T::DEX::get_liquidity_pool(other, Token(AUSD));
+		if reserve_other.is_zero() && reserve_stable.is_zero() {
+			return Some(trade);
+		}
+
+		// scale = min(1, max_ratio * reserve / amount) of both sides
+		let max_ratio = T::MaxPoolImpactRatio::get();
+		let allowed = |amount: Balance, reserve: Balance| {
+			Ratio::checked_from_rational(max_ratio.saturating_mul_int(reserve), amount).unwrap_or_else(Ratio::one)
+		};
+		let scale = allowed(trade.other_amount, reserve_other).min(allowed(trade.stable_amount, reserve_stable));
+		if scale >= Ratio::one() {
+			return Some(trade);
+		}
+		if strategy.skip_on_thin_pool {
+			return None;
 		}
 
-		let max_amount = diff.get(&Token(AUSD)).map(|d| d.diff_amount).unwrap_or_default();
-		let amount = strategy.trade_amount(lp_diff.diff_amount, max_amount).saturating_div(2);
-		if amount <= 0 {
-			return Ok(());
+		let other_amount = scale.saturating_mul_int(trade.other_amount);
+		let stable_amount = scale.saturating_mul_int(trade.stable_amount);
+		if other_amount.is_zero() || stable_amount.is_zero() {
+			return None;
+		}
+		Some(PlannedTrade {
+			other_amount,
+			stable_amount,
+			..trade
+		})
+	}
+
+	/// Buy currencies in `diff` below target range from AUSD, with their acquisition strategies.
+	fn acquire(diff: &BTreeMap<CurrencyId, AllocationDiff>) {
+		let lp_currency_ids: Vec<CurrencyId> = Self::strategies()
+			.iter()
+			.filter_map(|strategy| strategy.kind.lp_currency_id())
+			.collect();
+		// AUSD above target and the buffer can be used, shared by all acquisitions
+		let mut max_amount = diff.get(&Token(AUSD)).map(|d| d.diff_amount).unwrap_or_default();
+
+		for (currency_id, currency_diff) in diff.iter() {
+			if currency_diff.range_diff >= FixedI128::zero() || lp_currency_ids.contains(currency_id) {
+				continue;
+			}
+			let strategy = match Self::acquisition_strategies(currency_id) {
+				Some(strategy) => strategy,
+				None => continue,
+			};
+			match with_transaction_result(|| Self::acquire_currency(*currency_id, &strategy, currency_diff, max_amount))
+			{
+				Ok(stable_amount) => {
+					max_amount = max_amount.saturating_sub(stable_amount.unique_saturated_into());
+				}
+				Err(e) => log::error!(target: "adao-manager", "Acquiring {:?} failed: {:?}", currency_id, e),
+			}
+		}
+	}
+
+	/// Buy `currency_id` with at most `max_amount` AUSD. Returns the AUSD amount paid.
+	#[require_transactional]
+	fn acquire_currency(
+		currency_id: CurrencyId,
+		strategy: &AcquisitionStrategy,
+		diff: &AllocationDiff,
+		max_amount: i128,
+	) -> Result<Balance, DispatchError> {
+		let trading_pair =
+			TradingPair::from_currency_ids(Token(AUSD), currency_id).ok_or(Error::<T>::InvalidTradingPair)?;
+		if !Self::ensure_trading_pair_enabled(trading_pair) {
+			return Ok(Zero::zero());
 		}
 
-		let adao_price = Self::price(Token(ADAO))?;
-		let adao_to_mint = adao_price.saturating_mul_int(amount);
+		let usable_stable = T::Currency::free_balance(Token(AUSD), &T::DaoAccount::get())
+			.saturating_sub(T::MinStableBuffer::get());
+		let (_, reserve_stable) = T::DEX::get_liquidity_pool(currency_id, Token(AUSD));
+		let max_stable = usable_stable.min(T::MaxPoolImpactRatio::get().saturating_mul_int(reserve_stable));
+		let max_amount = max_amount.min(max_stable.unique_saturated_into());
+
+		// diff in AUSD value
+		let diff_value = Self::price(currency_id)?.saturating_mul_int(diff.diff_amount);
+		let stable_amount = strategy.trade_amount(diff_value, max_amount);
+		if stable_amount <= 0 {
+			return Ok(Zero::zero());
+		}
+
+		let stable_amount: Balance = stable_amount.unique_saturated_into();
+		let amount = T::DEX::swap_with_exact_supply(
+			&T::DaoAccount::get(),
+			&[Token(AUSD), currency_id],
+			stable_amount,
+			Zero::zero(),
+		)?;
+		Self::deposit_event(Event::<T>::Acquired {
+			currency_id,
+			stable_amount,
+			amount,
+		});
+		Ok(stable_amount)
+	}
+
+	/// Count periods of target allocations in `diff` at zero balance, and deposit
+	/// `TargetUnserviced` once exceeding `T::MaxUnservicedPeriods`.
+	fn track_unserviced(diff: &BTreeMap<CurrencyId, AllocationDiff>) {
+		let targets = Self::target_allocations();
+		for currency_id in diff
+			.keys()
+			.filter(|currency_id| **currency_id != Token(ADAO) && targets.contains_key(currency_id))
+		{
+			if !T::Currency::total_balance(*currency_id, &T::DaoAccount::get()).is_zero() {
+				UnservicedPeriods::<T>::remove(currency_id);
+				continue;
+			}
+			let periods = UnservicedPeriods::<T>::mutate(currency_id, |periods| {
+				*periods = periods.saturating_add(1);
+				*periods
+			});
+			if periods == T::MaxUnservicedPeriods::get().saturating_add(1) {
+				Self::deposit_event(Event::<T>::TargetUnserviced {
+					currency_id: *currency_id,
+					periods,
+				});
+			}
+		}
+	}
+
+	/// Returns the LP tokens received.
+	#[require_transactional]
+	fn rebalance_ausd_adao(trade: PlannedTrade) -> Result<Balance, DispatchError> {
+		let lp = trade.trading_pair.dex_share_currency_id();
 		let pallet_account = Self::account_id();
-		T::Currency::deposit(Token(ADAO), &pallet_account, adao_to_mint.unique_saturated_into())?;
-		let amount_u128: u128 = amount.unique_saturated_into();
-		T::Currency::transfer(Token(AUSD), &T::DaoAccount::get(), &pallet_account, amount_u128)?;
+		Self::mint_adao(MintReason::PolRebalance, &pallet_account, trade.other_amount)?;
+		T::Currency::transfer(Token(AUSD), &T::DaoAccount::get(), &pallet_account, trade.stable_amount)?;
 		T::DEX::add_liquidity(
 			&pallet_account,
 			Token(ADAO),
 			Token(AUSD),
-			adao_to_mint.unique_saturated_into(),
-			amount_u128,
+			trade.other_amount,
+			trade.stable_amount,
 			Zero::zero(),
 			false,
 		)?;
 
 		let lp_share = T::Currency::free_balance(lp, &pallet_account);
-		T::Currency::transfer(lp, &pallet_account, &T::DaoAccount::get(), lp_share)
+		T::Currency::transfer(lp, &pallet_account, &T::DaoAccount::get(), lp_share)?;
+
+		let record = (
+			frame_system::Pallet::<T>::block_number(),
+			trade.other_amount,
+			trade.stable_amount,
+			lp_share,
+		);
+		PolMintHistory::<T>::mutate(|history| {
+			if history.try_push(record).is_err() && !history.is_empty() {
+				history.remove(0);
+				let _ = history.try_push(record);
+			}
+		});
+		Self::deposit_event(Event::<T>::Rebalanced {
+			adao_minted: trade.other_amount,
+			ausd_paired: trade.stable_amount,
+			lp_received: lp_share,
+		});
+		Ok(lp_share)
 	}
 
+	/// Returns the LP tokens received.
 	#[require_transactional]
-	fn rebalance_ausd_other(
-		strategy: &Strategy,
-		other: TokenSymbol,
-		diff: BTreeMap<CurrencyId, AllocationDiff>,
-	) -> DispatchResult {
-		let trading_pair =
-			TradingPair::from_currency_ids(CurrencyId::Token(TokenSymbol::AUSD), CurrencyId::Token(other))
-				.ok_or(Error::<T>::InvalidTradingPair)?;
-		let lp = trading_pair.dex_share_currency_id();
-		let lp_diff = match diff.get(&lp) {
-			Some(d) => d,
-			None => return Ok(()),
-		};
-		if lp_diff.range_diff >= FixedI128::zero() {
-			return Ok(());
-		}
-
-		let other_price = Self::price(Token(other))?;
-		let max_other_to_add = T::Currency::free_balance(Token(other), &T::DaoAccount::get());
-		let max_other_to_add_amount = other_price.saturating_mul_int(max_other_to_add);
-
-		let max_amount = diff.get(&Token(AUSD)).map(|d| d.diff_amount).unwrap_or_default();
-		let amount = strategy
-			.trade_amount(
-				lp_diff.diff_amount,
-				max_amount.min(max_other_to_add_amount.unique_saturated_into()),
-			)
-			.saturating_div(2);
-		let other_to_add = other_price.saturating_mul_int(amount);
-		if amount <= 0 || other_to_add <= 0 {
-			return Ok(());
-		}
-
+	fn rebalance_ausd_other(other: CurrencyId, trade: PlannedTrade) -> Result<Balance, DispatchError> {
+		let lp = trade.trading_pair.dex_share_currency_id();
+		let dao_account = T::DaoAccount::get();
+		let lp_before = T::Currency::free_balance(lp, &dao_account);
 		T::DEX::add_liquidity(
-			&T::DaoAccount::get(),
-			Token(other),
+			&dao_account,
+			other,
 			Token(AUSD),
-			other_to_add.unique_saturated_into(),
-			amount.unique_saturated_into(),
+			trade.other_amount,
+			trade.stable_amount,
 			Zero::zero(),
 			false,
 		)?;
 
-		Ok(())
+		Ok(T::Currency::free_balance(lp, &dao_account).saturating_sub(lp_before))
 	}
 }
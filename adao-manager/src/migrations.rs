@@ -0,0 +1,110 @@
+// This file is part of Acala.
+
+// Copyright (C) 2022 Acala Foundation.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Storage migrations.
+
+use super::*;
+
+/// Add `haircut` to `Allocation`, defaulting to no haircut.
+pub mod v1 {
+	use super::*;
+
+	#[derive(Decode)]
+	struct OldAllocation {
+		value: Balance,
+		range: Balance,
+	}
+
+	impl From<OldAllocation> for Allocation {
+		fn from(old: OldAllocation) -> Self {
+			Allocation::new(old.value, old.range)
+		}
+	}
+
+	pub fn migrate<T: Config>() -> Weight {
+		if StorageVersion::get::<Pallet<T>>() >= 1 {
+			return 0;
+		}
+
+		let _ = TargetAllocations::<T>::translate::<BTreeMap<CurrencyId, OldAllocation>, _>(|maybe_old| {
+			maybe_old.map(|old| {
+				old.into_iter()
+					.map(|(currency_id, allocation)| (currency_id, allocation.into()))
+					.collect()
+			})
+		});
+		let _ = PendingTargetAllocations::<T>::translate::<
+			(T::BlockNumber, Vec<(CurrencyId, Option<OldAllocation>)>),
+			_,
+		>(|maybe_old| {
+			maybe_old.map(|(activate_at, targets)| {
+				let targets = targets
+					.into_iter()
+					.map(|(currency_id, maybe_allocation)| (currency_id, maybe_allocation.map(Into::into)))
+					.collect();
+				(activate_at, targets)
+			})
+		});
+		StorageVersion::new(1).put::<Pallet<T>>();
+		log::info!(target: "adao-manager", "migrated target allocations to v1");
+
+		T::DbWeight::get().reads_writes(3, 3)
+	}
+}
+
+/// Add `skip_on_thin_pool` to `Strategy`, defaulting to scaling trades down.
+pub mod v2 {
+	use super::*;
+
+	#[derive(Decode)]
+	struct OldStrategy {
+		kind: StrategyKind,
+		percent_per_trade: FixedU128,
+		max_amount_per_trade: i128,
+		min_amount_per_trade: i128,
+	}
+
+	impl From<OldStrategy> for Strategy {
+		fn from(old: OldStrategy) -> Self {
+			Strategy {
+				kind: old.kind,
+				percent_per_trade: old.percent_per_trade,
+				max_amount_per_trade: old.max_amount_per_trade,
+				min_amount_per_trade: old.min_amount_per_trade,
+				skip_on_thin_pool: false,
+			}
+		}
+	}
+
+	pub fn migrate<T: Config>() -> Weight {
+		if StorageVersion::get::<Pallet<T>>() >= 2 {
+			return 0;
+		}
+
+		let _ = Strategies::<T>::translate::<Vec<OldStrategy>, _>(|maybe_old| {
+			maybe_old.map(|old| old.into_iter().map(Into::into).collect())
+		});
+		let _ = PendingStrategies::<T>::translate::<(T::BlockNumber, Vec<OldStrategy>), _>(|maybe_old| {
+			maybe_old.map(|(activate_at, old)| (activate_at, old.into_iter().map(Into::into).collect()))
+		});
+		StorageVersion::new(2).put::<Pallet<T>>();
+		log::info!(target: "adao-manager", "migrated strategies to v2");
+
+		T::DbWeight::get().reads_writes(3, 3)
+	}
+}
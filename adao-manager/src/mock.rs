@@ -21,7 +21,7 @@
 #![cfg(test)]
 
 use super::*;
-use acala_primitives::DexShare;
+use acala_primitives::{DexShare, TokenInfo};
 use frame_support::{
 	construct_runtime, ord_parameter_types, parameter_types,
 	traits::{ConstU32, ConstU64, Everything, Nothing},
@@ -32,7 +32,7 @@ use module_support::{
 	mocks::{MockAddressMapping, MockStableAsset},
 	Price,
 };
-use orml_traits::parameter_type_with_key;
+use orml_traits::{parameter_type_with_key, Happened};
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
@@ -50,10 +50,14 @@ pub const AUSD: CurrencyId = CurrencyId::Token(TokenSymbol::AUSD);
 pub const ACA: CurrencyId = CurrencyId::Token(TokenSymbol::ACA);
 pub const ADAO: CurrencyId = CurrencyId::Token(TokenSymbol::ADAO);
 pub const DOT: CurrencyId = CurrencyId::Token(TokenSymbol::DOT);
+pub const SDAO: CurrencyId = CurrencyId::Token(TokenSymbol::SDAO);
 pub const ACA_AUSD_LP: CurrencyId =
 	CurrencyId::DexShare(DexShare::Token(TokenSymbol::ACA), DexShare::Token(TokenSymbol::AUSD));
 pub const ADAO_AUSD_LP: CurrencyId =
 	CurrencyId::DexShare(DexShare::Token(TokenSymbol::AUSD), DexShare::Token(TokenSymbol::ADAO));
+pub const FOREIGN: CurrencyId = CurrencyId::ForeignAsset(0);
+pub const FOREIGN_AUSD_LP: CurrencyId =
+	CurrencyId::DexShare(DexShare::Token(TokenSymbol::AUSD), DexShare::ForeignAsset(0));
 
 impl frame_system::Config for Runtime {
 	type Origin = Origin;
@@ -142,6 +146,8 @@ parameter_types! {
 	pub EnabledTradingPairs: Vec<TradingPair> = vec![
 		TradingPair::from_currency_ids(ACA, AUSD).unwrap(),
 		TradingPair::from_currency_ids(ADAO, AUSD).unwrap(),
+		TradingPair::from_currency_ids(FOREIGN, AUSD).unwrap(),
+		TradingPair::from_currency_ids(DOT, AUSD).unwrap(),
 	];
 	pub const ExtendedProvisioningBlocks: BlockNumber = 0;
 }
@@ -150,6 +156,40 @@ ord_parameter_types! {
 	pub const Alice: AccountId = ALICE;
 }
 
+thread_local! {
+	static LIQUIDITY_POOL_UPDATES: RefCell<u32> = RefCell::new(0);
+	static LAST_LIQUIDITY_POOL_UPDATE: RefCell<Option<(TradingPair, Balance, Balance)>> = RefCell::new(None);
+}
+
+/// Captures DEX liquidity pool updates.
+pub struct MockOnLiquidityPoolUpdated;
+impl MockOnLiquidityPoolUpdated {
+	/// Number of liquidity pool updates.
+	pub fn updates() -> u32 {
+		LIQUIDITY_POOL_UPDATES.with(|v| *v.borrow())
+	}
+
+	/// The last liquidity pool update, in `(trading_pair, pool_0, pool_1)`.
+	pub fn last_update() -> Option<(TradingPair, Balance, Balance)> {
+		LAST_LIQUIDITY_POOL_UPDATE.with(|v| *v.borrow())
+	}
+
+	pub fn reset() {
+		LIQUIDITY_POOL_UPDATES.with(|v| *v.borrow_mut() = 0);
+		LAST_LIQUIDITY_POOL_UPDATE.with(|v| *v.borrow_mut() = None);
+	}
+
+	pub fn assert_no_updates() {
+		assert_eq!(Self::updates(), 0, "unexpected liquidity pool updates");
+	}
+}
+impl Happened<(TradingPair, Balance, Balance)> for MockOnLiquidityPoolUpdated {
+	fn happened(info: &(TradingPair, Balance, Balance)) {
+		LIQUIDITY_POOL_UPDATES.with(|v| *v.borrow_mut() += 1);
+		LAST_LIQUIDITY_POOL_UPDATE.with(|v| *v.borrow_mut() = Some(*info));
+	}
+}
+
 impl module_dex::Config for Runtime {
 	type Event = Event;
 	type Currency = Currencies;
@@ -161,7 +201,7 @@ impl module_dex::Config for Runtime {
 	type WeightInfo = ();
 	type ListingOrigin = EnsureSignedBy<Alice, AccountId>;
 	type ExtendedProvisioningBlocks = ExtendedProvisioningBlocks;
-	type OnLiquidityPoolUpdated = ();
+	type OnLiquidityPoolUpdated = MockOnLiquidityPoolUpdated;
 	type StableAsset = MockStableAsset<CurrencyId, Balance, AccountId, BlockNumber>;
 }
 
@@ -171,6 +211,16 @@ thread_local! {
 	static ADAO_PRICE: RefCell<Option<Price>> = RefCell::new(Some(Price::one()));
 	static ACA_AUSD_PRICE: RefCell<Option<Price>> = RefCell::new(Some(Price::one()));
 	static ADAO_AUSD_PRICE: RefCell<Option<Price>> = RefCell::new(Some(Price::one()));
+	static FOREIGN_PRICE: RefCell<Option<Price>> = RefCell::new(Some(Price::one()));
+	static FOREIGN_AUSD_PRICE: RefCell<Option<Price>> = RefCell::new(Some(Price::one()));
+	static DOT_PRICE: RefCell<Option<Price>> = RefCell::new(Some(Price::one()));
+}
+
+thread_local! {
+	static PRICE_CALLS: RefCell<u32> = RefCell::new(0);
+	static LAST_PRICE_QUERY: RefCell<Option<CurrencyId>> = RefCell::new(None);
+	static RELATIVE_PRICE_CALLS: RefCell<u32> = RefCell::new(0);
+	static LAST_RELATIVE_PRICE_QUERY: RefCell<Option<(CurrencyId, CurrencyId)>> = RefCell::new(None);
 }
 
 pub struct MockPriceSource;
@@ -182,23 +232,64 @@ impl MockPriceSource {
 			ADAO => ADAO_PRICE.with(|v| *v.borrow_mut() = price),
 			ACA_AUSD_LP => ACA_AUSD_PRICE.with(|v| *v.borrow_mut() = price),
 			ADAO_AUSD_LP => ADAO_AUSD_PRICE.with(|v| *v.borrow_mut() = price),
+			FOREIGN => FOREIGN_PRICE.with(|v| *v.borrow_mut() = price),
+			FOREIGN_AUSD_LP => FOREIGN_AUSD_PRICE.with(|v| *v.borrow_mut() = price),
+			DOT => DOT_PRICE.with(|v| *v.borrow_mut() = price),
 			_ => {}
 		}
 	}
+
+	/// Number of price queries made, both `get_price` and DEX `get_relative_price`.
+	pub fn price_calls() -> u32 {
+		PRICE_CALLS.with(|v| *v.borrow()) + RELATIVE_PRICE_CALLS.with(|v| *v.borrow())
+	}
+
+	/// The last `get_price` query.
+	pub fn last_price_query() -> Option<CurrencyId> {
+		LAST_PRICE_QUERY.with(|v| *v.borrow())
+	}
+
+	/// Number of DEX `get_relative_price` queries made.
+	pub fn relative_price_calls() -> u32 {
+		RELATIVE_PRICE_CALLS.with(|v| *v.borrow())
+	}
+
+	/// The last DEX `get_relative_price` query, in `(base, quote)`.
+	pub fn last_relative_price_query() -> Option<(CurrencyId, CurrencyId)> {
+		LAST_RELATIVE_PRICE_QUERY.with(|v| *v.borrow())
+	}
+
+	pub fn reset_calls() {
+		PRICE_CALLS.with(|v| *v.borrow_mut() = 0);
+		LAST_PRICE_QUERY.with(|v| *v.borrow_mut() = None);
+		RELATIVE_PRICE_CALLS.with(|v| *v.borrow_mut() = 0);
+		LAST_RELATIVE_PRICE_QUERY.with(|v| *v.borrow_mut() = None);
+	}
+
+	pub fn assert_no_price_calls() {
+		assert_eq!(Self::price_calls(), 0, "unexpected price queries");
+	}
 }
 impl PriceProvider<CurrencyId> for MockPriceSource {
 	fn get_price(currency_id: CurrencyId) -> Option<Price> {
+		PRICE_CALLS.with(|v| *v.borrow_mut() += 1);
+		LAST_PRICE_QUERY.with(|v| *v.borrow_mut() = Some(currency_id));
 		match currency_id {
 			ACA => ACA_PRICE.with(|v| *v.borrow()),
 			AUSD => AUSD_PRICE.with(|v| *v.borrow()),
 			ACA_AUSD_LP => ACA_AUSD_PRICE.with(|v| *v.borrow()),
 			ADAO_AUSD_LP => ADAO_AUSD_PRICE.with(|v| *v.borrow()),
+			CurrencyId::ForeignAsset(_) => FOREIGN_PRICE.with(|v| *v.borrow()),
+			FOREIGN_AUSD_LP => FOREIGN_AUSD_PRICE.with(|v| *v.borrow()),
+			DOT => DOT_PRICE.with(|v| *v.borrow()),
 			_ => None,
 		}
 	}
 }
 impl DEXPriceProvider<CurrencyId> for MockPriceSource {
 	fn get_relative_price(base: CurrencyId, quote: CurrencyId) -> Option<Price> {
+		RELATIVE_PRICE_CALLS.with(|v| *v.borrow_mut() += 1);
+		LAST_RELATIVE_PRICE_QUERY.with(|v| *v.borrow_mut() = Some((base, quote)));
 		if quote != AUSD {
 			return None;
 		}
@@ -210,6 +301,115 @@ impl DEXPriceProvider<CurrencyId> for MockPriceSource {
 	}
 }
 
+pub struct MockTradingPairStatus;
+impl DEXTradingPairStatus for MockTradingPairStatus {
+	fn is_enabled(trading_pair: TradingPair) -> bool {
+		matches!(
+			module_dex::TradingPairStatuses::<Runtime>::get(trading_pair),
+			module_dex::TradingPairStatus::Enabled
+		)
+	}
+}
+
+/// Only `FOREIGN` is a registered foreign asset.
+pub struct MockCurrencyDecimals;
+impl CurrencyDecimals for MockCurrencyDecimals {
+	fn decimals(currency_id: CurrencyId) -> Option<u8> {
+		match currency_id {
+			FOREIGN => Some(12),
+			CurrencyId::ForeignAsset(_) => None,
+			_ => currency_id.decimals(),
+		}
+	}
+}
+
+thread_local! {
+	static ENACTMENT_DELAY: RefCell<BlockNumber> = RefCell::new(0);
+	static MAX_ALLOCATIONS_PER_REBALANCE: RefCell<u32> = RefCell::new(50);
+	static STAKED_TOKEN_RATE: RefCell<Rate> = RefCell::new(Rate::one());
+	static MIN_STABLE_BUFFER: RefCell<Balance> = RefCell::new(0);
+	static MAX_POOL_IMPACT_RATIO: RefCell<Ratio> = RefCell::new(Ratio::one());
+	static REBALANCE_PERIOD: RefCell<BlockNumber> = RefCell::new(2);
+}
+
+/// SDAO to ADAO exchange rate, 1 by default.
+pub struct MockStakedTokenRate;
+impl MockStakedTokenRate {
+	pub fn set(rate: Rate) {
+		STAKED_TOKEN_RATE.with(|v| *v.borrow_mut() = rate);
+	}
+}
+impl Get<Rate> for MockStakedTokenRate {
+	fn get() -> Rate {
+		STAKED_TOKEN_RATE.with(|v| *v.borrow())
+	}
+}
+
+/// Minimum AUSD buffer of the DAO account, zero by default.
+pub struct MockMinStableBuffer;
+impl MockMinStableBuffer {
+	pub fn set(buffer: Balance) {
+		MIN_STABLE_BUFFER.with(|v| *v.borrow_mut() = buffer);
+	}
+}
+impl Get<Balance> for MockMinStableBuffer {
+	fn get() -> Balance {
+		MIN_STABLE_BUFFER.with(|v| *v.borrow())
+	}
+}
+
+/// Maximum pool impact ratio of a trade, 1 by default.
+pub struct MockMaxPoolImpactRatio;
+impl MockMaxPoolImpactRatio {
+	pub fn set(ratio: Ratio) {
+		MAX_POOL_IMPACT_RATIO.with(|v| *v.borrow_mut() = ratio);
+	}
+}
+impl Get<Ratio> for MockMaxPoolImpactRatio {
+	fn get() -> Ratio {
+		MAX_POOL_IMPACT_RATIO.with(|v| *v.borrow())
+	}
+}
+
+/// Rebalance period, 2 by default.
+pub struct MockRebalancePeriod;
+impl MockRebalancePeriod {
+	pub fn set(period: BlockNumber) {
+		REBALANCE_PERIOD.with(|v| *v.borrow_mut() = period);
+	}
+}
+impl Get<BlockNumber> for MockRebalancePeriod {
+	fn get() -> BlockNumber {
+		REBALANCE_PERIOD.with(|v| *v.borrow())
+	}
+}
+
+/// Enactment delay of governance updates, zero by default.
+pub struct MockEnactmentDelay;
+impl MockEnactmentDelay {
+	pub fn set(delay: BlockNumber) {
+		ENACTMENT_DELAY.with(|v| *v.borrow_mut() = delay);
+	}
+}
+impl Get<BlockNumber> for MockEnactmentDelay {
+	fn get() -> BlockNumber {
+		ENACTMENT_DELAY.with(|v| *v.borrow())
+	}
+}
+
+/// Max allocations evaluated per rebalance, 50 by default.
+pub struct MockMaxAllocationsPerRebalance;
+impl MockMaxAllocationsPerRebalance {
+	pub fn set(max: u32) {
+		MAX_ALLOCATIONS_PER_REBALANCE.with(|v| *v.borrow_mut() = max);
+	}
+}
+impl Get<u32> for MockMaxAllocationsPerRebalance {
+	fn get() -> u32 {
+		MAX_ALLOCATIONS_PER_REBALANCE.with(|v| *v.borrow())
+	}
+}
+
 parameter_types! {
 	pub const GetStableCurrency: CurrencyId = AUSD;
 	pub const GetDaoAccount: AccountId = DAO;
@@ -219,15 +419,25 @@ parameter_types! {
 impl module::Config for Runtime {
 	type Event = Event;
 	type StableCurrencyId = GetStableCurrency;
-	type RebalancePeriod = ConstU64<2>;
+	type RebalancePeriod = MockRebalancePeriod;
 	type RebalanceOffset = ConstU64<1>;
+	type EnactmentDelay = MockEnactmentDelay;
+	type MaxAllocationsPerRebalance = MockMaxAllocationsPerRebalance;
+	type MaxTargetsPerCall = ConstU32<10>;
 	type DaoAccount = GetDaoAccount;
+	type MinStableBuffer = MockMinStableBuffer;
+	type MaxPolMintHistory = ConstU32<10>;
+	type MaxPoolImpactRatio = MockMaxPoolImpactRatio;
+	type MaxUnservicedPeriods = ConstU32<10>;
 	type PalletId = AquaDaoPalletId;
 	type DEX = DexModule;
+	type TradingPairStatus = MockTradingPairStatus;
+	type CurrencyDecimals = MockCurrencyDecimals;
 	type Currency = Currencies;
 	type UpdateOrigin = EnsureSignedBy<Alice, AccountId>;
 	type AssetPriceProvider = MockPriceSource;
 	type AdaoPriceProvider = MockPriceSource;
+	type StakedTokenRate = MockStakedTokenRate;
 	type WeightInfo = ();
 }
 
@@ -21,7 +21,7 @@
 #![cfg(test)]
 
 use super::*;
-use mock::{Event, ACA, AUSD, DOT, *};
+use mock::{Event, ACA, AUSD, DOT, SDAO, *};
 
 use frame_support::{assert_noop, assert_ok, error::BadOrigin};
 use module_support::dex::DEXManager;
@@ -36,25 +36,71 @@ fn run_to_block(n: BlockNumber) {
 	}
 }
 
-// Sets AUSD/ADAO and AUSD/ACA for liquidity provision
-fn set_test_strategies() {
-	// Set strategies
+// AUSD/ADAO and AUSD/ACA liquidity provision
+fn test_strategies() -> Vec<Strategy> {
 	let strategy = Strategy {
 		kind: StrategyKind::LiquidityProvisionAusdAdao,
 		percent_per_trade: FixedU128::saturating_from_rational(1, 2),
 		max_amount_per_trade: 1_000_000,
 		min_amount_per_trade: -1_000_000,
+		skip_on_thin_pool: false,
 	};
 	let strategy2 = Strategy {
 		kind: StrategyKind::LiquidityProvisionAusdOther(TokenSymbol::ACA),
 		percent_per_trade: FixedU128::saturating_from_rational(1, 2),
 		max_amount_per_trade: 1_000_000,
 		min_amount_per_trade: -1_000_000,
+		skip_on_thin_pool: false,
 	};
-	assert_ok!(AquaDAO::set_strategies(
-		Origin::signed(ALICE),
-		vec![strategy, strategy2]
+	vec![strategy, strategy2]
+}
+
+// Sets AUSD/ADAO and AUSD/ACA for liquidity provision
+fn set_test_strategies() {
+	assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), test_strategies()));
+}
+
+// Seeds the AUSD/`currency_id` pool with 1_000_000 of each from BOB, for rebalance to add liquidity to
+fn seed_pool(currency_id: CurrencyId) {
+	for c in [AUSD, currency_id] {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			c, &BOB, 2_000_000
+		));
+	}
+	assert_ok!(DexModule::add_liquidity(
+		Origin::signed(BOB),
+		AUSD,
+		currency_id,
+		1_000_000,
+		1_000_000,
+		0,
+		false
 	));
+	MockOnLiquidityPoolUpdated::reset();
+}
+
+#[test]
+fn set_target_allocations_fails_if_duplicate_currency() {
+	ExtBuilder::default().build().execute_with(|| {
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc))]
+		));
+
+		assert_noop!(
+			AquaDAO::set_target_allocations(
+				Origin::signed(ALICE),
+				vec![
+					(DOT, Some(alloc)),
+					(AUSD, Some(Allocation::new(200, 20))),
+					(DOT, None)
+				]
+			),
+			Error::<Runtime>::DuplicateCurrency
+		);
+		assert_eq!(AquaDAO::target_allocations(), BTreeMap::from([(AUSD, alloc)]));
+	});
 }
 
 #[test]
@@ -68,17 +114,29 @@ fn set_target_allocations_fails() {
 	});
 }
 
+#[test]
+fn set_target_allocations_fails_if_too_many_targets() {
+	ExtBuilder::default().build().execute_with(|| {
+		let alloc = Allocation::new(100, 10);
+		let targets: Vec<_> = (0..11u16).map(|i| (CurrencyId::ForeignAsset(i), Some(alloc))).collect();
+		assert_noop!(
+			AquaDAO::set_target_allocations(Origin::signed(ALICE), targets),
+			Error::<Runtime>::TooManyTargets
+		);
+	});
+}
+
 #[test]
 fn set_target_allocations_works() {
 	ExtBuilder::default().build().execute_with(|| {
-		let alloc = Allocation { value: 100, range: 10 };
+		let alloc = Allocation::new(100, 10);
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![(ACA, Some(alloc)), (AUSD, Some(alloc))]
 		));
-		System::assert_last_event(Event::AquaDAO(crate::Event::TargetAllocationSet {
-			currency_id: AUSD,
-			allocation: alloc,
+		System::assert_last_event(Event::AquaDAO(crate::Event::TargetAllocationsUpdated {
+			set: vec![ACA, AUSD],
+			removed: vec![],
 		}));
 		assert_eq!(
 			TargetAllocationPercents::<Runtime>::get().get(&AUSD).unwrap(),
@@ -89,15 +147,15 @@ fn set_target_allocations_works() {
 			}
 		);
 
-		let alloc2 = Allocation { value: 50, range: 5 };
+		let alloc2 = Allocation::new(50, 5);
 		// Will overwrite existing allocation
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![(ACA, Some(alloc2))]
 		));
-		System::assert_last_event(Event::AquaDAO(crate::Event::TargetAllocationSet {
-			currency_id: ACA,
-			allocation: alloc2,
+		System::assert_last_event(Event::AquaDAO(crate::Event::TargetAllocationsUpdated {
+			set: vec![ACA],
+			removed: vec![],
 		}));
 
 		// State is correct for target allocations
@@ -109,8 +167,9 @@ fn set_target_allocations_works() {
 			Origin::signed(ALICE),
 			vec![(ACA, None)]
 		));
-		System::assert_last_event(Event::AquaDAO(crate::Event::TargetAllocationRemoved {
-			currency_id: ACA,
+		System::assert_last_event(Event::AquaDAO(crate::Event::TargetAllocationsUpdated {
+			set: vec![],
+			removed: vec![ACA],
 		}));
 		// Percents are correct
 		assert_eq!(
@@ -149,7 +208,7 @@ fn adjust_target_allocations_fails() {
 #[test]
 fn adjust_target_allocations_works() {
 	ExtBuilder::default().build().execute_with(|| {
-		let alloc = Allocation { value: 100, range: 10 };
+		let alloc = Allocation::new(100, 10);
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![(ACA, Some(alloc)), (AUSD, Some(alloc))]
@@ -176,7 +235,7 @@ fn adjust_target_allocations_works() {
 		// Target allocation is adjusted
 		assert_eq!(
 			TargetAllocations::<Runtime>::get().get(&ACA).unwrap(),
-			&Allocation { value: 50, range: 5 }
+			&Allocation::new(50, 5)
 		);
 		assert_eq!(TargetAllocations::<Runtime>::get().get(&AUSD).unwrap(), &alloc);
 		assert_eq!(
@@ -201,8 +260,85 @@ fn set_strategies_works() {
 			percent_per_trade: FixedU128::default(),
 			max_amount_per_trade: 0,
 			min_amount_per_trade: 0,
+			skip_on_thin_pool: false,
 		};
+		assert_noop!(
+			AquaDAO::set_strategies(Origin::signed(ALICE), vec![strategy]),
+			Error::<Runtime>::StrategyWithoutAllocation
+		);
+
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (ADAO_AUSD_LP, Some(alloc))]
+		));
 		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![strategy]));
+		assert_eq!(Strategies::<Runtime>::get(), vec![strategy]);
+	});
+}
+
+#[test]
+fn set_strategies_accepts_pending_allocation() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		MockEnactmentDelay::set(10);
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (ACA_AUSD_LP, Some(alloc))]
+		));
+
+		let strategy = |kind| Strategy {
+			kind,
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		assert_noop!(
+			AquaDAO::set_strategies(
+				Origin::signed(ALICE),
+				vec![strategy(StrategyKind::LiquidityProvisionAusdAdao)]
+			),
+			Error::<Runtime>::StrategyWithoutAllocation
+		);
+		assert_ok!(AquaDAO::set_strategies(
+			Origin::signed(ALICE),
+			vec![strategy(StrategyKind::LiquidityProvisionAusdOther(TokenSymbol::ACA))]
+		));
+	});
+}
+
+#[test]
+fn removing_allocation_orphans_strategy() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![
+				(AUSD, Some(alloc)),
+				(ACA_AUSD_LP, Some(alloc)),
+				(ADAO_AUSD_LP, Some(alloc))
+			]
+		));
+		set_test_strategies();
+		let adao = test_strategies()[0];
+
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(ADAO_AUSD_LP, None)]
+		));
+		System::assert_has_event(Event::AquaDAO(crate::Event::StrategyOrphaned { strategy: adao }));
+		assert_eq!(
+			System::events()
+				.iter()
+				.filter(|r| matches!(r.event, Event::AquaDAO(crate::Event::StrategyOrphaned { .. })))
+				.count(),
+			1
+		);
+		// orphaned strategy is kept
+		assert_eq!(AquaDAO::strategies(), test_strategies());
 	});
 }
 
@@ -216,7 +352,7 @@ fn test_current_allocations() {
 			ACA, &DAO, 1_000_000
 		));
 
-		let alloc = Allocation { value: 100, range: 10 };
+		let alloc = Allocation::new(100, 10);
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![(ACA, Some(alloc)), (AUSD, Some(alloc))]
@@ -235,6 +371,103 @@ fn test_current_allocations() {
 	});
 }
 
+#[test]
+fn current_allocations_values_sdao_by_exchange_rate() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 2_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			SDAO, &DAO, 1_000
+		));
+		// 1 SDAO = 2 ADAO
+		MockStakedTokenRate::set(Rate::saturating_from_integer(2));
+
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(SDAO, Some(alloc)), (AUSD, Some(alloc))]
+		));
+
+		let (curr_allocations, total_value) = AquaDAO::current_allocations().unwrap();
+		assert_eq!(total_value, 4_000);
+		assert_eq!(
+			curr_allocations.get(&SDAO).unwrap(),
+			&CurrentAllocation {
+				amount: 1_000,
+				value: 2_000,
+				percent: FixedU128::saturating_from_rational(1, 2)
+			}
+		);
+
+		// strategies must not trade SDAO
+		assert_noop!(
+			AquaDAO::set_strategies(
+				Origin::signed(ALICE),
+				vec![Strategy {
+					kind: StrategyKind::LiquidityProvisionWith(SDAO),
+					percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+					max_amount_per_trade: 1_000_000,
+					min_amount_per_trade: -1_000_000,
+					skip_on_thin_pool: false,
+				}]
+			),
+			Error::<Runtime>::InvalidStrategy
+		);
+	});
+}
+
+#[test]
+fn haircut_discounts_allocation_value() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			ACA, &DAO, 1_000_000
+		));
+
+		let alloc = Allocation::new(100, 10);
+		for haircut in [Ratio::zero(), Ratio::saturating_from_rational(3, 2)] {
+			assert_noop!(
+				AquaDAO::set_target_allocations(
+					Origin::signed(ALICE),
+					vec![(ACA, Some(Allocation { haircut, ..alloc }))]
+				),
+				Error::<Runtime>::InvalidHaircut
+			);
+		}
+
+		let haircut = Ratio::saturating_from_rational(1, 2);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(ACA, Some(Allocation { haircut, ..alloc })), (AUSD, Some(alloc))]
+		));
+
+		// ACA valued at half of its price
+		let (curr_allocations, total_value) = AquaDAO::current_allocations().unwrap();
+		assert_eq!(total_value, 1_500_000);
+		assert_eq!(
+			curr_allocations.get(&ACA).unwrap(),
+			&CurrentAllocation {
+				amount: 1_000_000,
+				value: 500_000,
+				percent: FixedU128::saturating_from_rational(1, 3)
+			}
+		);
+		assert_eq!(
+			curr_allocations.get(&AUSD).unwrap().percent,
+			FixedU128::saturating_from_rational(2, 3)
+		);
+
+		let diff = AquaDAO::allocation_diff().unwrap();
+		let aca_diff = diff.get(&ACA).unwrap();
+		assert_eq!(aca_diff.current, FixedU128::saturating_from_rational(1, 3));
+		assert_eq!(aca_diff.target, FixedU128::saturating_from_rational(1, 2));
+		assert!(aca_diff.diff_amount < 0);
+	});
+}
+
 #[test]
 fn test_allocation_diff() {
 	ExtBuilder::default().build().execute_with(|| {
@@ -245,7 +478,7 @@ fn test_allocation_diff() {
 			ACA, &DAO, 1_000_000
 		));
 
-		let alloc = Allocation { value: 100, range: 10 };
+		let alloc = Allocation::new(100, 10);
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![(ACA, Some(alloc)), (AUSD, Some(alloc))]
@@ -298,6 +531,69 @@ fn test_allocation_diff() {
 	});
 }
 
+#[test]
+fn drift_score_works_with_two_assets() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			ACA, &DAO, 3_000_000
+		));
+
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(ACA, Some(alloc)), (AUSD, Some(alloc))]
+		));
+
+		// AUSD: |0.25 - 0.45| * 0.5 = 0.1
+		// ACA: |0.75 - 0.55| * 0.5 = 0.1
+		assert_eq!(
+			AquaDAO::drift_score(),
+			Ok(FixedU128::saturating_from_rational(2, 10))
+		);
+
+		// within range
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 2_000_000
+		));
+		assert_eq!(AquaDAO::drift_score(), Ok(FixedU128::zero()));
+	});
+}
+
+#[test]
+fn drift_score_works_with_three_assets() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			ACA, &DAO, 2_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			FOREIGN, &DAO, 1_000_000
+		));
+
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![
+				(AUSD, Some(Allocation::new(50, 10))),
+				(ACA, Some(Allocation::new(25, 5))),
+				(FOREIGN, Some(Allocation::new(25, 5))),
+			]
+		));
+
+		// AUSD: |0.25 - 0.4| * 0.5 = 0.075
+		// ACA: |0.5 - 0.3| * 0.25 = 0.05
+		// FOREIGN: 0.25 within [0.2, 0.3]
+		assert_eq!(
+			AquaDAO::drift_score(),
+			Ok(FixedU128::saturating_from_rational(125, 1_000))
+		);
+	});
+}
+
 #[test]
 fn on_initialize_no_allocations() {
 	ExtBuilder::default().build().execute_with(|| {
@@ -317,12 +613,13 @@ fn on_initialize_no_allocations() {
 		assert_eq!(Currencies::free_balance(AUSD, &DAO), 1_000_000);
 
 		// Nothing happens when no allocations are set and no strategies are set
+		MockOnLiquidityPoolUpdated::reset();
 		run_to_block(4);
+		MockOnLiquidityPoolUpdated::assert_no_updates();
 		assert_eq!(Currencies::free_balance(AUSD, &DAO), 1_000_000);
 
-		set_test_strategies();
-		// Check strategies are set
-		assert_eq!(Strategies::<Runtime>::get().len(), 2);
+		// strategies without allocations, for instance orphaned by allocation removals
+		Strategies::<Runtime>::put(test_strategies());
 
 		// Nothing happens when no allocations are set
 		run_to_block(8);
@@ -333,6 +630,7 @@ fn on_initialize_no_allocations() {
 #[test]
 fn rebalance_ausd_other_works() {
 	ExtBuilder::default().build().execute_with(|| {
+		seed_pool(ACA);
 		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
 			AUSD, &DAO, 1_000_000
 		));
@@ -340,8 +638,8 @@ fn rebalance_ausd_other_works() {
 			ACA, &DAO, 1_000_000
 		));
 
-		let alloc = Allocation { value: 100, range: 10 };
-		let alloc2 = Allocation { value: 50, range: 5 };
+		let alloc = Allocation::new(100, 10);
+		let alloc2 = Allocation::new(50, 5);
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![(AUSD, Some(alloc)), (ACA, Some(alloc2)), (ACA_AUSD_LP, Some(alloc))]
@@ -353,6 +651,7 @@ fn rebalance_ausd_other_works() {
 			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
 			max_amount_per_trade: 1_000_000,
 			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
 		};
 
 		assert_eq!(
@@ -401,11 +700,13 @@ fn rebalance_ausd_other_works() {
 #[test]
 fn rebalance_ausd_adao_works() {
 	ExtBuilder::default().build().execute_with(|| {
+		seed_pool(ADAO);
 		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
 			AUSD, &DAO, 1_000_000
 		));
+		let adao_issuance = Currencies::total_issuance(ADAO);
 
-		let alloc = Allocation { value: 100, range: 10 };
+		let alloc = Allocation::new(100, 10);
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![(AUSD, Some(alloc)), (ADAO_AUSD_LP, Some(alloc))]
@@ -417,6 +718,7 @@ fn rebalance_ausd_adao_works() {
 			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
 			max_amount_per_trade: 1_000_000,
 			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
 		};
 
 		assert_eq!(
@@ -446,70 +748,443 @@ fn rebalance_ausd_adao_works() {
 		assert_ok!(AquaDAO::rebalance(&strategy, diff.clone()));
 		// LP token deposited into dao account
 		assert_eq!(Currencies::free_balance(ADAO_AUSD_LP, &DAO), 406_250);
+
+		// minted ADAO recorded: 125_000 + 78_125
+		assert_eq!(AquaDAO::mint_ledger(MintReason::PolRebalance), 203_125);
+		assert_eq!(AquaDAO::total_minted(), 203_125);
+		assert_eq!(Currencies::total_issuance(ADAO), adao_issuance + AquaDAO::total_minted());
 	});
 }
 
 #[test]
-fn alternates_strategies_correctly() {
+fn rebalance_ausd_adao_records_pol_mint_history() {
 	ExtBuilder::default().build().execute_with(|| {
-		// Set Balances for DaoAccount
+		seed_pool(ADAO);
+		System::set_block_number(1);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (ADAO_AUSD_LP, Some(alloc))]
+		));
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionAusdAdao,
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+
+		let mut expected = vec![];
+		for block in [1, 2] {
+			System::set_block_number(block);
+			let adao_issuance = Currencies::total_issuance(ADAO);
+			let ausd = Currencies::free_balance(AUSD, &DAO);
+			let lp = Currencies::free_balance(ADAO_AUSD_LP, &DAO);
+
+			assert_ok!(AquaDAO::rebalance(&strategy, AquaDAO::allocation_diff().unwrap()));
+
+			let adao_minted = Currencies::total_issuance(ADAO) - adao_issuance;
+			let ausd_paired = ausd - Currencies::free_balance(AUSD, &DAO);
+			let lp_received = Currencies::free_balance(ADAO_AUSD_LP, &DAO) - lp;
+			assert!(adao_minted > 0 && ausd_paired > 0 && lp_received > 0);
+			System::assert_last_event(Event::AquaDAO(crate::Event::Rebalanced {
+				adao_minted,
+				ausd_paired,
+				lp_received,
+			}));
+			expected.push((block, adao_minted, ausd_paired, lp_received));
+		}
+		assert_eq!(AquaDAO::pol_mint_history().into_inner(), expected);
+	});
+}
+
+#[test]
+fn rebalance_limits_pool_impact() {
+	// DAO account plans to add 100_000 AUSD and 100_000 ACA, to a pool with `reserve` of each
+	let setup = |reserve: Balance| {
+		System::set_block_number(1);
 		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
 			AUSD, &DAO, 1_000_000
 		));
 		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
 			ACA, &DAO, 1_000_000
 		));
-		set_test_strategies();
-
-		let alloc = Allocation { value: 100, range: 10 };
+		let alloc = Allocation::new(100, 10);
+		let alloc2 = Allocation::new(50, 5);
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
-			vec![
-				(AUSD, Some(alloc)),
-				(ACA, Some(alloc)),
-				(ACA_AUSD_LP, Some(alloc)),
-				(ADAO_AUSD_LP, Some(alloc))
-			]
+			vec![(AUSD, Some(alloc)), (ACA, Some(alloc2)), (ACA_AUSD_LP, Some(alloc))]
 		));
-		run_to_block(2);
+		assert_ok!(DexModule::add_liquidity(
+			Origin::signed(ALICE),
+			AUSD,
+			ACA,
+			reserve,
+			reserve,
+			0,
+			false
+		));
+		MockMaxPoolImpactRatio::set(Ratio::saturating_from_rational(1, 5));
+	};
+	let mut strategy = Strategy {
+		kind: StrategyKind::LiquidityProvisionAusdOther(TokenSymbol::ACA),
+		percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+		max_amount_per_trade: 1_000_000,
+		min_amount_per_trade: -1_000_000,
+		skip_on_thin_pool: true,
+	};
+	let trading_pair = strategy.kind.trading_pair().unwrap();
 
-		// Nothing happens as offset is 1 so only will rebalance on odd blocks
+	ExtBuilder::default().build().execute_with(|| {
+		setup(100_000);
+		let diff = AquaDAO::allocation_diff().unwrap();
+		let planned = AquaDAO::plan_trade(&strategy, &diff).unwrap().unwrap();
+		assert_eq!((planned.other_amount, planned.stable_amount), (100_000, 100_000));
+
+		// skipped by strategy
+		assert_ok!(AquaDAO::rebalance(&strategy, diff.clone()));
+		System::assert_last_event(Event::AquaDAO(crate::Event::PoolImpactLimited {
+			trading_pair,
+			planned_stable_amount: 100_000,
+			stable_amount: 0,
+		}));
 		assert_eq!(Currencies::free_balance(AUSD, &DAO), 1_000_000);
-		assert_eq!(Currencies::free_balance(ACA, &DAO), 1_000_000);
-		run_to_block(3);
+		assert_eq!(DexModule::get_liquidity_pool(ACA, AUSD), (100_000, 100_000));
 
-		// rebalance with ausd and other token (ACA in this case)
-		assert_eq!(Currencies::free_balance(AUSD, &DAO), 875_000);
-		assert_eq!(Currencies::free_balance(ACA, &DAO), 875_000);
-		run_to_block(5);
+		// scaled down to 20% of reserves
+		strategy.skip_on_thin_pool = false;
+		assert_ok!(AquaDAO::rebalance(&strategy, diff));
+		System::assert_has_event(Event::AquaDAO(crate::Event::PoolImpactLimited {
+			trading_pair,
+			planned_stable_amount: 100_000,
+			stable_amount: 20_000,
+		}));
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 980_000);
+		assert_eq!(DexModule::get_liquidity_pool(ACA, AUSD), (120_000, 120_000));
+	});
 
-		// rebalance with ausd and adao
-		assert_eq!(Currencies::free_balance(AUSD, &DAO), 750_000);
-		assert_eq!(Currencies::free_balance(ACA, &DAO), 875_000);
+	// deep pool is not limited
+	ExtBuilder::default().build().execute_with(|| {
+		setup(900_000);
+		assert_ok!(AquaDAO::rebalance(&strategy, AquaDAO::allocation_diff().unwrap()));
+		assert!(!System::events()
+			.iter()
+			.any(|record| matches!(record.event, Event::AquaDAO(crate::Event::PoolImpactLimited { .. }))));
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 900_000);
+		assert_eq!(DexModule::get_liquidity_pool(ACA, AUSD), (1_000_000, 1_000_000));
 	});
 }
 
 #[test]
-fn allocate_with_no_funds() {
+fn rebalance_keeps_min_stable_buffer() {
 	ExtBuilder::default().build().execute_with(|| {
-		set_test_strategies();
+		seed_pool(ADAO);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
 
-		let alloc = Allocation { value: 100, range: 10 };
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![
-				(AUSD, Some(alloc)),
-				(ACA, Some(alloc)),
-				(ACA_AUSD_LP, Some(alloc)),
-				(ADAO_AUSD_LP, Some(alloc))
+				(AUSD, Some(Allocation::new(100, 10))),
+				(ADAO_AUSD_LP, Some(Allocation::new(300, 10))),
 			]
 		));
-		System::reset_events();
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionAusdAdao,
+			percent_per_trade: FixedU128::one(),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
 
-		run_to_block(5);
+		// balance below buffer, nothing to trade
+		MockMinStableBuffer::set(2_000_000);
+		let diff = AquaDAO::allocation_diff().unwrap();
+		assert_ok!(AquaDAO::rebalance(&strategy, diff));
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 1_000_000);
+		assert_eq!(Currencies::free_balance(ADAO_AUSD_LP, &DAO), 0);
+
+		// AUSD diff amount is 750_000, but only 500_000 above buffer can be used, half of which is
+		// added with the same value of ADAO
+		MockMinStableBuffer::set(500_000);
+		let diff = AquaDAO::allocation_diff().unwrap();
+		assert_eq!(diff.get(&AUSD).unwrap().diff_amount, 750_000);
+		assert_ok!(AquaDAO::rebalance(&strategy, diff));
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 750_000);
+		assert_eq!(AquaDAO::total_minted(), 250_000);
+	});
+}
+
+#[test]
+fn alternates_strategies_correctly() {
+	ExtBuilder::default().build().execute_with(|| {
+		seed_pool(ACA);
+		seed_pool(ADAO);
+		// Set Balances for DaoAccount
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			ACA, &DAO, 1_000_000
+		));
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![
+				(AUSD, Some(alloc)),
+				(ACA, Some(alloc)),
+				(ACA_AUSD_LP, Some(alloc)),
+				(ADAO_AUSD_LP, Some(alloc))
+			]
+		));
+		set_test_strategies();
+		run_to_block(2);
+
+		// Nothing happens as offset is 1 so only will rebalance on odd blocks
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 1_000_000);
+		assert_eq!(Currencies::free_balance(ACA, &DAO), 1_000_000);
+		run_to_block(3);
+
+		// rebalance with ausd and other token (ACA in this case)
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 875_000);
+		assert_eq!(Currencies::free_balance(ACA, &DAO), 875_000);
+		assert_eq!(MockOnLiquidityPoolUpdated::updates(), 1);
+		assert_eq!(
+			MockOnLiquidityPoolUpdated::last_update().map(|(pair, _, _)| pair),
+			TradingPair::from_currency_ids(AUSD, ACA)
+		);
+		run_to_block(5);
+
+		// rebalance with ausd and adao
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 750_000);
+		assert_eq!(Currencies::free_balance(ACA, &DAO), 875_000);
+		assert_eq!(MockOnLiquidityPoolUpdated::updates(), 2);
+		assert_eq!(
+			MockOnLiquidityPoolUpdated::last_update().map(|(pair, _, _)| pair),
+			TradingPair::from_currency_ids(AUSD, ADAO)
+		);
+		assert_eq!(MockPriceSource::last_relative_price_query(), Some((ADAO, AUSD)));
+	});
+}
+
+#[test]
+fn strategy_stats_accumulate_and_reset() {
+	ExtBuilder::default().build().execute_with(|| {
+		seed_pool(ACA);
+		seed_pool(ADAO);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			ACA, &DAO, 1_000_000
+		));
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![
+				(AUSD, Some(alloc)),
+				(ACA, Some(alloc)),
+				(ACA_AUSD_LP, Some(alloc)),
+				(ADAO_AUSD_LP, Some(alloc))
+			]
+		));
+		set_test_strategies();
+		let [adao_strategy, aca_strategy] = [test_strategies()[0], test_strategies()[1]];
+
+		// ACA strategy on block 3, ADAO strategy on block 5
+		run_to_block(5);
+		assert_eq!(
+			AquaDAO::strategy_stats(aca_strategy.kind),
+			StrategyExecutionStats {
+				times_executed: 1,
+				total_stable_deployed: 125_000,
+				total_lp_acquired: Currencies::free_balance(ACA_AUSD_LP, &DAO),
+			}
+		);
+		assert_eq!(
+			AquaDAO::strategy_stats(adao_strategy.kind),
+			StrategyExecutionStats {
+				times_executed: 1,
+				total_stable_deployed: 125_000,
+				total_lp_acquired: Currencies::free_balance(ADAO_AUSD_LP, &DAO),
+			}
+		);
+
+		run_to_block(9);
+		let aca_stats = AquaDAO::strategy_stats(aca_strategy.kind);
+		let adao_stats = AquaDAO::strategy_stats(adao_strategy.kind);
+		assert_eq!((aca_stats.times_executed, adao_stats.times_executed), (2, 2));
+		assert_eq!(
+			aca_stats.total_stable_deployed + adao_stats.total_stable_deployed,
+			1_000_000 - Currencies::free_balance(AUSD, &DAO)
+		);
+		assert_eq!(aca_stats.total_lp_acquired, Currencies::free_balance(ACA_AUSD_LP, &DAO));
+		assert_eq!(adao_stats.total_lp_acquired, Currencies::free_balance(ADAO_AUSD_LP, &DAO));
+
+		// removed strategy is reset, the unchanged one is kept
+		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![adao_strategy]));
+		assert_eq!(AquaDAO::strategy_stats(aca_strategy.kind), Default::default());
+		assert_eq!(AquaDAO::strategy_stats(adao_strategy.kind), adao_stats);
+
+		// replaced strategy is reset
+		let replaced = Strategy {
+			percent_per_trade: FixedU128::saturating_from_rational(1, 4),
+			..adao_strategy
+		};
+		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![replaced]));
+		assert_eq!(AquaDAO::strategy_stats(adao_strategy.kind), Default::default());
+	});
+}
+
+#[test]
+fn strategy_rotation_survives_strategy_removal() {
+	ExtBuilder::default().build().execute_with(|| {
+		seed_pool(ADAO);
+		seed_pool(ACA);
+		seed_pool(FOREIGN);
+		for currency_id in [AUSD, ACA, FOREIGN] {
+			assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+				currency_id,
+				&DAO,
+				1_000_000
+			));
+		}
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![
+				(AUSD, Some(alloc)),
+				(ACA, Some(alloc)),
+				(FOREIGN, Some(alloc)),
+				(ADAO_AUSD_LP, Some(alloc)),
+				(ACA_AUSD_LP, Some(alloc)),
+				(FOREIGN_AUSD_LP, Some(alloc)),
+			]
+		));
+
+		let strategy = |kind| Strategy {
+			kind,
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		let adao = strategy(StrategyKind::LiquidityProvisionAusdAdao);
+		let aca = strategy(StrategyKind::LiquidityProvisionAusdOther(TokenSymbol::ACA));
+		let foreign = strategy(StrategyKind::LiquidityProvisionWith(FOREIGN));
+		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![adao, aca, foreign]));
+		let last_pair = || MockOnLiquidityPoolUpdated::last_update().map(|(pair, _, _)| pair);
+
+		// cursor initialized by block number: 3 / 2 = 1
+		run_to_block(3);
+		assert_eq!(last_pair(), TradingPair::from_currency_ids(AUSD, ACA));
+		assert_eq!(AquaDAO::next_strategy_cursor(), Some(2));
+
+		// remove the strategy just run, the next one is still `foreign`
+		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![adao, foreign]));
+		assert_eq!(AquaDAO::next_strategy_cursor(), Some(1));
+
+		run_to_block(5);
+		assert_eq!(last_pair(), TradingPair::from_currency_ids(AUSD, FOREIGN));
+		run_to_block(7);
+		assert_eq!(last_pair(), TradingPair::from_currency_ids(AUSD, ADAO));
+		run_to_block(9);
+		assert_eq!(last_pair(), TradingPair::from_currency_ids(AUSD, FOREIGN));
+		assert_eq!(AquaDAO::next_strategy_cursor(), Some(0));
+	});
+}
+
+#[test]
+fn rebalance_period_change_restarts_schedule() {
+	ExtBuilder::default().build().execute_with(|| {
+		for currency_id in [AUSD, ACA, FOREIGN] {
+			assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+				currency_id,
+				&DAO,
+				1_000_000
+			));
+		}
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![
+				(AUSD, Some(alloc)),
+				(ACA, Some(alloc)),
+				(FOREIGN, Some(alloc)),
+				(ADAO_AUSD_LP, Some(alloc)),
+				(ACA_AUSD_LP, Some(alloc)),
+				(FOREIGN_AUSD_LP, Some(alloc)),
+			]
+		));
+		let strategy = |kind| Strategy {
+			kind,
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		assert_ok!(AquaDAO::set_strategies(
+			Origin::signed(ALICE),
+			vec![
+				strategy(StrategyKind::LiquidityProvisionAusdAdao),
+				strategy(StrategyKind::LiquidityProvisionAusdOther(TokenSymbol::ACA)),
+				strategy(StrategyKind::LiquidityProvisionWith(FOREIGN)),
+			]
+		));
+
+		// rebalanced on blocks 3, 5, 7, 9
+		run_to_block(9);
+		assert_eq!(AquaDAO::rebalance_schedule(), Some((2, 1, 0)));
+		assert_eq!(AquaDAO::next_strategy_cursor(), Some(2));
+
+		// every rebalance moves the strategy cursor
+		MockRebalancePeriod::set(5);
+		let mut rebalanced_at = vec![];
+		for block in 10..=16 {
+			let cursor = AquaDAO::next_strategy_cursor();
+			run_to_block(block);
+			if AquaDAO::next_strategy_cursor() != cursor {
+				rebalanced_at.push(block);
+			}
+		}
+		System::assert_has_event(Event::AquaDAO(crate::Event::RebalanceScheduleChanged {
+			period: 5,
+			offset: 1,
+			since: 10,
+		}));
+		assert_eq!(AquaDAO::rebalance_schedule(), Some((5, 1, 10)));
+		// one rebalance in the transition window, continuing the rotation from the stored cursor
+		assert_eq!(rebalanced_at, vec![11, 16]);
+		assert_eq!(AquaDAO::next_strategy_cursor(), Some(1));
+	});
+}
+
+#[test]
+fn allocate_with_no_funds() {
+	ExtBuilder::default().build().execute_with(|| {
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![
+				(AUSD, Some(alloc)),
+				(ACA, Some(alloc)),
+				(ACA_AUSD_LP, Some(alloc)),
+				(ADAO_AUSD_LP, Some(alloc))
+			]
+		));
+		set_test_strategies();
+		System::reset_events();
+
+		run_to_block(5);
 
 		// rebalance will error out and no liquidity is added to pools
 		assert_eq!(System::events(), vec![]);
+		MockOnLiquidityPoolUpdated::assert_no_updates();
 		assert_eq!(
 			DexModule::get_liquidity_pool(
 				CurrencyId::Token(TokenSymbol::ADAO),
@@ -530,10 +1205,8 @@ fn allocate_with_no_funds() {
 #[test]
 fn zero_amount_allocations_test() {
 	ExtBuilder::default().build().execute_with(|| {
-		set_test_strategies();
-
-		let alloc = Allocation { value: 0, range: 10 };
-		let alloc2 = Allocation { value: 1, range: 10 };
+		let alloc = Allocation::new(0, 10);
+		let alloc2 = Allocation::new(1, 10);
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![
@@ -544,12 +1217,14 @@ fn zero_amount_allocations_test() {
 				(ADAO_AUSD_LP, Some(alloc))
 			]
 		));
+		set_test_strategies();
 
 		System::reset_events();
 		run_to_block(5);
 
 		// rebalance will error out and no liquidity is added to pools
 		assert_eq!(System::events(), vec![]);
+		MockOnLiquidityPoolUpdated::assert_no_updates();
 		assert_eq!(
 			DexModule::get_liquidity_pool(
 				CurrencyId::Token(TokenSymbol::ADAO),
@@ -577,9 +1252,7 @@ fn on_initialize_max_greater_than_one() {
 		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
 			ACA, &DAO, 1_000_000
 		));
-		set_test_strategies();
-
-		let alloc = Allocation { value: 100, range: 200 };
+		let alloc = Allocation::new(100, 200);
 		assert_ok!(AquaDAO::set_target_allocations(
 			Origin::signed(ALICE),
 			vec![
@@ -589,6 +1262,7 @@ fn on_initialize_max_greater_than_one() {
 				(ADAO_AUSD_LP, Some(alloc))
 			]
 		));
+		set_test_strategies();
 
 		// Nothing happens due to range being larger than value in allocation
 		run_to_block(5);
@@ -596,3 +1270,689 @@ fn on_initialize_max_greater_than_one() {
 		assert_eq!(Currencies::free_balance(ACA, &DAO), 1_000_000);
 	});
 }
+
+#[test]
+fn rebalance_skips_not_enabled_trading_pair() {
+	ExtBuilder::default().build().execute_with(|| {
+		seed_pool(ACA);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			ACA, &DAO, 1_000_000
+		));
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![
+				(AUSD, Some(alloc)),
+				(ACA, Some(alloc)),
+				(ACA_AUSD_LP, Some(alloc)),
+				(ADAO_AUSD_LP, Some(alloc))
+			]
+		));
+		set_test_strategies();
+		assert_ok!(DexModule::disable_trading_pair(Origin::signed(ALICE), AUSD, ADAO));
+		run_to_block(3);
+
+		// rebalance with ausd and other token (ACA in this case)
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 875_000);
+		assert_eq!(Currencies::free_balance(ACA, &DAO), 875_000);
+		run_to_block(5);
+
+		// AUSD/ADAO is not enabled, rebalance skipped
+		System::assert_last_event(Event::AquaDAO(crate::Event::TradingPairNotEnabled {
+			trading_pair: TradingPair::from_currency_ids(AUSD, ADAO).unwrap(),
+		}));
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 875_000);
+		assert_eq!(Currencies::free_balance(ADAO_AUSD_LP, &DAO), 0);
+	});
+}
+
+#[test]
+fn pol_positions_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionAusdAdao,
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (ADAO_AUSD_LP, Some(alloc))]
+		));
+		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![strategy]));
+		let trading_pair = TradingPair::from_currency_ids(AUSD, ADAO).unwrap();
+		assert_eq!(AquaDAO::pol_positions(), vec![(trading_pair, 0, 0)]);
+		assert_ok!(AquaDAO::bootstrap_pool(Origin::signed(ALICE), ADAO, 10_000, 10_000));
+
+		run_to_block(3);
+
+		// DAO account owns all the LP shares
+		let (reserve_0, reserve_1) = DexModule::get_liquidity_pool(trading_pair.first(), trading_pair.second());
+		assert!(!reserve_0.is_zero() && !reserve_1.is_zero());
+		assert_eq!(
+			AquaDAO::pol_positions(),
+			vec![(trading_pair, reserve_0, reserve_1)]
+		);
+		assert_eq!(AquaDAO::pol_positions_snapshot(), AquaDAO::pol_positions());
+	});
+}
+
+#[test]
+fn simulate_rebalance_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		seed_pool(ADAO);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionAusdAdao,
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (ADAO_AUSD_LP, Some(alloc))]
+		));
+		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![strategy]));
+
+		assert_noop!(AquaDAO::simulate_rebalance(Origin::signed(BOB), 0), BadOrigin);
+		assert_noop!(
+			AquaDAO::simulate_rebalance(Origin::signed(ALICE), 1),
+			Error::<Runtime>::StrategyNotFound
+		);
+
+		assert_ok!(AquaDAO::simulate_rebalance(Origin::signed(ALICE), 0));
+		System::assert_last_event(Event::AquaDAO(crate::Event::RebalanceSimulated {
+			strategy,
+			currency_id: ADAO_AUSD_LP,
+			amounts: (125_000, 125_000),
+		}));
+		// no balance changes
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 1_000_000);
+		assert_eq!(Currencies::total_issuance(ADAO), 2_000_000);
+		assert_eq!(DexModule::get_liquidity_pool(ADAO, AUSD), (1_000_000, 1_000_000));
+
+		// no trade on a disabled trading pair, as in real rebalance
+		assert_ok!(DexModule::disable_trading_pair(Origin::signed(ALICE), AUSD, ADAO));
+		assert_ok!(AquaDAO::simulate_rebalance(Origin::signed(ALICE), 0));
+		System::assert_last_event(Event::AquaDAO(crate::Event::RebalanceSimulated {
+			strategy,
+			currency_id: ADAO_AUSD_LP,
+			amounts: (0, 0),
+		}));
+		assert_ok!(DexModule::enable_trading_pair(Origin::signed(ALICE), AUSD, ADAO));
+
+		// real rebalance matches the simulation
+		let diff = AquaDAO::allocation_diff().unwrap();
+		assert_ok!(AquaDAO::rebalance(&strategy, diff));
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 875_000);
+		assert_eq!(DexModule::get_liquidity_pool(ADAO, AUSD), (1_125_000, 1_125_000));
+	});
+}
+
+#[test]
+fn pending_target_allocations_applied_at_activation_block() {
+	ExtBuilder::default().build().execute_with(|| {
+		seed_pool(ADAO);
+		System::set_block_number(1);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionAusdAdao,
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		MockEnactmentDelay::set(10);
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (ADAO_AUSD_LP, Some(alloc))]
+		));
+		System::assert_last_event(Event::AquaDAO(crate::Event::PendingUpdateScheduled {
+			kind: PendingUpdateKind::TargetAllocations,
+			activate_at: 11,
+		}));
+		// strategies can be set for pending target allocations
+		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![strategy]));
+		assert!(AquaDAO::target_allocations().is_empty());
+
+		// rebalance blocks before activation are not affected
+		run_to_block(10);
+		assert!(AquaDAO::target_allocations().is_empty());
+		assert_eq!(Currencies::free_balance(ADAO_AUSD_LP, &DAO), 0);
+
+		run_to_block(11);
+		assert_eq!(AquaDAO::pending_target_allocations(), None);
+		assert_eq!(AquaDAO::target_allocations().get(&AUSD), Some(&alloc));
+		System::assert_has_event(Event::AquaDAO(crate::Event::PendingUpdateApplied {
+			kind: PendingUpdateKind::TargetAllocations,
+		}));
+		assert_eq!(AquaDAO::strategies(), vec![strategy]);
+		assert!(Currencies::free_balance(ADAO_AUSD_LP, &DAO) > 0);
+	});
+}
+
+#[test]
+fn cancel_pending_update_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionAusdAdao,
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		// strategy without allocation
+		Strategies::<Runtime>::put(vec![strategy]);
+
+		MockEnactmentDelay::set(10);
+		assert_noop!(
+			AquaDAO::cancel_pending_update(Origin::signed(ALICE)),
+			Error::<Runtime>::NoPendingUpdate
+		);
+
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (ADAO_AUSD_LP, Some(alloc))]
+		));
+		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![]));
+
+		assert_noop!(AquaDAO::cancel_pending_update(Origin::signed(BOB)), BadOrigin);
+		assert_ok!(AquaDAO::cancel_pending_update(Origin::signed(ALICE)));
+		System::assert_has_event(Event::AquaDAO(crate::Event::PendingUpdateCancelled {
+			kind: PendingUpdateKind::TargetAllocations,
+		}));
+		System::assert_has_event(Event::AquaDAO(crate::Event::PendingUpdateCancelled {
+			kind: PendingUpdateKind::Strategies,
+		}));
+
+		run_to_block(20);
+		assert!(AquaDAO::target_allocations().is_empty());
+		assert_eq!(AquaDAO::strategies(), vec![strategy]);
+		assert_eq!(Currencies::free_balance(ADAO_AUSD_LP, &DAO), 0);
+	});
+}
+
+#[test]
+fn set_target_allocations_fails_if_unknown_foreign_asset_decimals() {
+	ExtBuilder::default().build().execute_with(|| {
+		let alloc = Allocation::new(100, 10);
+		assert_noop!(
+			AquaDAO::set_target_allocations(
+				Origin::signed(ALICE),
+				vec![(AUSD, Some(alloc)), (CurrencyId::ForeignAsset(1), Some(alloc))]
+			),
+			Error::<Runtime>::UnknownCurrencyDecimals
+		);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (FOREIGN, Some(alloc))]
+		));
+	});
+}
+
+#[test]
+fn rebalance_with_foreign_asset_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		seed_pool(FOREIGN);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			FOREIGN, &DAO, 1_000_000
+		));
+
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![
+				(AUSD, Some(alloc)),
+				(FOREIGN, Some(alloc)),
+				(FOREIGN_AUSD_LP, Some(alloc))
+			]
+		));
+
+		let diff = AquaDAO::allocation_diff().unwrap();
+		assert_eq!(diff.get(&FOREIGN).unwrap().current, FixedU128::saturating_from_rational(1, 2));
+		assert!(diff.get(&FOREIGN_AUSD_LP).unwrap().range_diff < FixedI128::zero());
+
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionWith(FOREIGN),
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		assert_ok!(AquaDAO::rebalance(&strategy, diff));
+
+		assert!(Currencies::free_balance(FOREIGN_AUSD_LP, &DAO) > 0);
+		assert!(Currencies::free_balance(FOREIGN, &DAO) < 1_000_000);
+		assert!(Currencies::free_balance(AUSD, &DAO) < 1_000_000);
+	});
+}
+
+#[test]
+fn rebalance_window_rotates_over_all_allocations() {
+	ExtBuilder::default().build().execute_with(|| {
+		MockMaxAllocationsPerRebalance::set(4);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+
+		// AUSD and 9 foreign assets
+		let alloc = Allocation::new(100, 10);
+		let mut targets = BTreeMap::from([(AUSD, alloc)]);
+		for i in 0..9 {
+			let currency_id = CurrencyId::ForeignAsset(i);
+			assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+				currency_id,
+				&DAO,
+				1_000
+			));
+			targets.insert(currency_id, alloc);
+		}
+		TargetAllocations::<Runtime>::put(targets.clone());
+		assert_ok!(AquaDAO::update_target_allocation_percents());
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionWith(FOREIGN),
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		Strategies::<Runtime>::put(vec![strategy]);
+
+		let mut evaluated = BTreeSet::new();
+		for (i, block) in [3, 5, 7].into_iter().enumerate() {
+			assert_eq!(AquaDAO::rebalance_cursor(), 3 * i as u32);
+			let window = AquaDAO::rebalance_window();
+			assert_eq!(window.len(), 4);
+			assert!(window.contains(&AUSD));
+			let diff = AquaDAO::allocation_diff_in(&window).unwrap();
+			assert_eq!(diff.keys().copied().collect::<BTreeSet<_>>(), window.iter().copied().collect());
+			evaluated.extend(window);
+
+			run_to_block(block);
+		}
+
+		// every currency evaluated, and the window wraps around
+		assert_eq!(evaluated, targets.into_keys().collect());
+		assert_eq!(AquaDAO::rebalance_cursor(), 0);
+	});
+}
+
+#[test]
+fn rebalance_window_rotates_over_all_allocations_without_stable_target() {
+	ExtBuilder::default().build().execute_with(|| {
+		MockMaxAllocationsPerRebalance::set(4);
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+
+		// 10 foreign assets, and no AUSD
+		let alloc = Allocation::new(100, 10);
+		let mut targets = BTreeMap::new();
+		for i in 0..10 {
+			let currency_id = CurrencyId::ForeignAsset(i);
+			assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+				currency_id,
+				&DAO,
+				1_000
+			));
+			targets.insert(currency_id, alloc);
+		}
+		TargetAllocations::<Runtime>::put(targets.clone());
+		assert_ok!(AquaDAO::update_target_allocation_percents());
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionWith(FOREIGN),
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		Strategies::<Runtime>::put(vec![strategy]);
+
+		let mut evaluated = BTreeSet::new();
+		for (i, block) in [3, 5, 7].into_iter().enumerate() {
+			assert_eq!(AquaDAO::rebalance_cursor(), 4 * i as u32);
+			let window = AquaDAO::rebalance_window();
+			assert_eq!(window.len(), 4);
+			assert!(!window.contains(&AUSD));
+			evaluated.extend(window);
+
+			run_to_block(block);
+		}
+
+		// every currency evaluated, including the last one
+		assert_eq!(evaluated, targets.into_keys().collect());
+		assert_eq!(AquaDAO::rebalance_cursor(), 2);
+	});
+}
+
+fn dot_acquisition_strategy() -> AcquisitionStrategy {
+	AcquisitionStrategy {
+		percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+		max_amount_per_trade: 100_000,
+		min_amount_per_trade: 0,
+	}
+}
+
+#[test]
+fn set_acquisition_strategy_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		let strategy = dot_acquisition_strategy();
+		assert_noop!(
+			AquaDAO::set_acquisition_strategy(Origin::signed(BOB), DOT, Some(strategy)),
+			BadOrigin
+		);
+		for currency_id in [AUSD, ADAO, SDAO, ACA_AUSD_LP] {
+			assert_noop!(
+				AquaDAO::set_acquisition_strategy(Origin::signed(ALICE), currency_id, Some(strategy)),
+				Error::<Runtime>::InvalidAcquisition
+			);
+		}
+
+		assert_ok!(AquaDAO::set_acquisition_strategy(
+			Origin::signed(ALICE),
+			DOT,
+			Some(strategy)
+		));
+		assert_eq!(AquaDAO::acquisition_strategies(DOT), Some(strategy));
+		System::assert_last_event(Event::AquaDAO(crate::Event::AcquisitionStrategySet {
+			currency_id: DOT,
+			strategy: Some(strategy),
+		}));
+
+		assert_ok!(AquaDAO::set_acquisition_strategy(Origin::signed(ALICE), DOT, None));
+		assert_eq!(AquaDAO::acquisition_strategies(DOT), None);
+	});
+}
+
+#[test]
+fn rebalance_acquires_zero_balance_target() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &ALICE, 10_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			DOT, &ALICE, 10_000_000
+		));
+		assert_ok!(DexModule::add_liquidity(
+			Origin::signed(ALICE),
+			AUSD,
+			DOT,
+			10_000_000,
+			10_000_000,
+			0,
+			false
+		));
+
+		let alloc = Allocation::new(50, 5);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (DOT, Some(alloc))]
+		));
+		assert_ok!(AquaDAO::set_acquisition_strategy(
+			Origin::signed(ALICE),
+			DOT,
+			Some(dot_acquisition_strategy())
+		));
+		assert_eq!(Currencies::free_balance(DOT, &DAO), 0);
+
+		// bought with `max_amount_per_trade` AUSD per period
+		run_to_block(3);
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 900_000);
+		let first = Currencies::free_balance(DOT, &DAO);
+		System::assert_has_event(Event::AquaDAO(crate::Event::Acquired {
+			currency_id: DOT,
+			stable_amount: 100_000,
+			amount: first,
+		}));
+		assert_eq!(AquaDAO::unserviced_periods(DOT), 0);
+
+		run_to_block(5);
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 800_000);
+		assert!(Currencies::free_balance(DOT, &DAO) > first);
+
+		// stops once within target range
+		run_to_block(21);
+		assert_eq!(
+			AquaDAO::allocation_diff().unwrap().get(&DOT).unwrap().range_diff,
+			FixedI128::zero()
+		);
+		let ausd = Currencies::free_balance(AUSD, &DAO);
+		System::reset_events();
+		run_to_block(23);
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), ausd);
+		assert!(!System::events()
+			.iter()
+			.any(|record| matches!(record.event, Event::AquaDAO(crate::Event::Acquired { .. }))));
+	});
+}
+
+#[test]
+fn rebalance_reports_unserviced_target() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		let alloc = Allocation::new(50, 5);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (DOT, Some(alloc))]
+		));
+		// no DOT/AUSD liquidity to buy from
+		assert_ok!(AquaDAO::set_acquisition_strategy(
+			Origin::signed(ALICE),
+			DOT,
+			Some(dot_acquisition_strategy())
+		));
+
+		// rebalance on odd blocks, `MaxUnservicedPeriods` is 10
+		run_to_block(21);
+		assert_eq!(AquaDAO::unserviced_periods(DOT), 10);
+		assert_eq!(AquaDAO::unserviced_periods(AUSD), 0);
+		assert!(!System::events()
+			.iter()
+			.any(|record| matches!(record.event, Event::AquaDAO(crate::Event::TargetUnserviced { .. }))));
+
+		run_to_block(23);
+		System::assert_last_event(Event::AquaDAO(crate::Event::TargetUnserviced {
+			currency_id: DOT,
+			periods: 11,
+		}));
+
+		// deposited once
+		System::reset_events();
+		run_to_block(25);
+		assert_eq!(AquaDAO::unserviced_periods(DOT), 12);
+		assert_eq!(System::events(), vec![]);
+
+		// cleared on target removal
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(DOT, None)]
+		));
+		assert_eq!(AquaDAO::unserviced_periods(DOT), 0);
+	});
+}
+
+#[test]
+fn rebalance_skips_pool_not_bootstrapped() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (ADAO_AUSD_LP, Some(alloc))]
+		));
+		let strategy = test_strategies()[0];
+		assert_ok!(AquaDAO::set_strategies(Origin::signed(ALICE), vec![strategy]));
+		let trading_pair = TradingPair::from_currency_ids(AUSD, ADAO).unwrap();
+
+		// no trade planned on the empty pool
+		assert_ok!(AquaDAO::simulate_rebalance(Origin::signed(ALICE), 0));
+		System::assert_last_event(Event::AquaDAO(crate::Event::RebalanceSimulated {
+			strategy,
+			currency_id: ADAO_AUSD_LP,
+			amounts: (0, 0),
+		}));
+
+		assert_eq!(AquaDAO::rebalance(&strategy, AquaDAO::allocation_diff().unwrap()), Ok(None));
+		System::assert_last_event(Event::AquaDAO(crate::Event::PoolNotBootstrapped { trading_pair }));
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 1_000_000);
+		assert_eq!(Currencies::total_issuance(ADAO), 0);
+		assert_eq!(DexModule::get_liquidity_pool(ADAO, AUSD), (0, 0));
+
+		run_to_block(3);
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 1_000_000);
+		MockOnLiquidityPoolUpdated::assert_no_updates();
+	});
+}
+
+#[test]
+fn bootstrap_pool_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			AUSD, &DAO, 1_000_000
+		));
+		assert_ok!(<Currencies as MultiCurrencyExtended<AccountId>>::update_balance(
+			ACA, &DAO, 1_000_000
+		));
+		let trading_pair = TradingPair::from_currency_ids(AUSD, ADAO).unwrap();
+
+		assert_noop!(
+			AquaDAO::bootstrap_pool(Origin::signed(BOB), ADAO, 200_000, 100_000),
+			BadOrigin
+		);
+		assert_noop!(
+			AquaDAO::bootstrap_pool(Origin::signed(ALICE), SDAO, 200_000, 100_000),
+			Error::<Runtime>::InvalidTradingPair
+		);
+
+		// ADAO is minted at the stated price of 0.5 AUSD
+		assert_ok!(AquaDAO::bootstrap_pool(Origin::signed(ALICE), ADAO, 200_000, 100_000));
+		assert_eq!(DexModule::get_liquidity_pool(ADAO, AUSD), (200_000, 100_000));
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 900_000);
+		assert_eq!(AquaDAO::mint_ledger(MintReason::PoolBootstrap), 200_000);
+		assert_eq!(Currencies::total_issuance(ADAO), 200_000);
+		let lp_received = Currencies::free_balance(ADAO_AUSD_LP, &DAO);
+		assert!(lp_received > 0);
+		System::assert_last_event(Event::AquaDAO(crate::Event::PoolBootstrapped {
+			trading_pair,
+			other_amount: 200_000,
+			stable_amount: 100_000,
+			lp_received,
+		}));
+		assert_noop!(
+			AquaDAO::bootstrap_pool(Origin::signed(ALICE), ADAO, 200_000, 100_000),
+			Error::<Runtime>::PoolAlreadyBootstrapped
+		);
+
+		// other currencies are paired from the DAO account
+		assert_ok!(AquaDAO::bootstrap_pool(Origin::signed(ALICE), ACA, 50_000, 50_000));
+		assert_eq!(DexModule::get_liquidity_pool(ACA, AUSD), (50_000, 50_000));
+		assert_eq!(Currencies::free_balance(ACA, &DAO), 950_000);
+		assert_eq!(Currencies::free_balance(AUSD, &DAO), 850_000);
+		assert!(Currencies::free_balance(ACA_AUSD_LP, &DAO) > 0);
+
+		// rebalance adds liquidity to the bootstrapped pool
+		let alloc = Allocation::new(100, 10);
+		assert_ok!(AquaDAO::set_target_allocations(
+			Origin::signed(ALICE),
+			vec![(AUSD, Some(alloc)), (ADAO_AUSD_LP, Some(alloc))]
+		));
+		let strategy = test_strategies()[0];
+		assert!(AquaDAO::rebalance(&strategy, AquaDAO::allocation_diff().unwrap())
+			.unwrap()
+			.is_some());
+		assert!(Currencies::free_balance(ADAO_AUSD_LP, &DAO) > lp_received);
+	});
+}
+
+#[test]
+fn call_indices_are_stable() {
+	let calls: Vec<(crate::Call<Runtime>, u8)> = vec![
+		(crate::Call::set_target_allocations { targets: vec![] }, 0),
+		(crate::Call::adjust_target_allocations { adjustments: vec![] }, 1),
+		(crate::Call::set_strategies { strategies: vec![] }, 2),
+		(crate::Call::cancel_pending_update {}, 3),
+		(crate::Call::simulate_rebalance { strategy_index: 0 }, 4),
+		(
+			crate::Call::set_acquisition_strategy {
+				currency_id: DOT,
+				strategy: None,
+			},
+			5,
+		),
+		(
+			crate::Call::bootstrap_pool {
+				currency_id: ADAO,
+				other_amount: 0,
+				stable_amount: 0,
+			},
+			6,
+		),
+	];
+	for (call, index) in calls {
+		assert_eq!(call.encode()[0], index, "{:?}", call);
+	}
+}
+
+#[test]
+fn migrate_strategies_to_v2_works() {
+	use frame_support::storage::unhashed;
+
+	ExtBuilder::default().build().execute_with(|| {
+		// v1 storage, strategies without `skip_on_thin_pool`
+		let old_strategy = (
+			StrategyKind::LiquidityProvisionAusdAdao,
+			FixedU128::saturating_from_rational(1, 2),
+			1_000_000i128,
+			-1_000_000i128,
+		);
+		unhashed::put(&Strategies::<Runtime>::hashed_key(), &vec![old_strategy]);
+		unhashed::put(
+			&PendingStrategies::<Runtime>::hashed_key(),
+			&(10 as BlockNumber, vec![old_strategy]),
+		);
+		StorageVersion::new(1).put::<AquaDAO>();
+
+		migrations::v2::migrate::<Runtime>();
+
+		let strategy = Strategy {
+			kind: StrategyKind::LiquidityProvisionAusdAdao,
+			percent_per_trade: FixedU128::saturating_from_rational(1, 2),
+			max_amount_per_trade: 1_000_000,
+			min_amount_per_trade: -1_000_000,
+			skip_on_thin_pool: false,
+		};
+		assert_eq!(StorageVersion::get::<AquaDAO>(), 2);
+		assert_eq!(AquaDAO::strategies(), vec![strategy]);
+		assert_eq!(PendingStrategies::<Runtime>::get(), Some((10, vec![strategy])));
+	});
+}
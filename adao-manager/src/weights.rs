@@ -29,8 +29,12 @@ pub trait WeightInfo {
 	fn set_target_allocations(n: u32) -> Weight;
 	fn adjust_target_allocations(n: u32) -> Weight;
 	fn set_strategies() -> Weight;
-	fn on_initialize_with_rebalance() -> Weight;
+	fn cancel_pending_update() -> Weight;
+	fn apply_pending_updates() -> Weight;
+	fn on_initialize_with_rebalance(n: u32) -> Weight;
 	fn on_initialize_without_rebalance() -> Weight;
+	fn set_acquisition_strategy() -> Weight;
+	fn bootstrap_pool() -> Weight;
 }
 
 /// Weights for ecosystem_adao_manager using the Acala node and recommended hardware.
@@ -45,12 +49,24 @@ impl<T: frame_system::Config> WeightInfo for AcalaWeight<T> {
 	fn set_strategies() -> Weight {
 		0
 	}
-	fn on_initialize_with_rebalance() -> Weight {
+	fn cancel_pending_update() -> Weight {
+		0
+	}
+	fn apply_pending_updates() -> Weight {
+		0
+	}
+	fn on_initialize_with_rebalance(_n: u32) -> Weight {
 		0
 	}
 	fn on_initialize_without_rebalance() -> Weight {
 		0
 	}
+	fn set_acquisition_strategy() -> Weight {
+		0
+	}
+	fn bootstrap_pool() -> Weight {
+		0
+	}
 }
 
 // For backwards compatibility and tests
@@ -64,10 +80,22 @@ impl WeightInfo for () {
 	fn set_strategies() -> Weight {
 		0
 	}
-	fn on_initialize_with_rebalance() -> Weight {
+	fn cancel_pending_update() -> Weight {
+		0
+	}
+	fn apply_pending_updates() -> Weight {
+		0
+	}
+	fn on_initialize_with_rebalance(_n: u32) -> Weight {
 		0
 	}
 	fn on_initialize_without_rebalance() -> Weight {
 		0
 	}
+	fn set_acquisition_strategy() -> Weight {
+		0
+	}
+	fn bootstrap_pool() -> Weight {
+		0
+	}
 }
@@ -0,0 +1,61 @@
+// This file is part of Acala.
+
+// Copyright (C) 2022 Acala Foundation.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Runtime API definition for AquaDao module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The `too_many_arguments` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::too_many_arguments)]
+// The `unnecessary_mut_passed` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::unnecessary_mut_passed)]
+
+use codec::Codec;
+use sp_runtime::FixedI128;
+
+sp_api::decl_runtime_apis! {
+	/// Subscription IDs are `u64` since version 2.
+	#[api_version(2)]
+	pub trait AquaDaoApi<AccountId, BlockNumber, MintPreview> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+		MintPreview: Codec,
+	{
+		/// The pallet account which holds subscription payments.
+		fn dao_pallet_account() -> AccountId;
+
+		#[changed_in(2)]
+		fn discount_at(subscription_id: u32, at: BlockNumber) -> Option<FixedI128>;
+
+		/// The price discount of a subscription at block `at`, if no subscribing happens before.
+		fn discount_at(subscription_id: u64, at: BlockNumber) -> Option<FixedI128>;
+
+		#[changed_in(2)]
+		fn blocks_until_discount(subscription_id: u32, target: FixedI128) -> Option<BlockNumber>;
+
+		/// The number of blocks until the price discount of a subscription reaches `target`, if no
+		/// subscribing happens before.
+		fn blocks_until_discount(subscription_id: u64, target: FixedI128) -> Option<BlockNumber>;
+
+		#[changed_in(2)]
+		fn quote_subscription(subscription_id: u32, payment_amount: u128) -> Option<(u128, FixedI128, MintPreview)>;
+
+		/// Quote the ADAO amount, price discount and mint preview of subscribing `payment_amount`
+		/// at current block.
+		fn quote_subscription(subscription_id: u64, payment_amount: u128) -> Option<(u128, FixedI128, MintPreview)>;
+	}
+}
@@ -20,27 +20,36 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{pallet_prelude::*, traits::EnsureOrigin, transactional, PalletId};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{ConstU32, Contains, EnsureOrigin},
+	transactional, BoundedVec, PalletId,
+};
 use frame_system::pallet_prelude::*;
 use sp_runtime::{
+	helpers_128bit::multiply_by_rational,
 	traits::{
 		AccountIdConversion, BlockNumberProvider, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, IntegerSquareRoot,
 		One, Saturating, UniqueSaturatedInto, Zero,
 	},
 	ArithmeticError, FixedI128, FixedPointNumber, FixedU128,
 };
-use sp_std::result::Result;
+use sp_std::{prelude::*, result::Result};
 
 use orml_traits::MultiCurrency;
 
 use acala_primitives::{
 	Balance,
 	CurrencyId::{self, Token},
-	TokenInfo,
+	DexShare, TokenInfo,
 	TokenSymbol::*,
 };
 use module_support::{DEXPriceProvider, Price, PriceProvider, Ratio};
 
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+pub mod migrations;
 mod mock;
 mod tests;
 
@@ -49,34 +58,158 @@ pub use weights::WeightInfo;
 
 pub use module::*;
 
-pub type SubscriptionId = u32;
+pub type SubscriptionId = u64;
 pub type DiscountRate = FixedI128;
 
+/// Vesting period tiers, in `(threshold, vesting_period)` sorted by threshold ascending.
+/// Thresholds are in `ADAO` amount.
+pub type VestingTiers<BlockNumber> = BoundedVec<(Balance, BlockNumber), ConstU32<4>>;
+
 /// Subscription parameters and state.
-#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
-pub struct Subscription<BlockNumber> {
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Subscription<AccountId, BlockNumber> {
 	/// The currency id for payments.
 	pub currency_id: CurrencyId,
 	/// Vesting period for staked tokens on subscribe.
 	pub vesting_period: BlockNumber,
+	/// Vesting period for subscribed amount reaching a threshold. The highest threshold not
+	/// exceeding subscribed amount applies, or `vesting_period` if none.
+	pub vesting_tiers: VestingTiers<BlockNumber>,
 	/// minimum subscription amount.
 	pub min_amount: Balance,
 	/// At least this amount of subscribed currency per aDAO.
 	pub min_ratio: Ratio,
 	/// The maximum amount that can be subscribed.
 	pub amount: Balance,
+	/// The denomination of `amount` and `min_amount`.
+	pub denomination: AmountDenomination,
 	/// Discount parameters.
 	pub discount: Discount<BlockNumber>,
+	/// Whether subscribers are exempted from `T::SubscriberFilter`.
+	pub bypass_filter: bool,
+	/// Subscription status.
+	pub status: SubscriptionStatus<BlockNumber>,
+	/// The first block to subscribe at.
+	pub start_block: BlockNumber,
+	/// The last block to subscribe at. The subscription is closed after it, if set.
+	pub end_block: Option<BlockNumber>,
+	/// Whether only whitelisted accounts can subscribe.
+	pub permissioned: bool,
+	/// The account receiving subscription payments.
+	pub payment_dest: AccountId,
+	/// The minimum number of blocks between two subscribings for a beneficiary, if set.
+	pub per_account_cooldown: Option<BlockNumber>,
 	/// Subscription state.
 	pub state: SubscriptionState<BlockNumber>,
 }
 
-pub type SubscriptionOf<T> = Subscription<<T as frame_system::Config>::BlockNumber>;
+pub type SubscriptionOf<T> =
+	Subscription<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
 
-#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, Default, TypeInfo)]
+/// Optional settings of a new subscription. The default is a permissionless subscription open
+/// from now on, without end block or cooldown, paying to `T::ProceedsDestination`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct SubscriptionParams<AccountId, BlockNumber> {
+	/// Vesting period tiers by subscribed amount.
+	pub vesting_tiers: VestingTiers<BlockNumber>,
+	/// The denomination of `amount` and `min_amount`.
+	pub denomination: AmountDenomination,
+	/// The first block to subscribe at.
+	pub start_block: BlockNumber,
+	/// The last block to subscribe at, if any.
+	pub end_block: Option<BlockNumber>,
+	/// Whether only whitelisted accounts can subscribe.
+	pub permissioned: bool,
+	/// The account receiving subscription payments, or `T::ProceedsDestination` if `None`.
+	pub payment_dest: Option<AccountId>,
+	/// The minimum number of blocks between two subscribings for a beneficiary, if any.
+	pub per_account_cooldown: Option<BlockNumber>,
+}
+
+impl<AccountId, BlockNumber: Default> Default for SubscriptionParams<AccountId, BlockNumber> {
+	fn default() -> Self {
+		SubscriptionParams {
+			vesting_tiers: Default::default(),
+			denomination: AmountDenomination::Adao,
+			start_block: Default::default(),
+			end_block: None,
+			permissioned: false,
+			payment_dest: None,
+			per_account_cooldown: None,
+		}
+	}
+}
+
+pub type SubscriptionParamsOf<T> =
+	SubscriptionParams<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
+
+/// Optional settings to update on a subscription. `None` fields are left unchanged.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct SubscriptionUpdate<AccountId, BlockNumber> {
+	/// New vesting period tiers.
+	pub vesting_tiers: Option<VestingTiers<BlockNumber>>,
+	/// New denomination of `amount` and `min_amount`.
+	pub denomination: Option<AmountDenomination>,
+	/// Whether subscribers are exempted from `T::SubscriberFilter`.
+	pub bypass_filter: Option<bool>,
+	/// New end block. `Some(None)` removes it.
+	pub end_block: Option<Option<BlockNumber>>,
+	/// Whether only whitelisted accounts can subscribe.
+	pub permissioned: Option<bool>,
+	/// New account receiving subscription payments.
+	pub payment_dest: Option<AccountId>,
+	/// New per beneficiary cooldown. `Some(None)` removes it.
+	pub per_account_cooldown: Option<Option<BlockNumber>>,
+}
+
+impl<AccountId, BlockNumber> Default for SubscriptionUpdate<AccountId, BlockNumber> {
+	fn default() -> Self {
+		SubscriptionUpdate {
+			vesting_tiers: None,
+			denomination: None,
+			bypass_filter: None,
+			end_block: None,
+			permissioned: None,
+			payment_dest: None,
+			per_account_cooldown: None,
+		}
+	}
+}
+
+pub type SubscriptionUpdateOf<T> =
+	SubscriptionUpdate<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
+
+/// The denomination of subscription amount limits.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum AmountDenomination {
+	/// In `ADAO` units.
+	Adao,
+	/// In stable currency value, converted into `ADAO` units by current price on subscribe.
+	Stable,
+}
+
+impl Default for AmountDenomination {
+	fn default() -> Self {
+		AmountDenomination::Adao
+	}
+}
+
+/// The subscription status.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum SubscriptionStatus<BlockNumber> {
+	/// Open for subscribing.
+	Active,
+	/// Paused at block `since`. The paused period doesn't count as idle for discount.
+	Paused { since: BlockNumber },
+}
+
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, Default, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct Discount<BlockNumber> {
 	/// Max discount rate.
 	pub max: DiscountRate,
+	/// Min discount rate. Could be negative, as a price premium.
+	pub min: DiscountRate,
 	/// The amount of block number, as the unit for `inc_on_idle` calculation.
 	pub interval: BlockNumber,
 	/// The percentage to increase for each interval.
@@ -87,15 +220,46 @@ pub struct Discount<BlockNumber> {
 	pub dec_per_unit: DiscountRate,
 }
 
+impl<BlockNumber> Discount<BlockNumber> {
+	/// Clamp `rate` to `[min, max]`.
+	fn clamp(&self, rate: DiscountRate) -> DiscountRate {
+		rate.max(self.min).min(self.max)
+	}
+
+	/// Returns `true` if `min <= max <= 1`, so the discounted price is never negative.
+	fn is_valid(&self) -> bool {
+		self.min <= self.max && self.max <= DiscountRate::one()
+	}
+}
+
 /// The subscription state.
-#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 pub struct SubscriptionState<BlockNumber> {
 	/// Total amount of sold subscriptions.
 	pub total_sold: Balance,
+	/// Total payment received, in the payment currency.
+	pub total_payment: Balance,
 	/// The block number on which the latest subscribing happened.
 	pub last_sold_at: BlockNumber,
 	/// The discount of the latest subscribing.
 	pub last_discount: DiscountRate,
+	/// Whether the remaining capacity has dropped below `min_amount`.
+	pub sold_out: bool,
+	/// Whether a subscribing has executed at `discount.max`.
+	pub discount_cap_reached: bool,
+}
+
+/// ADAO minted and SDAO received on a subscription mint.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, Default, TypeInfo)]
+pub struct MintPreview {
+	/// Total ADAO minted, including the treasury and DAO share.
+	pub mint: Balance,
+	/// SDAO received by the subscriber, in vesting.
+	pub staked: Balance,
+	/// SDAO received by the treasury.
+	pub treasury_staked: Balance,
+	/// SDAO received by the DAO.
+	pub dao_staked: Balance,
 }
 
 /// SDAO token manager.
@@ -106,6 +270,39 @@ pub trait StakedTokenManager<AccountId, BlockNumber> {
 		subscription_amount: Balance,
 		vesting_period: BlockNumber,
 	) -> DispatchResult;
+
+	/// Preview `mint_for_subscription` of given `subscription_amount` at the current exchange
+	/// rate, without storage writes.
+	fn preview_mint_for_subscription(subscription_amount: Balance) -> Result<MintPreview, DispatchError>;
+
+	/// Returns `true` if a subscription mint vesting for `vesting_period` blocks can be added to
+	/// `who`, without exceeding the vesting chunk limit.
+	fn can_mint_for(who: &AccountId, vesting_period: BlockNumber) -> bool;
+
+	/// Unstake given `amount` of SDAO token for `who`. Returns the ADAO amount received after fee.
+	fn unstake_for(who: &AccountId, amount: Balance) -> Result<Balance, DispatchError>;
+}
+
+/// Decimals of currencies unknown to `TokenInfo`, like foreign assets and ERC20 tokens.
+pub trait CurrencyDecimals {
+	/// Returns the decimals of `currency_id`, or `None` if unknown.
+	fn decimals(currency_id: CurrencyId) -> Option<u8>;
+}
+
+/// Stable asset pool tokens, for valuing pool token payments.
+pub trait StableAssetValuation {
+	/// Returns the decimals of pool token `currency_id`, or `None` if unknown.
+	fn decimals(currency_id: CurrencyId) -> Option<u8>;
+
+	/// Returns the price of pool token `currency_id` quoted in `quote`, or `None` if not
+	/// available.
+	fn price(currency_id: CurrencyId, quote: CurrencyId) -> Option<Price>;
+}
+
+/// DEX liquidity pools, for valuing DEX share payments.
+pub trait LiquidityPoolProvider {
+	/// The pool reserves of `(currency_id_a, currency_id_b)`, in the same order.
+	fn get_liquidity_pool(currency_id_a: CurrencyId, currency_id_b: CurrencyId) -> (Balance, Balance);
 }
 
 #[frame_support::pallet]
@@ -118,17 +315,40 @@ pub mod module {
 
 		type Currency: MultiCurrency<Self::AccountId, Balance = Balance, CurrencyId = CurrencyId>;
 
+		/// The stable currency, for balance-denominated amounts like `AmountDenomination::Stable`
+		/// subscription limits.
 		type StableCurrencyId: Get<CurrencyId>;
 
+		/// The quote currency of prices for subscription valuation.
+		type ValuationCurrencyId: Get<CurrencyId>;
+
 		/// The required origin to create/update/close subscriptions.
 		type UpdateOrigin: EnsureOrigin<Self::Origin>;
 
+		/// Accounts eligible to subscribe, unless the subscription bypasses the filter. Use
+		/// `Everything` for no restriction.
+		type SubscriberFilter: Contains<Self::AccountId>;
+
+		/// Beneficiaries exempted from `per_account_cooldown` of subscriptions, for instance
+		/// governance accounts.
+		type CooldownExempt: Contains<Self::AccountId>;
+
 		/// Used for payment currency prices.
 		type AssetPriceProvider: PriceProvider<CurrencyId>;
 
 		/// Used for `ADAO` token price.
 		type AdaoPriceProvider: DEXPriceProvider<CurrencyId>;
 
+		/// Used for DEX share payment prices, valued by the pool reserves.
+		type LiquidityPools: LiquidityPoolProvider;
+
+		/// Used for stable asset pool token payment prices and decimals.
+		type StableAssetValuation: StableAssetValuation;
+
+		/// Used for decimals of currencies `TokenInfo` doesn't know of, for instance foreign assets
+		/// and ERC20 tokens, usually backed by the asset registry.
+		type CurrencyDecimals: CurrencyDecimals;
+
 		/// The block number provider
 		type BlockNumberProvider: BlockNumberProvider<BlockNumber = Self::BlockNumber>;
 
@@ -138,6 +358,47 @@ pub mod module {
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
 
+		/// The DAO account which receives subscription payments, unless the subscription sets its
+		/// own `payment_dest`.
+		#[pallet::constant]
+		type ProceedsDestination: Get<Self::AccountId>;
+
+		/// The maximum number of idle intervals for discount increase. Idle discount won't accrue
+		/// beyond this, for instance while the price feed is missing.
+		#[pallet::constant]
+		type MaxIdleIntervals: Get<u32>;
+
+		/// The number of blocks per era, for subscription analytics.
+		#[pallet::constant]
+		type BlocksPerEra: Get<Self::BlockNumber>;
+
+		/// The number of recent eras of subscription analytics to retain.
+		#[pallet::constant]
+		type SoldRetentionEras: Get<u32>;
+
+		/// The maximum number of legs in a `subscribe_batch` call.
+		#[pallet::constant]
+		type MaxBatchSize: Get<u32>;
+
+		/// The interval in blocks to record the DEX spot ADAO price at. If not zero, subscriptions
+		/// are priced by the average of recorded samples, instead of the spot price.
+		#[pallet::constant]
+		type PriceRecordInterval: Get<Self::BlockNumber>;
+
+		/// The maximum number of recorded ADAO price samples to average. The oldest sample is dropped
+		/// if exceeded.
+		#[pallet::constant]
+		type MaxPriceSamples: Get<u32>;
+
+		/// The maximum relative deviation of the ADAO price from the `T::AssetPriceProvider` oracle
+		/// price on subscribing. Zero to disable the check.
+		#[pallet::constant]
+		type MaxPriceDeviation: Get<Ratio>;
+
+		/// The maximum number of open subscriptions.
+		#[pallet::constant]
+		type MaxActiveSubscriptions: Get<u32>;
+
 		type WeightInfo: WeightInfo;
 	}
 
@@ -152,6 +413,72 @@ pub mod module {
 	#[pallet::getter(fn subscriptions)]
 	pub type Subscriptions<T: Config> = StorageMap<_, Twox64Concat, SubscriptionId, SubscriptionOf<T>, OptionQuery>;
 
+	/// The pallet account which holds legacy subscription payments, derived from `T::PalletId`.
+	/// Written at genesis.
+	/// PalletAccountCache: value AccountId
+	#[pallet::storage]
+	#[pallet::getter(fn pallet_account_cache)]
+	pub type PalletAccountCache<T: Config> = StorageValue<_, T::AccountId, OptionQuery>;
+
+	/// Sold stats of a subscription per era, in `(payment_value, sold_amount)`. Payment value is
+	/// quoted in `T::ValuationCurrencyId`. Only the last `T::SoldRetentionEras` eras are retained.
+	///
+	/// DailySold: double_map SubscriptionId, Era => (Balance, Balance)
+	#[pallet::storage]
+	pub type DailySold<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, SubscriptionId, Twox64Concat, u32, (Balance, Balance), ValueQuery>;
+
+	/// Accounts whitelisted to subscribe to a permissioned subscription.
+	///
+	/// SubscriptionWhitelist: double_map SubscriptionId, AccountId => Option<()>
+	#[pallet::storage]
+	pub type SubscriptionWhitelist<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, SubscriptionId, Twox64Concat, T::AccountId, (), OptionQuery>;
+
+	/// The block number of the last subscribing for a beneficiary to a subscription, for
+	/// `per_account_cooldown`.
+	///
+	/// LastSubscribedAt: double_map SubscriptionId, AccountId => Option<BlockNumber>
+	#[pallet::storage]
+	pub type LastSubscribedAt<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, SubscriptionId, Twox64Concat, T::AccountId, T::BlockNumber, OptionQuery>;
+
+	/// Recent DEX spot ADAO prices, recorded every `T::PriceRecordInterval` blocks, oldest first.
+	///
+	/// AdaoPriceSamples: value BoundedVec<Price, MaxPriceSamples>
+	#[pallet::storage]
+	#[pallet::getter(fn adao_price_samples)]
+	pub type AdaoPriceSamples<T: Config> = StorageValue<_, BoundedVec<Price, T::MaxPriceSamples>, ValueQuery>;
+
+	/// All subscriptions are paused by governance, regardless of their own status.
+	///
+	/// AllSubscriptionsPaused: value bool
+	#[pallet::storage]
+	#[pallet::getter(fn all_subscriptions_paused)]
+	pub type AllSubscriptionsPaused<T> = StorageValue<_, bool, ValueQuery>;
+
+	/// Total ADAO minted for subscriptions, including the treasury and DAO share.
+	///
+	/// TotalSubscriptionMinted: value Balance
+	#[pallet::storage]
+	#[pallet::getter(fn total_subscription_minted)]
+	pub type TotalSubscriptionMinted<T> = StorageValue<_, Balance, ValueQuery>;
+
+	/// The cap of `TotalSubscriptionMinted`. No cap if `None`.
+	///
+	/// MintCap: value Option<Balance>
+	#[pallet::storage]
+	#[pallet::getter(fn mint_cap)]
+	pub type MintCap<T> = StorageValue<_, Balance, OptionQuery>;
+
+	/// IDs of open subscriptions, in creation order.
+	///
+	/// ActiveSubscriptions: value BoundedVec<SubscriptionId, MaxActiveSubscriptions>
+	#[pallet::storage]
+	#[pallet::getter(fn active_subscriptions)]
+	pub type ActiveSubscriptions<T: Config> =
+		StorageValue<_, BoundedVec<SubscriptionId, T::MaxActiveSubscriptions>, ValueQuery>;
+
 	#[pallet::error]
 	pub enum Error<T> {
 		/// Subscription not found.
@@ -162,10 +489,48 @@ pub mod module {
 		SubscriptionIsFull,
 		/// The received amount on subscription is below minimum target amount.
 		BelowMinTargetAmount,
+		/// The effective price on subscription is above the maximum price.
+		PriceAboveLimit,
+		/// Vesting tier thresholds are not sorted ascending.
+		InvalidVestingTiers,
+		/// Discount `min` is above `max`, or `max` is above one.
+		InvalidDiscount,
+		/// Payment currency not supported, for instance ADAO, SDAO or SDAO LP tokens.
+		UnsupportedPaymentCurrency,
 		/// Below minimum subscription amount.
 		BelowMinSubscriptionAmount,
 		/// Currency has no decimals info.
 		NoDecimalsInfo,
+		/// Payment amount is zero.
+		ZeroPayment,
+		/// No vesting slot left for subscription. Claim matured vestings first.
+		VestingSlotsExhausted,
+		/// Account not eligible to subscribe.
+		NotEligible,
+		/// Subscription is paused.
+		SubscriptionPaused,
+		/// Subscription is not paused.
+		SubscriptionNotPaused,
+		/// Subscription is expired, after its end block.
+		SubscriptionExpired,
+		/// Account not whitelisted for a permissioned subscription.
+		NotWhitelisted,
+		/// Subscription amount rounds to zero for the payment.
+		ZeroSubscriptionAmount,
+		/// Discount `interval`, `min_ratio` or `amount` is zero, which would fail every subscribing.
+		InvalidDiscountParameters,
+		/// Subscribed again within `per_account_cooldown` of the subscription.
+		CooldownActive,
+		/// Subscription is not started, before its start block.
+		SubscriptionNotStarted,
+		/// All subscriptions are paused.
+		SubscriptionsPaused,
+		/// The ADAO price deviates from the oracle price more than `T::MaxPriceDeviation`.
+		PriceOutOfBounds,
+		/// The subscription mint would exceed `MintCap`.
+		MintCapExceeded,
+		/// Open subscriptions exceed `T::MaxActiveSubscriptions`.
+		TooManyActiveSubscriptions,
 	}
 
 	#[pallet::event]
@@ -175,31 +540,194 @@ pub mod module {
 			id: SubscriptionId,
 			subscription: SubscriptionOf<T>,
 		},
+		/// Subscription updated, with `state` after the update.
 		SubscriptionUpdated {
 			id: SubscriptionId,
+			state: SubscriptionState<T::BlockNumber>,
 		},
+		/// Subscription closed, with its final totals: `total_sold` in `ADAO`, `total_payment` raised
+		/// in the payment currency, and unsold `remaining` in `ADAO` (zero if it can't be priced).
 		SubscriptionClosed {
 			id: SubscriptionId,
+			currency_id: CurrencyId,
+			total_sold: Balance,
+			total_payment: Balance,
+			remaining: Balance,
+		},
+		SubscriptionPaused {
+			id: SubscriptionId,
+		},
+		SubscriptionResumed {
+			id: SubscriptionId,
+		},
+		/// Remaining capacity of the subscription dropped below `min_amount`, or to zero. A subscription
+		/// denominated in ADAO is closed once its capacity drops to zero.
+		SubscriptionSoldOut {
+			id: SubscriptionId,
+			total_sold: Balance,
+			total_payment: Balance,
+		},
+		/// A subscribing executed at the max discount for the first time.
+		DiscountCapReached {
+			id: SubscriptionId,
+		},
+		WhitelistAdded {
+			subscription_id: SubscriptionId,
+			accounts: Vec<T::AccountId>,
+		},
+		WhitelistRemoved {
+			subscription_id: SubscriptionId,
+			accounts: Vec<T::AccountId>,
+		},
+		LegacyProceedsMigrated {
+			currency_id: CurrencyId,
+			amount: Balance,
 		},
 		Subscribed {
+			/// The paying account.
 			who: T::AccountId,
+			/// The account receiving the subscribed SDAO.
+			beneficiary: T::AccountId,
+			/// The account receiving the payment.
+			payment_dest: T::AccountId,
 			subscription_id: SubscriptionId,
 			payment_amount: Balance,
 			subscription_amount: Balance,
+			/// Remaining capacity of the subscription after this purchase, in `ADAO` amount.
+			remaining_after: Balance,
+			/// Total payment raised by the subscription after this purchase.
+			total_payment: Balance,
+			/// The raw `ADAO` price used, quoted in `T::ValuationCurrencyId`, before discount.
+			adao_price: Price,
+			/// The payment currency price used, quoted in `T::ValuationCurrencyId`.
+			payment_price: Price,
+		},
+		/// `orders` subscriptions made by `who` in a batch, subscribing `total_subscription_amount`
+		/// of `ADAO` in total.
+		BatchSubscribed {
+			who: T::AccountId,
+			orders: u32,
+			total_subscription_amount: Balance,
 		},
+		AllSubscriptionsPaused,
+		AllSubscriptionsResumed,
+		/// The subscription mint cap is set, or removed if `None`.
+		MintCapSet {
+			cap: Option<Balance>,
+		},
+	}
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Subscriptions open from genesis, in `(currency_id, vesting_period, min_amount, min_ratio,
+		/// amount, discount)`. Other parameters are the defaults of `create_subscription`.
+		#[allow(clippy::type_complexity)]
+		pub initial_subscriptions: Vec<(CurrencyId, T::BlockNumber, Balance, Ratio, Balance, Discount<T::BlockNumber>)>,
 	}
 
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			GenesisConfig {
+				initial_subscriptions: vec![],
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			PalletAccountCache::<T>::put(Pallet::<T>::account_id());
+
+			let now = T::BlockNumberProvider::current_block_number();
+			for (currency_id, vesting_period, min_amount, min_ratio, amount, discount) in
+				self.initial_subscriptions.iter().cloned()
+			{
+				assert!(
+					Pallet::<T>::is_supported_payment_currency(currency_id),
+					"unsupported initial subscription payment currency"
+				);
+				assert!(discount.is_valid(), "invalid initial subscription discount");
+				assert!(
+					Pallet::<T>::ensure_valid_parameters(&discount, min_ratio, amount).is_ok(),
+					"invalid initial subscription parameters"
+				);
+				let subscription: SubscriptionOf<T> = Subscription {
+					currency_id,
+					vesting_period,
+					vesting_tiers: Default::default(),
+					min_amount,
+					min_ratio,
+					amount,
+					denomination: AmountDenomination::Adao,
+					discount,
+					bypass_filter: false,
+					status: SubscriptionStatus::Active,
+					start_block: Zero::zero(),
+					end_block: None,
+					permissioned: false,
+					payment_dest: T::ProceedsDestination::get(),
+					per_account_cooldown: None,
+					state: SubscriptionState {
+						total_sold: Zero::zero(),
+						total_payment: Zero::zero(),
+						last_sold_at: now,
+						last_discount: Zero::zero(),
+						sold_out: false,
+						discount_cap_reached: false,
+					},
+				};
+				Pallet::<T>::insert_subscription(subscription).expect("too many initial subscriptions");
+			}
+		}
+	}
+
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 	#[pallet::pallet]
-	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+		/// Close expired subscriptions, and record the ADAO price sample if due.
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			let now = T::BlockNumberProvider::current_block_number();
+			let record_weight = Self::record_adao_price(now);
+			let active = Self::active_subscriptions();
+			let count = active.len() as u32;
+			let expired: Vec<_> = active
+				.into_iter()
+				.filter_map(|id| Subscriptions::<T>::get(id).map(|subscription| (id, subscription)))
+				.filter(|(_, subscription)| Self::is_expired(subscription, now))
+				.collect();
+			for (id, subscription) in expired.iter() {
+				Subscriptions::<T>::remove(id);
+				Self::remove_active_subscription(*id);
+				Self::clear_account_records(*id);
+				Self::deposit_event(Self::closed_event(*id, subscription));
+			}
+			<T as Config>::WeightInfo::on_initialize(count, expired.len() as u32).saturating_add(record_weight)
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			migrations::v1::migrate::<T>().saturating_add(migrations::v2::migrate::<T>())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			migrations::v1::pre_upgrade::<T>()
+		}
+	}
 
+	// Call indices follow declaration order, and are pinned by the `call_indices_are_stable` test.
+	// Append new calls at the end, never insert or reorder.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Create a subscription. Requires `T::UpdateOrigin` origin.
-		#[pallet::weight(<T as Config>::WeightInfo::create_subscription())]
+		///
+		/// The subscription can be subscribed from `start_block`, and idle discount accrues from it.
+		#[pallet::weight(<T as Config>::WeightInfo::create_subscription(T::MaxActiveSubscriptions::get()))]
 		#[transactional]
 		pub fn create_subscription(
 			origin: OriginFor<T>,
@@ -209,28 +737,56 @@ pub mod module {
 			min_ratio: Ratio,
 			#[pallet::compact] amount: Balance,
 			discount: Discount<T::BlockNumber>,
+			params: SubscriptionParamsOf<T>,
 		) -> DispatchResult {
 			T::UpdateOrigin::ensure_origin(origin)?;
+			let SubscriptionParams {
+				vesting_tiers,
+				denomination,
+				start_block,
+				end_block,
+				permissioned,
+				payment_dest,
+				per_account_cooldown,
+			} = params;
+			ensure!(
+				Self::is_supported_payment_currency(currency_id),
+				Error::<T>::UnsupportedPaymentCurrency
+			);
+			// payments are valued with the payment currency decimals and price
+			Self::currency_accuracy(currency_id)?;
+			Self::ensure_payment_price_available(currency_id)?;
+			ensure!(Self::is_sorted(&vesting_tiers), Error::<T>::InvalidVestingTiers);
+			ensure!(discount.is_valid(), Error::<T>::InvalidDiscount);
+			Self::ensure_valid_parameters(&discount, min_ratio, amount)?;
 
-			let subscription_id = SubscriptionIndex::<T>::try_mutate(|id| -> Result<SubscriptionId, DispatchError> {
-				let current_id = *id;
-				*id = id.checked_add(One::one()).ok_or(ArithmeticError::Overflow)?;
-				Ok(current_id)
-			})?;
 			let subscription: SubscriptionOf<T> = Subscription {
 				currency_id,
 				vesting_period,
+				vesting_tiers,
 				min_amount,
 				min_ratio,
 				amount,
+				denomination,
 				discount,
+				bypass_filter: false,
+				status: SubscriptionStatus::Active,
+				start_block,
+				end_block,
+				permissioned,
+				payment_dest: payment_dest.unwrap_or_else(T::ProceedsDestination::get),
+				per_account_cooldown,
 				state: SubscriptionState {
 					total_sold: Zero::zero(),
-					last_sold_at: T::BlockNumberProvider::current_block_number(),
+					total_payment: Zero::zero(),
+					// idle discount accrues from the start
+					last_sold_at: start_block.max(T::BlockNumberProvider::current_block_number()),
 					last_discount: Zero::zero(),
+					sold_out: false,
+					discount_cap_reached: false,
 				},
 			};
-			Subscriptions::<T>::insert(subscription_id, subscription);
+			let subscription_id = Self::insert_subscription(subscription.clone())?;
 
 			Self::deposit_event(Event::<T>::SubscriptionCreated {
 				id: subscription_id,
@@ -240,6 +796,9 @@ pub mod module {
 		}
 
 		/// Update a subscription. Requires `T::UpdateOrigin` origin.
+		///
+		/// Updating `discount` clamps the last discount into the new `[min, max]` range, and restarts
+		/// the idle period from now.
 		#[pallet::weight(<T as Config>::WeightInfo::update_subscription())]
 		#[transactional]
 		pub fn update_subscription(
@@ -250,8 +809,18 @@ pub mod module {
 			min_ratio: Option<Ratio>,
 			amount: Option<Balance>,
 			discount: Option<Discount<T::BlockNumber>>,
+			update: SubscriptionUpdateOf<T>,
 		) -> DispatchResult {
 			T::UpdateOrigin::ensure_origin(origin)?;
+			let SubscriptionUpdate {
+				vesting_tiers,
+				denomination,
+				bypass_filter,
+				end_block,
+				permissioned,
+				payment_dest,
+				per_account_cooldown,
+			} = update;
 
 			Subscriptions::<T>::try_mutate_exists(subscription_id, |maybe_subscription| -> DispatchResult {
 				let subscription = maybe_subscription.as_mut().ok_or(Error::<T>::SubscriptionNotFound)?;
@@ -259,6 +828,10 @@ pub mod module {
 				if let Some(new_vesting_period) = vesting_period {
 					subscription.vesting_period = new_vesting_period;
 				}
+				if let Some(new_vesting_tiers) = vesting_tiers {
+					ensure!(Self::is_sorted(&new_vesting_tiers), Error::<T>::InvalidVestingTiers);
+					subscription.vesting_tiers = new_vesting_tiers;
+				}
 				if let Some(new_min_amount) = min_amount {
 					subscription.min_amount = new_min_amount;
 				}
@@ -268,26 +841,59 @@ pub mod module {
 				if let Some(new_amount) = amount {
 					subscription.amount = new_amount;
 				}
+				if let Some(new_denomination) = denomination {
+					subscription.denomination = new_denomination;
+				}
 				if let Some(new_discount) = discount {
+					ensure!(new_discount.is_valid(), Error::<T>::InvalidDiscount);
 					subscription.discount = new_discount;
+					subscription.state.last_discount = new_discount.clamp(subscription.state.last_discount);
+					// restart the idle period, so idle blocks before the update don't accrue discount with new
+					// parameters. A paused subscription restarts on resuming.
+					subscription.state.last_sold_at = match subscription.status {
+						SubscriptionStatus::Paused { since } => since,
+						SubscriptionStatus::Active => T::BlockNumberProvider::current_block_number(),
+					};
+				}
+				if let Some(new_bypass_filter) = bypass_filter {
+					subscription.bypass_filter = new_bypass_filter;
 				}
+				if let Some(new_end_block) = end_block {
+					subscription.end_block = new_end_block;
+				}
+				if let Some(new_permissioned) = permissioned {
+					subscription.permissioned = new_permissioned;
+				}
+				if let Some(new_payment_dest) = payment_dest {
+					subscription.payment_dest = new_payment_dest;
+				}
+				if let Some(new_per_account_cooldown) = per_account_cooldown {
+					subscription.per_account_cooldown = new_per_account_cooldown;
+				}
+				Self::ensure_valid_parameters(&subscription.discount, subscription.min_ratio, subscription.amount)?;
 
-				Self::deposit_event(Event::<T>::SubscriptionUpdated { id: subscription_id });
+				Self::deposit_event(Event::<T>::SubscriptionUpdated {
+					id: subscription_id,
+					state: subscription.state,
+				});
 				Ok(())
 			})
 		}
 
-		/// Close a subscription. Requires `T::ClosingOrigin` origin.
-		#[pallet::weight(<T as Config>::WeightInfo::close_subscription())]
+		/// Close a subscription. Requires `T::UpdateOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::close_subscription(T::MaxActiveSubscriptions::get()))]
 		#[transactional]
 		pub fn close_subscription(origin: OriginFor<T>, subscription_id: SubscriptionId) -> DispatchResult {
 			T::UpdateOrigin::ensure_origin(origin)?;
-			Subscriptions::<T>::take(subscription_id).ok_or(Error::<T>::SubscriptionNotFound)?;
-			Self::deposit_event(Event::<T>::SubscriptionClosed { id: subscription_id });
+			let subscription = Subscriptions::<T>::take(subscription_id).ok_or(Error::<T>::SubscriptionNotFound)?;
+			Self::remove_active_subscription(subscription_id);
+			Self::clear_account_records(subscription_id);
+			Self::deposit_event(Self::closed_event(subscription_id, &subscription));
 			Ok(())
 		}
 
-		/// Subscribe to given `subscription_id`, would fail if below minimum target amount.
+		/// Subscribe to given `subscription_id`, would fail if below minimum target amount, or if
+		/// the effective ADAO price, quoted in `T::ValuationCurrencyId`, is above `max_price`.
 		#[pallet::weight(<T as Config>::WeightInfo::subscribe())]
 		#[transactional]
 		pub fn subscribe(
@@ -295,85 +901,604 @@ pub mod module {
 			subscription_id: SubscriptionId,
 			payment_amount: Balance,
 			min_target_amount: Balance,
+			max_price: Option<Price>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::do_subscribe(&who, &who, subscription_id, payment_amount, min_target_amount, max_price)?;
+			Ok(())
+		}
+
+		/// Move subscription proceeds left in the pallet account, of given `currencies`, to
+		/// `T::ProceedsDestination`. Currencies with zero balance are skipped, so it is safe to call
+		/// repeatedly. Requires `T::UpdateOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::migrate_legacy_proceeds(currencies.len() as u32))]
+		#[transactional]
+		pub fn migrate_legacy_proceeds(origin: OriginFor<T>, currencies: Vec<CurrencyId>) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+
+			let pallet_account = Self::account_id();
+			let destination = T::ProceedsDestination::get();
+			for currency_id in currencies {
+				let amount = T::Currency::free_balance(currency_id, &pallet_account);
+				if amount.is_zero() {
+					continue;
+				}
+				T::Currency::transfer(currency_id, &pallet_account, &destination, amount)?;
+				Self::deposit_event(Event::<T>::LegacyProceedsMigrated { currency_id, amount });
+			}
+			Ok(())
+		}
+
+		/// Pause a subscription, `subscribe` fails until resumed. Requires `T::UpdateOrigin`
+		/// origin.
+		#[pallet::weight(<T as Config>::WeightInfo::pause_subscription())]
+		#[transactional]
+		pub fn pause_subscription(origin: OriginFor<T>, subscription_id: SubscriptionId) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
 
 			Subscriptions::<T>::try_mutate_exists(subscription_id, |maybe_subscription| -> DispatchResult {
 				let subscription = maybe_subscription.as_mut().ok_or(Error::<T>::SubscriptionNotFound)?;
-				let now = T::BlockNumberProvider::current_block_number();
-				let (subscription_amount, last_discount) =
-					Self::subscription_amount(subscription, payment_amount, now)?;
-
-				ensure!(
-					subscription_amount >= subscription.min_amount,
-					Error::<T>::BelowMinSubscriptionAmount
-				);
 				ensure!(
-					subscription_amount <= subscription.amount.saturating_sub(subscription.state.total_sold),
-					Error::<T>::SubscriptionIsFull
-				);
-				ensure!(
-					subscription_amount >= min_target_amount,
-					Error::<T>::BelowMinTargetAmount
+					subscription.status == SubscriptionStatus::Active,
+					Error::<T>::SubscriptionPaused
 				);
+				subscription.status = SubscriptionStatus::Paused {
+					since: T::BlockNumberProvider::current_block_number(),
+				};
+
+				Self::deposit_event(Event::<T>::SubscriptionPaused { id: subscription_id });
+				Ok(())
+			})
+		}
+
+		/// Resume a paused subscription. The paused period is excluded from discount idle
+		/// intervals. Requires `T::UpdateOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::resume_subscription())]
+		#[transactional]
+		pub fn resume_subscription(origin: OriginFor<T>, subscription_id: SubscriptionId) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
 
-				subscription.state.total_sold = subscription
+			Subscriptions::<T>::try_mutate_exists(subscription_id, |maybe_subscription| -> DispatchResult {
+				let subscription = maybe_subscription.as_mut().ok_or(Error::<T>::SubscriptionNotFound)?;
+				let since = match subscription.status {
+					SubscriptionStatus::Paused { since } => since,
+					SubscriptionStatus::Active => return Err(Error::<T>::SubscriptionNotPaused.into()),
+				};
+				let now = T::BlockNumberProvider::current_block_number();
+				subscription.state.last_sold_at = subscription
 					.state
-					.total_sold
-					.checked_add(subscription_amount)
-					.expect("Subscription amount is smaller than remaining; qed");
-				subscription.state.last_sold_at = now;
-				subscription.state.last_discount = last_discount;
-
-				// payment
-				T::Currency::transfer(subscription.currency_id, &who, &Self::account_id(), payment_amount)?;
-				// mint ADAO token
-				T::StakedToken::mint_for_subscription(&who, subscription_amount, subscription.vesting_period)?;
-
-				Self::deposit_event(Event::<T>::Subscribed {
-					who,
-					subscription_id,
-					payment_amount,
-					subscription_amount,
-				});
+					.last_sold_at
+					.saturating_add(now.saturating_sub(since));
+				subscription.status = SubscriptionStatus::Active;
+
+				Self::deposit_event(Event::<T>::SubscriptionResumed { id: subscription_id });
 				Ok(())
 			})
 		}
+
+		/// Add `accounts` to the whitelist of a subscription. Requires `T::UpdateOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::add_to_whitelist(accounts.len() as u32))]
+		#[transactional]
+		pub fn add_to_whitelist(
+			origin: OriginFor<T>,
+			subscription_id: SubscriptionId,
+			accounts: Vec<T::AccountId>,
+		) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(
+				Subscriptions::<T>::contains_key(subscription_id),
+				Error::<T>::SubscriptionNotFound
+			);
+
+			for who in accounts.iter() {
+				SubscriptionWhitelist::<T>::insert(subscription_id, who, ());
+			}
+			Self::deposit_event(Event::<T>::WhitelistAdded {
+				subscription_id,
+				accounts,
+			});
+			Ok(())
+		}
+
+		/// Remove `accounts` from the whitelist of a subscription. Requires `T::UpdateOrigin`
+		/// origin.
+		#[pallet::weight(<T as Config>::WeightInfo::remove_from_whitelist(accounts.len() as u32))]
+		#[transactional]
+		pub fn remove_from_whitelist(
+			origin: OriginFor<T>,
+			subscription_id: SubscriptionId,
+			accounts: Vec<T::AccountId>,
+		) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			ensure!(
+				Subscriptions::<T>::contains_key(subscription_id),
+				Error::<T>::SubscriptionNotFound
+			);
+
+			for who in accounts.iter() {
+				SubscriptionWhitelist::<T>::remove(subscription_id, who);
+			}
+			Self::deposit_event(Event::<T>::WhitelistRemoved {
+				subscription_id,
+				accounts,
+			});
+			Ok(())
+		}
+
+		/// Subscribe to given `subscription_id` on behalf of `beneficiary`. Payment is drawn from
+		/// the signer, while the subscribed SDAO is minted and vested for `beneficiary`.
+		#[pallet::weight(<T as Config>::WeightInfo::subscribe_for())]
+		#[transactional]
+		pub fn subscribe_for(
+			origin: OriginFor<T>,
+			beneficiary: T::AccountId,
+			subscription_id: SubscriptionId,
+			payment_amount: Balance,
+			min_target_amount: Balance,
+			max_price: Option<Price>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_subscribe(
+				&who,
+				&beneficiary,
+				subscription_id,
+				payment_amount,
+				min_target_amount,
+				max_price,
+			)?;
+			Ok(())
+		}
+
+		/// Subscribe to multiple subscriptions at once, with `legs` of `(subscription_id,
+		/// payment_amount, min_target_amount)`. Either all legs succeed, or none. At most
+		/// `T::MaxBatchSize` legs.
+		#[pallet::weight(<T as Config>::WeightInfo::subscribe().saturating_mul(legs.len() as Weight))]
+		#[transactional]
+		pub fn subscribe_batch(
+			origin: OriginFor<T>,
+			legs: BoundedVec<(SubscriptionId, Balance, Balance), T::MaxBatchSize>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let orders = legs.len() as u32;
+			let mut total_subscription_amount: Balance = Zero::zero();
+			for (subscription_id, payment_amount, min_target_amount) in legs {
+				let subscription_amount =
+					Self::do_subscribe(&who, &who, subscription_id, payment_amount, min_target_amount, None)?;
+				total_subscription_amount = total_subscription_amount.saturating_add(subscription_amount);
+			}
+			Self::deposit_event(Event::<T>::BatchSubscribed {
+				who,
+				orders,
+				total_subscription_amount,
+			});
+			Ok(())
+		}
+
+		/// Pause all subscriptions, subscribing fails until `resume_all`. Creating, updating and
+		/// closing subscriptions is still allowed. Requires `T::UpdateOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::pause_all())]
+		pub fn pause_all(origin: OriginFor<T>) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			AllSubscriptionsPaused::<T>::put(true);
+			Self::deposit_event(Event::<T>::AllSubscriptionsPaused);
+			Ok(())
+		}
+
+		/// Resume subscribing after `pause_all`. Subscriptions paused on their own stay paused.
+		/// Requires `T::UpdateOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::resume_all())]
+		pub fn resume_all(origin: OriginFor<T>) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			AllSubscriptionsPaused::<T>::kill();
+			Self::deposit_event(Event::<T>::AllSubscriptionsResumed);
+			Ok(())
+		}
+
+		/// Set the cap of total ADAO minted for subscriptions, including the treasury and DAO
+		/// share, or remove it if `None`. Requires `T::UpdateOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::set_mint_cap())]
+		pub fn set_mint_cap(origin: OriginFor<T>, cap: Option<Balance>) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			MintCap::<T>::set(cap);
+			Self::deposit_event(Event::<T>::MintCapSet { cap });
+			Ok(())
+		}
 	}
 }
 
 impl<T: Config> Pallet<T> {
-	/// Calculate the amount of ADAO tokens to be minted for a subscription.
+	/// The `(adao_price, payment_price)` of subscribing with `currency_id`, both quoted in
+	/// `T::ValuationCurrencyId`.
+	fn subscription_prices(currency_id: CurrencyId) -> Result<(Price, Price), DispatchError> {
+		// ADAO price: from DEX
+		let adao_price = Self::adao_price()?;
+		Self::ensure_adao_price_within_bounds(adao_price)?;
+		let payment_price = match currency_id {
+			CurrencyId::DexShare(dex_share_0, dex_share_1) => {
+				Self::dex_share_price(currency_id, dex_share_0.into(), dex_share_1.into())?
+			}
+			_ => Self::token_price(currency_id)?,
+		};
+		Ok((adao_price, payment_price))
+	}
+
+	/// Ensure `adao_price` deviates from the oracle ADAO price by no more than
+	/// `T::MaxPriceDeviation`. Skipped if the deviation limit is zero, or no oracle price.
+	fn ensure_adao_price_within_bounds(adao_price: Price) -> DispatchResult {
+		let max_deviation = T::MaxPriceDeviation::get();
+		if max_deviation.is_zero() {
+			return Ok(());
+		}
+		let reference = match T::AssetPriceProvider::get_relative_price(Token(ADAO), T::ValuationCurrencyId::get()) {
+			Some(price) if !price.is_zero() => price,
+			_ => return Ok(()),
+		};
+
+		// deviation = |adao_price - reference| / reference
+		let deviation = adao_price
+			.max(reference)
+			.saturating_sub(adao_price.min(reference))
+			.checked_div(&reference)
+			.ok_or(ArithmeticError::Overflow)?;
+		ensure!(deviation <= max_deviation, Error::<T>::PriceOutOfBounds);
+		Ok(())
+	}
+
+	/// Token price, quoted in `T::ValuationCurrencyId`. `ADAO` price is from DEX, others from
+	/// `oracle_price`.
+	fn token_price(currency_id: CurrencyId) -> Result<Price, DispatchError> {
+		match currency_id {
+			Token(ADAO) => Self::adao_price(),
+			_ => Self::oracle_price(currency_id),
+		}
+	}
+
+	/// Token price not derived from DEX pools, quoted in `T::ValuationCurrencyId`. Stable asset
+	/// pool tokens are from `T::StableAssetValuation`, others including `ADAO` from oracles.
+	fn oracle_price(currency_id: CurrencyId) -> Result<Price, DispatchError> {
+		let valuation = T::ValuationCurrencyId::get();
+		match currency_id {
+			CurrencyId::StableAssetPoolToken(_) => {
+				T::StableAssetValuation::price(currency_id, valuation).ok_or_else(|| Error::<T>::NoPrice.into())
+			}
+			_ => T::AssetPriceProvider::get_relative_price(currency_id, valuation)
+				.ok_or_else(|| Error::<T>::NoPrice.into()),
+		}
+	}
+
+	/// Ensure the price of payment currency `currency_id` is available. DEX shares require both
+	/// oracle token prices, while the pool may still be empty.
+	fn ensure_payment_price_available(currency_id: CurrencyId) -> DispatchResult {
+		match currency_id {
+			CurrencyId::DexShare(dex_share_0, dex_share_1) => {
+				Self::oracle_price(dex_share_0.into())?;
+				Self::oracle_price(dex_share_1.into())?;
+			}
+			_ => {
+				Self::token_price(currency_id)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Fair price of DEX share `lp_currency_id` of `(currency_id_0, currency_id_1)` pool, quoted
+	/// in `T::ValuationCurrencyId`. Valued by the reserves invariant at oracle token prices, so
+	/// neither swapping within the pool nor its spot price can move it.
+	///
+	/// lp_price = 2 * sqrt(reserve_0 * price_0 * reserve_1 * price_1) / lp_total_issuance,
+	/// normalized by currency accuracy.
+	fn dex_share_price(
+		lp_currency_id: CurrencyId,
+		currency_id_0: CurrencyId,
+		currency_id_1: CurrencyId,
+	) -> Result<Price, DispatchError> {
+		let (reserve_0, reserve_1) = T::LiquidityPools::get_liquidity_pool(currency_id_0, currency_id_1);
+		let reserve_value = |currency_id: CurrencyId, reserve: Balance| -> Result<Price, DispatchError> {
+			Price::checked_from_rational(reserve, Self::currency_accuracy(currency_id)?)
+				.and_then(|units| units.checked_mul(&Self::oracle_price(currency_id).ok()?))
+				.ok_or_else(|| Error::<T>::NoPrice.into())
+		};
+		let (value_0, value_1) = (reserve_value(currency_id_0, reserve_0)?, reserve_value(currency_id_1, reserve_1)?);
+		// sqrt each side first only if the product overflows, at the cost of precision
+		let root = match value_0.checked_mul(&value_1) {
+			Some(product) => fixed_u128_sqrt(product)?,
+			None => fixed_u128_sqrt(value_0)?
+				.checked_mul(&fixed_u128_sqrt(value_1)?)
+				.ok_or(ArithmeticError::Overflow)?,
+		};
+		let pool_value = root
+			.checked_mul(&Price::saturating_from_integer(2))
+			.ok_or(ArithmeticError::Overflow)?;
+
+		let total_issuance = T::Currency::total_issuance(lp_currency_id);
+		ensure!(!total_issuance.is_zero(), Error::<T>::NoPrice);
+		let lp_units = Price::checked_from_rational(total_issuance, Self::currency_accuracy(lp_currency_id)?)
+			.ok_or(ArithmeticError::Overflow)?;
+		pool_value
+			.checked_div(&lp_units)
+			.ok_or_else(|| Error::<T>::NoPrice.into())
+	}
+
+	/// Subscribe to `subscription_id`, paying from `payer` and minting the subscribed SDAO for
+	/// `beneficiary`. Eligibility, whitelist and vesting slot checks apply to `beneficiary`.
+	///
+	/// Returns the subscribed amount.
+	fn do_subscribe(
+		payer: &T::AccountId,
+		beneficiary: &T::AccountId,
+		subscription_id: SubscriptionId,
+		payment_amount: Balance,
+		min_target_amount: Balance,
+		max_price: Option<Price>,
+	) -> Result<Balance, DispatchError> {
+		ensure!(!Self::all_subscriptions_paused(), Error::<T>::SubscriptionsPaused);
+		ensure!(!payment_amount.is_zero(), Error::<T>::ZeroPayment);
+
+		Subscriptions::<T>::try_mutate_exists(subscription_id, |maybe_subscription| -> Result<Balance, DispatchError> {
+			let subscription = maybe_subscription.as_mut().ok_or(Error::<T>::SubscriptionNotFound)?;
+			ensure!(
+				subscription.status == SubscriptionStatus::Active,
+				Error::<T>::SubscriptionPaused
+			);
+			let now = T::BlockNumberProvider::current_block_number();
+			ensure!(now >= subscription.start_block, Error::<T>::SubscriptionNotStarted);
+			ensure!(
+				!Self::is_expired(subscription, now),
+				Error::<T>::SubscriptionExpired
+			);
+			ensure!(
+				subscription.bypass_filter || T::SubscriberFilter::contains(beneficiary),
+				Error::<T>::NotEligible
+			);
+			ensure!(
+				!subscription.permissioned || SubscriptionWhitelist::<T>::contains_key(subscription_id, beneficiary),
+				Error::<T>::NotWhitelisted
+			);
+			if let Some(cooldown) = subscription.per_account_cooldown {
+				let last_subscribed_at = LastSubscribedAt::<T>::get(subscription_id, beneficiary);
+				ensure!(
+					T::CooldownExempt::contains(beneficiary)
+						|| last_subscribed_at.map_or(true, |last| now >= last.saturating_add(cooldown)),
+					Error::<T>::CooldownActive
+				);
+			}
+			// storage-light checks first, before any price fetching
+			if subscription.denomination == AmountDenomination::Adao {
+				ensure!(
+					subscription.state.total_sold < subscription.amount,
+					Error::<T>::SubscriptionIsFull
+				);
+			}
+
+			let prices = Self::subscription_prices(subscription.currency_id)?;
+			let (subscription_amount, last_discount, payment_value) =
+				Self::subscription_amount(subscription, payment_amount, prices, now)?;
+			ensure!(!subscription_amount.is_zero(), Error::<T>::ZeroSubscriptionAmount);
+			let (min_amount, amount) = Self::adao_amount_limits(subscription)?;
+
+			ensure!(
+				subscription_amount >= min_amount,
+				Error::<T>::BelowMinSubscriptionAmount
+			);
+			ensure!(
+				subscription_amount <= amount.saturating_sub(subscription.state.total_sold),
+				Error::<T>::SubscriptionIsFull
+			);
+			ensure!(
+				subscription_amount >= min_target_amount,
+				Error::<T>::BelowMinTargetAmount
+			);
+			if let Some(max_price) = max_price {
+				let price = Self::effective_price(payment_value, subscription_amount)?;
+				ensure!(price <= max_price, Error::<T>::PriceAboveLimit);
+			}
+
+			subscription.state.total_sold = subscription
+				.state
+				.total_sold
+				.checked_add(subscription_amount)
+				.expect("Subscription amount is smaller than remaining; qed");
+			subscription.state.total_payment = subscription.state.total_payment.saturating_add(payment_amount);
+			subscription.state.last_sold_at = now;
+			subscription.state.last_discount = last_discount;
+			LastSubscribedAt::<T>::insert(subscription_id, beneficiary, now);
+
+			let remaining = amount.saturating_sub(subscription.state.total_sold);
+			let sold_out = !subscription.state.sold_out && (remaining < min_amount || remaining.is_zero());
+			// the capacity of a subscription denominated in stable currency may grow with ADAO price
+			let fully_sold = remaining.is_zero() && subscription.denomination == AmountDenomination::Adao;
+			let discount_cap_reached =
+				!subscription.state.discount_cap_reached && last_discount == subscription.discount.max;
+			subscription.state.sold_out |= sold_out;
+			subscription.state.discount_cap_reached |= discount_cap_reached;
+
+			let vesting_period = Self::vesting_period_of(subscription, subscription_amount);
+			ensure!(
+				T::StakedToken::can_mint_for(beneficiary, vesting_period),
+				Error::<T>::VestingSlotsExhausted
+			);
+			let total_minted = T::StakedToken::preview_mint_for_subscription(subscription_amount)?
+				.mint
+				.checked_add(Self::total_subscription_minted())
+				.ok_or(ArithmeticError::Overflow)?;
+			ensure!(
+				Self::mint_cap().map_or(true, |cap| total_minted <= cap),
+				Error::<T>::MintCapExceeded
+			);
+
+			// payment
+			T::Currency::transfer(
+				subscription.currency_id,
+				payer,
+				&subscription.payment_dest,
+				payment_amount,
+			)?;
+			// mint ADAO token
+			T::StakedToken::mint_for_subscription(beneficiary, subscription_amount, vesting_period)?;
+			TotalSubscriptionMinted::<T>::put(total_minted);
+
+			Self::record_sold(subscription_id, now, payment_value, subscription_amount);
+
+			Self::deposit_event(Event::<T>::Subscribed {
+				who: payer.clone(),
+				beneficiary: beneficiary.clone(),
+				payment_dest: subscription.payment_dest.clone(),
+				subscription_id,
+				payment_amount,
+				subscription_amount,
+				remaining_after: remaining,
+				total_payment: subscription.state.total_payment,
+				adao_price: prices.0,
+				payment_price: prices.1,
+			});
+			if sold_out {
+				Self::deposit_event(Event::<T>::SubscriptionSoldOut {
+					id: subscription_id,
+					total_sold: subscription.state.total_sold,
+					total_payment: subscription.state.total_payment,
+				});
+			}
+			if discount_cap_reached {
+				Self::deposit_event(Event::<T>::DiscountCapReached { id: subscription_id });
+			}
+			if fully_sold {
+				Self::deposit_event(Self::closed_event(subscription_id, subscription));
+				*maybe_subscription = None;
+				Self::remove_active_subscription(subscription_id);
+				Self::clear_account_records(subscription_id);
+			}
+			Ok(subscription_amount)
+		})
+	}
+
+	/// Calculate the amount of ADAO tokens to be minted for a subscription, with `prices` from
+	/// `subscription_prices`.
 	///
-	/// Returns `(amount, last_discount)` if `Ok`.
+	/// Returns `(amount, last_discount, payment_value)` if `Ok`, with `payment_value` in
+	/// `T::ValuationCurrencyId`.
 	fn subscription_amount(
 		subscription: &SubscriptionOf<T>,
 		payment: Balance,
+		(adao_price, payment_price): (Price, Price),
 		now: T::BlockNumber,
-	) -> Result<(Balance, DiscountRate), DispatchError> {
+	) -> Result<(Balance, DiscountRate, Balance), DispatchError> {
 		let Subscription {
 			currency_id,
 			min_ratio,
 			discount,
-			state: subscription_state,
 			..
 		} = subscription;
 
-		// ADAO price: from DEX
-		let adao_price = T::AdaoPriceProvider::get_relative_price(Token(ADAO), T::StableCurrencyId::get())
-			.ok_or(Error::<T>::NoPrice)?;
-		// Payment currency price, from oracles
-		let payment_price = T::AssetPriceProvider::get_relative_price(*currency_id, T::StableCurrencyId::get())
-			.ok_or(Error::<T>::NoPrice)?;
+		let price_discount = Self::price_discount(subscription, now)?;
+
+		// start_price = price * (1 - price_discount), above `price` if `price_discount` is negative
+		let start_price = {
+			let ratio = DiscountRate::one()
+				.checked_sub(&price_discount)
+				.ok_or(ArithmeticError::Underflow)?;
+			// ratio is not negative, as `discount` <= `discount.max` <= 1.
+			let ratio_fixed_u128 = u128::try_from(ratio.into_inner())
+				.map(Price::from_inner)
+				.map_err(|_| ArithmeticError::Underflow)?;
+			adao_price
+				.checked_mul(&ratio_fixed_u128)
+				.ok_or(ArithmeticError::Overflow)?
+		};
+
+		// payment value in units of `T::ValuationCurrencyId`, the same as ADAO prices, so the
+		// solve below doesn't depend on the payment currency decimals
+		let payment_accuracy = Self::currency_accuracy(*currency_id)?;
+		let payment_value = Price::checked_from_rational(payment, payment_accuracy)
+			.ok_or(ArithmeticError::Overflow)?
+			.checked_mul(&payment_price)
+			.ok_or(ArithmeticError::Overflow)?;
+		// the ADAO price moves by `inc` per ADAO subscribed, up if `dec_per_unit` is positive and down
+		// if negative
+		let dec_per_unit = Price::from_inner(discount.dec_per_unit.into_inner().unsigned_abs());
+		let inc = adao_price.checked_mul(&dec_per_unit).ok_or(ArithmeticError::Overflow)?;
+		let x = (Price::one() + Price::one())
+			.checked_mul(&inc)
+			.ok_or(ArithmeticError::Overflow)?
+			.checked_mul(&payment_value)
+			.ok_or(ArithmeticError::Overflow)?;
+		let y = start_price.checked_mul(&start_price).ok_or(ArithmeticError::Overflow)?;
+
+		let receive_amount = if inc.is_zero() {
+			// receive_amount = payment_value / start_price
+			let amount = payment_value
+				.checked_div(&start_price)
+				.ok_or(ArithmeticError::DivisionByZero)?;
+			Self::fixed_u128_to_adao_balance(amount)?
+		} else if discount.dec_per_unit.is_positive() {
+			// receive_amount = (sqrt(2 * inc * payment_value + start_price ** 2) - startPrice) / inc
+			let z = x.checked_add(&y).ok_or(ArithmeticError::Overflow)?;
+			let amount = fixed_u128_sqrt(z)?
+				.checked_sub(&start_price)
+				.ok_or(ArithmeticError::Underflow)?
+				.checked_div(&inc)
+				.ok_or(ArithmeticError::DivisionByZero)?;
+			Self::fixed_u128_to_adao_balance(amount)?
+		} else {
+			// receive_amount = (start_price - sqrt(start_price ** 2 - 2 * inc * payment_value)) / inc
+			//                = 2 * payment_value / (start_price + sqrt(start_price ** 2 - 2 * inc * payment_value))
+			match y.checked_sub(&x) {
+				Some(z) => {
+					let amount = payment_value
+						.checked_add(&payment_value)
+						.ok_or(ArithmeticError::Overflow)?
+						.checked_div(&start_price.saturating_add(fixed_u128_sqrt(z)?))
+						.ok_or(ArithmeticError::DivisionByZero)?;
+					Self::fixed_u128_to_adao_balance(amount)?
+				}
+				// the price would drop to zero before the payment is used up, bounded by `min_ratio`
+				// below
+				None => Balance::max_value(),
+			}
+		};
+		// at most `1 / min_ratio` ADAO per unit of payment currency
+		let adao_accuracy = Self::currency_accuracy(Token(ADAO))?;
+		let payment_in_adao_accuracy = Price::checked_from_rational(adao_accuracy, payment_accuracy)
+			.ok_or(ArithmeticError::Overflow)?
+			.checked_mul_int(payment)
+			.ok_or(ArithmeticError::Overflow)?;
+		let max_amount = min_ratio
+			.reciprocal()
+			.ok_or(ArithmeticError::DivisionByZero)?
+			.checked_mul_int(payment_in_adao_accuracy)
+			.ok_or(ArithmeticError::Overflow)?;
+		let final_amount = receive_amount.min(max_amount);
 
-		// discount
+		let valuation_accuracy = Self::currency_accuracy(T::ValuationCurrencyId::get())?;
+		let payment_value_balance = payment_value
+			.checked_mul_int(valuation_accuracy)
+			.ok_or(ArithmeticError::Overflow)?;
+		Ok((final_amount, price_discount, payment_value_balance))
+	}
+
+	/// The price discount of a subscription at block `now`, before the subscribed amount is taken
+	/// into account.
+	fn price_discount(subscription: &SubscriptionOf<T>, now: T::BlockNumber) -> Result<DiscountRate, DispatchError> {
+		let discount = Self::unclamped_price_discount(subscription, now)?;
+		Ok(subscription.discount.clamp(discount))
+	}
 
-		// idle_intervals = (now - last_sold_at) / interval
+	/// The price discount of a subscription at block `now`, not clamped to `[discount.min,
+	/// discount.max]`.
+	fn unclamped_price_discount(
+		subscription: &SubscriptionOf<T>,
+		now: T::BlockNumber,
+	) -> Result<DiscountRate, DispatchError> {
+		let discount = &subscription.discount;
+		let subscription_state = &subscription.state;
+
+		// idle_intervals = min((now - last_sold_at) / interval, max_idle_intervals)
 		let idle_intervals = now
-			.saturating_sub(subscription.state.last_sold_at)
-			.checked_div(&subscription.discount.interval)
+			.saturating_sub(subscription_state.last_sold_at)
+			.checked_div(&discount.interval)
 			.map(|n| {
-				let n_u64 = UniqueSaturatedInto::<u64>::unique_saturated_into(n);
+				let n_u64 = UniqueSaturatedInto::<u64>::unique_saturated_into(n)
+					.min(T::MaxIdleIntervals::get().into());
 				DiscountRate::checked_from_integer(n_u64 as i128).expect("Block number can't overflow; qed")
 			})
 			.ok_or(ArithmeticError::Underflow)?;
@@ -396,85 +1521,312 @@ impl<T: Config> Pallet<T> {
 				.checked_mul(&DiscountRate::checked_from_integer(total_sold_units).ok_or(ArithmeticError::Overflow)?)
 				.ok_or(ArithmeticError::Overflow)?
 		};
-		// price_discount = min(max_discount, last_discount + discount_inc - discount_dec)
-		let price_discount = {
-			let d = subscription_state
-				.last_discount
-				.checked_add(&discount_inc)
-				.ok_or(ArithmeticError::Overflow)?
-				.checked_sub(&discount_dec)
-				.ok_or(ArithmeticError::Underflow)?;
-			FixedI128::min(d, discount.max)
-		};
+		// price_discount = last_discount + discount_inc - discount_dec
+		let d = subscription_state
+			.last_discount
+			.checked_add(&discount_inc)
+			.ok_or(ArithmeticError::Overflow)?
+			.checked_sub(&discount_dec)
+			.ok_or(ArithmeticError::Underflow)?;
+		Ok(d)
+	}
 
-		// start_price = price * (1 - price_discount)
-		let start_price = {
-			let ratio = DiscountRate::one()
-				.checked_sub(&price_discount)
-				.ok_or(ArithmeticError::Underflow)?;
-			// ratio is positive, as `discount` <= `discount.max`.
-			let ratio_fixed_u128 = Price::from_inner(ratio.into_inner().abs() as u128);
-			adao_price
-				.checked_mul(&ratio_fixed_u128)
-				.ok_or(ArithmeticError::Overflow)?
+	/// The price discount of a subscription at block `at`, if no subscribing happens before. A
+	/// paused subscription keeps the discount of when it was paused. `None` if the subscription is
+	/// not found.
+	pub fn discount_at(subscription_id: SubscriptionId, at: T::BlockNumber) -> Option<DiscountRate> {
+		let subscription = Self::subscriptions(subscription_id)?;
+		let at = match subscription.status {
+			SubscriptionStatus::Paused { since } => at.min(since),
+			SubscriptionStatus::Active => at,
 		};
+		Self::price_discount(&subscription, at).ok()
+	}
 
-		let payment_value = Price::checked_from_integer(payment)
-			.ok_or(ArithmeticError::Overflow)?
-			.checked_mul(&payment_price)
-			.ok_or(ArithmeticError::Overflow)?;
-		let dec_per_unit = Price::from_inner(discount.dec_per_unit.into_inner().abs() as u128);
-		let inc = adao_price.checked_mul(&dec_per_unit).ok_or(ArithmeticError::Overflow)?;
-		// receive_amount = (sqrt(2 * inc * payment_value + start_price ** 2) - startPrice) / inc
-		let x = {
-			let payment_accuracy = Self::currency_accuracy(*currency_id)?;
-			(Price::one() + Price::one())
-				.checked_mul(&inc)
-				.ok_or(ArithmeticError::Overflow)?
-				.checked_mul(&payment_value)
-				.ok_or(ArithmeticError::Overflow)?
-				// payment value needs to be normalized into units
-				.checked_div(&Price::saturating_from_integer(payment_accuracy))
-				.expect("Currency accuracy cannot be zero; qed")
-		};
-		let y = start_price.checked_mul(&start_price).ok_or(ArithmeticError::Overflow)?;
-		let z = x.checked_add(&y).ok_or(ArithmeticError::Overflow)?;
+	/// The number of blocks from now until the price discount of a subscription reaches `target`,
+	/// if no subscribing happens before. `None` if the subscription is not found, paused, or the
+	/// discount would never reach `target`.
+	pub fn blocks_until_discount(subscription_id: SubscriptionId, target: DiscountRate) -> Option<T::BlockNumber> {
+		let subscription = Self::subscriptions(subscription_id)?;
+		if subscription.status != SubscriptionStatus::Active {
+			return None;
+		}
+		let now = T::BlockNumberProvider::current_block_number();
+		if Self::price_discount(&subscription, now).ok()? >= target {
+			return Some(Zero::zero());
+		}
+		let discount = &subscription.discount;
+		if target > discount.max || discount.inc_on_idle <= DiscountRate::zero() {
+			return None;
+		}
 
-		let receive_amount = {
-			let sqrt = fixed_u128_sqrt(z)?;
-			let amount = sqrt
-				.checked_sub(&start_price)
-				.ok_or(ArithmeticError::Underflow)?
-				.checked_div(&inc)
-				.ok_or(ArithmeticError::DivisionByZero)?;
-			Self::fixed_u128_to_adao_balance(amount)?
-		};
-		let max_amount = min_ratio
-			.reciprocal()
-			.ok_or(ArithmeticError::DivisionByZero)?
-			.checked_mul_int(payment)
+		// idle intervals needed = ceil((target - discount without idle intervals) / inc_on_idle)
+		let base = Self::unclamped_price_discount(&subscription, subscription.state.last_sold_at).ok()?;
+		let intervals = target
+			.checked_sub(&base)?
+			.checked_div(&discount.inc_on_idle)?
+			.ceil()
+			.into_inner()
+			/ DiscountRate::accuracy();
+		if intervals > T::MaxIdleIntervals::get().into() {
+			return None;
+		}
+		let reached_at = discount
+			.interval
+			.checked_mul(&T::BlockNumber::from(intervals as u32))?
+			.checked_add(&subscription.state.last_sold_at)?;
+		Some(reached_at.saturating_sub(now))
+	}
+
+	/// Remaining capacity of a subscription, in `ADAO` amount. `None` if the subscription is not
+	/// found, or the capacity can't be converted into `ADAO` amount for lack of price.
+	pub fn remaining_capacity(subscription_id: SubscriptionId) -> Option<Balance> {
+		let subscription = Self::subscriptions(subscription_id)?;
+		Self::unsold_amount(&subscription)
+	}
+
+	fn unsold_amount(subscription: &SubscriptionOf<T>) -> Option<Balance> {
+		let (_, amount) = Self::adao_amount_limits(subscription).ok()?;
+		Some(amount.saturating_sub(subscription.state.total_sold))
+	}
+
+	/// Store `subscription` with the next subscription ID, and return the ID.
+	fn insert_subscription(subscription: SubscriptionOf<T>) -> Result<SubscriptionId, DispatchError> {
+		let subscription_id = SubscriptionIndex::<T>::try_mutate(|id| -> Result<SubscriptionId, DispatchError> {
+			let current_id = *id;
+			*id = id.checked_add(One::one()).ok_or(ArithmeticError::Overflow)?;
+			Ok(current_id)
+		})?;
+		ActiveSubscriptions::<T>::try_mutate(|ids| {
+			ids.try_push(subscription_id).map_err(|_| Error::<T>::TooManyActiveSubscriptions)
+		})?;
+		Subscriptions::<T>::insert(subscription_id, subscription);
+		Ok(subscription_id)
+	}
+
+	fn remove_active_subscription(id: SubscriptionId) {
+		ActiveSubscriptions::<T>::mutate(|ids| ids.retain(|active_id| *active_id != id));
+	}
+
+	fn closed_event(id: SubscriptionId, subscription: &SubscriptionOf<T>) -> Event<T> {
+		Event::<T>::SubscriptionClosed {
+			id,
+			currency_id: subscription.currency_id,
+			total_sold: subscription.state.total_sold,
+			total_payment: subscription.state.total_payment,
+			remaining: Self::unsold_amount(subscription).unwrap_or_default(),
+		}
+	}
+
+	/// Quote the `ADAO` amount, price discount and mint preview of subscribing `payment_amount`
+	/// at current block. `None` if the subscription is not found, or for lack of price.
+	pub fn quote_subscription(
+		subscription_id: SubscriptionId,
+		payment_amount: Balance,
+	) -> Option<(Balance, DiscountRate, MintPreview)> {
+		let subscription = Self::subscriptions(subscription_id)?;
+		let now = T::BlockNumberProvider::current_block_number();
+		let prices = Self::subscription_prices(subscription.currency_id).ok()?;
+		let (amount, discount, _) = Self::subscription_amount(&subscription, payment_amount, prices, now).ok()?;
+		let preview = T::StakedToken::preview_mint_for_subscription(amount).ok()?;
+		Some((amount, discount, preview))
+	}
+
+	/// Remove the whitelist and last subscribed blocks of a closed subscription.
+	fn clear_account_records(subscription_id: SubscriptionId) {
+		let _ = SubscriptionWhitelist::<T>::remove_prefix(subscription_id, None);
+		let _ = LastSubscribedAt::<T>::remove_prefix(subscription_id, None);
+	}
+
+	/// Returns `true` if `subscription` is expired at block `now`.
+	fn is_expired(subscription: &SubscriptionOf<T>, now: T::BlockNumber) -> bool {
+		subscription.end_block.map_or(false, |end_block| now > end_block)
+	}
+
+	/// Vesting period of a subscription for subscribed `amount`, based on vesting tiers.
+	fn vesting_period_of(subscription: &SubscriptionOf<T>, amount: Balance) -> T::BlockNumber {
+		subscription
+			.vesting_tiers
+			.iter()
+			.rev()
+			.find(|(threshold, _)| *threshold <= amount)
+			.map_or(subscription.vesting_period, |(_, period)| *period)
+	}
+
+	/// ADAO, SDAO and DEX shares containing SDAO can't be used for payment.
+	fn is_supported_payment_currency(currency_id: CurrencyId) -> bool {
+		match currency_id {
+			Token(ADAO) | Token(SDAO) => false,
+			// `ADAO` DEX shares are accepted for protocol owned liquidity
+			CurrencyId::DexShare(dex_share_0, dex_share_1) => ![dex_share_0, dex_share_1]
+				.iter()
+				.any(|dex_share| matches!(dex_share, DexShare::Token(SDAO))),
+			_ => true,
+		}
+	}
+
+	/// Ensure discount `interval`, `min_ratio` and `amount` are not zero. Subscribing divides by
+	/// `interval`, and gets nothing with zero `min_ratio` or `amount`.
+	fn ensure_valid_parameters(
+		discount: &Discount<T::BlockNumber>,
+		min_ratio: Ratio,
+		amount: Balance,
+	) -> DispatchResult {
+		ensure!(
+			!discount.interval.is_zero() && !min_ratio.is_zero() && !amount.is_zero(),
+			Error::<T>::InvalidDiscountParameters
+		);
+		Ok(())
+	}
+
+	/// Vesting tier thresholds are strictly ascending.
+	fn is_sorted(vesting_tiers: &VestingTiers<T::BlockNumber>) -> bool {
+		vesting_tiers.windows(2).all(|w| w[0].0 < w[1].0)
+	}
+
+	/// The effective ADAO price of a subscription, in `payment_value / amount` normalized by
+	/// currency accuracy. `payment_value` is in `T::ValuationCurrencyId`.
+	fn effective_price(payment_value: Balance, amount: Balance) -> Result<Price, DispatchError> {
+		let accuracy_ratio = Price::checked_from_rational(
+			Self::currency_accuracy(Token(ADAO))?,
+			Self::currency_accuracy(T::ValuationCurrencyId::get())?,
+		)
+		.ok_or(ArithmeticError::Overflow)?;
+		// zero amount or overflow, the price is above any limit
+		Price::checked_from_rational(payment_value, amount)
+			.and_then(|p| p.checked_mul(&accuracy_ratio))
+			.ok_or_else(|| Error::<T>::PriceAboveLimit.into())
+	}
+
+	/// The analytics era of block `n`.
+	fn era_of(n: T::BlockNumber) -> u32 {
+		n.checked_div(&T::BlocksPerEra::get())
+			.unwrap_or_default()
+			.unique_saturated_into()
+	}
+
+	/// Accumulate sold stats into the era of `now`, and prune eras beyond retention.
+	fn record_sold(subscription_id: SubscriptionId, now: T::BlockNumber, payment_value: Balance, sold: Balance) {
+		let era = Self::era_of(now);
+		DailySold::<T>::mutate(subscription_id, era, |(value, amount)| {
+			*value = value.saturating_add(payment_value);
+			*amount = amount.saturating_add(sold);
+		});
+
+		let retention = T::SoldRetentionEras::get();
+		let expired: Vec<u32> = DailySold::<T>::iter_key_prefix(subscription_id)
+			.filter(|e| e.saturating_add(retention) <= era)
+			.collect();
+		expired
+			.into_iter()
+			.for_each(|e| DailySold::<T>::remove(subscription_id, e));
+	}
+
+	/// Retained sold stats of a subscription, in `(era, payment_value, sold_amount)` sorted by
+	/// era.
+	pub fn sold_series(subscription_id: SubscriptionId) -> Vec<(u32, Balance, Balance)> {
+		let mut series: Vec<(u32, Balance, Balance)> = DailySold::<T>::iter_prefix(subscription_id)
+			.map(|(era, (value, amount))| (era, value, amount))
+			.collect();
+		series.sort_by_key(|(era, _, _)| *era);
+		series
+	}
+
+	/// The `(min_amount, amount)` limits of a subscription in `ADAO` units.
+	fn adao_amount_limits(subscription: &SubscriptionOf<T>) -> Result<(Balance, Balance), DispatchError> {
+		match subscription.denomination {
+			AmountDenomination::Adao => Ok((subscription.min_amount, subscription.amount)),
+			AmountDenomination::Stable => {
+				let adao_price = Self::adao_price()?;
+				let stable_price = Self::stable_price()?;
+				// adao_amount = stable_amount * stable_price / adao_price, normalized by currency accuracy
+				let ratio = stable_price
+					.checked_div(&adao_price)
+					.ok_or(ArithmeticError::DivisionByZero)?
+					.checked_mul(
+						&Price::checked_from_rational(
+							Self::currency_accuracy(Token(ADAO))?,
+							Self::currency_accuracy(T::StableCurrencyId::get())?,
+						)
+						.ok_or(ArithmeticError::Overflow)?,
+					)
+					.ok_or(ArithmeticError::Overflow)?;
+				let min_amount = ratio
+					.checked_mul_int(subscription.min_amount)
+					.ok_or(ArithmeticError::Overflow)?;
+				let amount = ratio
+					.checked_mul_int(subscription.amount)
+					.ok_or(ArithmeticError::Overflow)?;
+				Ok((min_amount, amount))
+			}
+		}
+	}
+
+	/// ADAO price, quoted in `T::ValuationCurrencyId`. The average of recorded samples if
+	/// `T::PriceRecordInterval` is set, and the DEX spot price otherwise or before any sample.
+	fn adao_price() -> Result<Price, DispatchError> {
+		let samples = Self::adao_price_samples();
+		if T::PriceRecordInterval::get().is_zero() || samples.is_empty() {
+			return T::AdaoPriceProvider::get_relative_price(Token(ADAO), T::ValuationCurrencyId::get())
+				.ok_or_else(|| Error::<T>::NoPrice.into());
+		}
+
+		let sum = samples
+			.iter()
+			.try_fold(Price::zero(), |acc, price| acc.checked_add(price))
 			.ok_or(ArithmeticError::Overflow)?;
-		let final_amount = receive_amount.min(max_amount);
+		sum.checked_div(&Price::saturating_from_integer(samples.len() as u128))
+			.ok_or_else(|| ArithmeticError::DivisionByZero.into())
+	}
+
+	/// Record the DEX spot ADAO price, if `now` is a `T::PriceRecordInterval` block. No sample is
+	/// recorded without a price.
+	fn record_adao_price(now: T::BlockNumber) -> Weight {
+		let interval = T::PriceRecordInterval::get();
+		if interval.is_zero() || !(now % interval).is_zero() {
+			return 0;
+		}
+
+		if let Some(price) = T::AdaoPriceProvider::get_relative_price(Token(ADAO), T::ValuationCurrencyId::get()) {
+			AdaoPriceSamples::<T>::mutate(|samples| {
+				if samples.try_push(price).is_err() && !samples.is_empty() {
+					samples.remove(0);
+					let _ = samples.try_push(price);
+				}
+			});
+		}
+		<T as Config>::WeightInfo::record_adao_price()
+	}
 
-		Ok((final_amount, price_discount))
+	/// `T::StableCurrencyId` price, quoted in `T::ValuationCurrencyId`.
+	fn stable_price() -> Result<Price, DispatchError> {
+		let stable = T::StableCurrencyId::get();
+		let valuation = T::ValuationCurrencyId::get();
+		if stable == valuation {
+			return Ok(Price::one());
+		}
+		T::AssetPriceProvider::get_relative_price(stable, valuation).ok_or_else(|| Error::<T>::NoPrice.into())
 	}
 
-	fn account_id() -> T::AccountId {
+	/// The pallet account which held subscription payments before `T::ProceedsDestination`.
+	pub fn account_id() -> T::AccountId {
 		T::PalletId::get().into_account()
 	}
 
 	fn currency_accuracy(currency: CurrencyId) -> Result<u128, DispatchError> {
-		let decimals = currency.decimals().ok_or(Error::<T>::NoDecimalsInfo)?;
+		let decimals = match currency {
+			CurrencyId::StableAssetPoolToken(_) => T::StableAssetValuation::decimals(currency),
+			_ => T::CurrencyDecimals::decimals(currency).or_else(|| currency.decimals()),
+		}
+		.ok_or(Error::<T>::NoDecimalsInfo)?;
 		Ok(10_u128.pow(decimals as u32))
 	}
 
 	fn fixed_u128_to_adao_balance(n: FixedU128) -> Result<Balance, DispatchError> {
 		let adao_accuracy = Self::currency_accuracy(Token(ADAO))?;
-		Ok(n.into_inner()
-			.checked_mul(adao_accuracy)
-			.ok_or(ArithmeticError::Overflow)?
-			.checked_div(FixedU128::accuracy())
-			.expect("`FixedPointNumber` accuracy can't be zero; qed"))
+		// n * adao_accuracy / accuracy, without overflowing on the intermediate product
+		multiply_by_rational(n.into_inner(), adao_accuracy, FixedU128::accuracy())
+			.map_err(|_| ArithmeticError::Overflow.into())
 	}
 }
 
@@ -0,0 +1,204 @@
+// This file is part of Acala.
+
+// Copyright (C) 2020-2022 Acala Foundation.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Storage migrations.
+
+use super::*;
+use frame_support::{
+	log,
+	storage::{
+		migration::{put_storage_value, storage_iter, storage_key_iter},
+		storage_prefix, unhashed,
+	},
+	traits::PalletInfoAccess,
+	StorageHasher,
+};
+use sp_runtime::traits::Bounded;
+
+/// `Subscription` before this storage version series, as encoded on chain at storage version 0.
+#[derive(Decode)]
+pub(crate) struct OldSubscription<BlockNumber> {
+	currency_id: CurrencyId,
+	vesting_period: BlockNumber,
+	min_amount: Balance,
+	min_ratio: Ratio,
+	amount: Balance,
+	discount: OldDiscount<BlockNumber>,
+	state: OldSubscriptionState<BlockNumber>,
+}
+
+/// `Discount` at storage version 0, without `min`.
+#[derive(Decode)]
+struct OldDiscount<BlockNumber> {
+	max: DiscountRate,
+	interval: BlockNumber,
+	inc_on_idle: DiscountRate,
+	dec_per_unit: DiscountRate,
+}
+
+/// `SubscriptionState` at storage version 0, without `total_payment` and lifecycle flags.
+#[derive(Decode)]
+struct OldSubscriptionState<BlockNumber> {
+	total_sold: Balance,
+	last_sold_at: BlockNumber,
+	last_discount: DiscountRate,
+}
+
+/// Translate a storage version 0 subscription into the current layout. Added parameters default
+/// to the behavior before they existed: no discount floor, vesting tiers, schedule, whitelist or
+/// cooldown, and payments to `T::ProceedsDestination`.
+pub(crate) fn translate_subscription<T: Config>(old: OldSubscription<T::BlockNumber>) -> SubscriptionOf<T> {
+	Subscription {
+		currency_id: old.currency_id,
+		vesting_period: old.vesting_period,
+		vesting_tiers: Default::default(),
+		min_amount: old.min_amount,
+		min_ratio: old.min_ratio,
+		amount: old.amount,
+		denomination: AmountDenomination::Adao,
+		discount: Discount {
+			max: old.discount.max,
+			min: DiscountRate::min_value(),
+			interval: old.discount.interval,
+			inc_on_idle: old.discount.inc_on_idle,
+			dec_per_unit: old.discount.dec_per_unit,
+		},
+		bypass_filter: false,
+		status: SubscriptionStatus::Active,
+		start_block: Zero::zero(),
+		end_block: None,
+		permissioned: false,
+		payment_dest: T::ProceedsDestination::get(),
+		per_account_cooldown: None,
+		state: SubscriptionState {
+			total_sold: old.state.total_sold,
+			// not tracked before
+			total_payment: Zero::zero(),
+			last_sold_at: old.state.last_sold_at,
+			last_discount: old.state.last_discount,
+			sold_out: false,
+			discount_cap_reached: false,
+		},
+	}
+}
+
+/// Index open subscriptions in `ActiveSubscriptions`.
+pub mod v1 {
+	use super::*;
+
+	/// Ids of stored subscriptions, sorted. Subscriptions are in storage version 0 layout, with
+	/// `u32` ids.
+	fn subscription_ids<T: Config>() -> Vec<u32> {
+		let mut ids: Vec<u32> = storage_key_iter::<u32, OldSubscription<T::BlockNumber>, Twox64Concat>(
+			Pallet::<T>::name().as_bytes(),
+			b"Subscriptions",
+		)
+		.map(|(id, _)| id)
+		.collect();
+		ids.sort_unstable();
+		ids
+	}
+
+	/// Check that all stored subscriptions fit in `ActiveSubscriptions`.
+	#[cfg(feature = "try-runtime")]
+	pub fn pre_upgrade<T: Config>() -> Result<(), &'static str> {
+		if StorageVersion::get::<Pallet<T>>() >= 1 {
+			return Ok(());
+		}
+		ensure!(
+			subscription_ids::<T>().len() <= T::MaxActiveSubscriptions::get() as usize,
+			"open subscriptions exceed MaxActiveSubscriptions"
+		);
+		Ok(())
+	}
+
+	pub fn migrate<T: Config>() -> Weight {
+		if StorageVersion::get::<Pallet<T>>() >= 1 {
+			return 0;
+		}
+
+		let ids = subscription_ids::<T>();
+		let count = ids.len() as u64;
+		if ids.len() > T::MaxActiveSubscriptions::get() as usize {
+			// left at storage version 0, to be migrated after closing subscriptions or raising the bound
+			log::error!(
+				target: "aqua-dao",
+				"{} open subscriptions exceed MaxActiveSubscriptions, migration skipped",
+				ids.len()
+			);
+			return T::DbWeight::get().reads(count.saturating_add(1));
+		}
+		// stored as `u32` ids, and translated in v2
+		unhashed::put(&ActiveSubscriptions::<T>::hashed_key(), &ids);
+		StorageVersion::new(1).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(count.saturating_add(1), 2)
+	}
+}
+
+/// Migrate `SubscriptionId` from `u32` to `u64`.
+pub mod v2 {
+	use super::*;
+
+	pub fn migrate<T: Config>() -> Weight {
+		// runs after v1 only
+		if StorageVersion::get::<Pallet<T>>() != 1 {
+			return 0;
+		}
+
+		let _ = SubscriptionIndex::<T>::translate::<u32, _>(|maybe_index| maybe_index.map(Into::into));
+		let _ = ActiveSubscriptions::<T>::translate::<Vec<u32>, _>(|maybe_ids| {
+			maybe_ids.map(|ids| {
+				let ids: Vec<SubscriptionId> = ids.into_iter().map(Into::into).collect();
+				// same length as before, within the bound, qed.
+				BoundedVec::try_from(ids).unwrap_or_default()
+			})
+		});
+		// subscriptions are translated from storage version 0 layout as well
+		let count = migrate_keys::<T, _, _, _>(b"Subscriptions", translate_subscription::<T>)
+			.saturating_add(migrate_keys::<T, (Balance, Balance), _, _>(b"DailySold", |v| v))
+			.saturating_add(migrate_keys::<T, (), _, _>(b"SubscriptionWhitelist", |v| v))
+			.saturating_add(migrate_keys::<T, T::BlockNumber, _, _>(b"LastSubscribedAt", |v| v));
+		StorageVersion::new(2).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(count.saturating_add(2), count.saturating_mul(2).saturating_add(3))
+	}
+
+	/// Re-key entries of the `item` map or double map, whose first key is a `Twox64Concat` hashed
+	/// `u32` subscription id, with the `u64` id, and `translate` the value. Entries failing to decode
+	/// are kept under the old key. Returns the number of migrated entries.
+	fn migrate_keys<T: Config, V: Decode, W: Encode, F: Fn(V) -> W>(item: &[u8], translate: F) -> u64 {
+		let module = Pallet::<T>::name().as_bytes();
+		let prefix = storage_prefix(module, item);
+		// collected first, as re-keyed entries would be iterated again otherwise
+		let entries: Vec<(Vec<u8>, V)> = storage_iter::<V>(module, item).collect();
+		let mut count = 0u64;
+		for (key, value) in entries {
+			// 8 bytes of hash, followed by the encoded id and the rest of double map keys if any
+			let maybe_id = key.get(8..12).and_then(|mut raw| u32::decode(&mut raw).ok());
+			if let Some(id) = maybe_id {
+				let mut new_key = Twox64Concat::hash(&SubscriptionId::from(id).encode());
+				new_key.extend_from_slice(&key[12..]);
+				put_storage_value(module, item, &new_key, translate(value));
+				unhashed::kill(&[&prefix[..], &key].concat());
+				count = count.saturating_add(1);
+			}
+		}
+		count
+	}
+}
@@ -24,7 +24,7 @@ use super::*;
 use acala_primitives::{Amount, TokenSymbol};
 use frame_support::{
 	parameter_types,
-	traits::{Everything, Nothing},
+	traits::{Contains, Everything, Nothing},
 };
 use frame_system::EnsureRoot;
 use module_support::mocks::MockAddressMapping;
@@ -41,10 +41,17 @@ pub type AccountId = AccountId32;
 pub type BlockNumber = u64;
 
 pub const ALICE: AccountId = AccountId32::new([1u8; 32]);
+pub const BOB: AccountId = AccountId32::new([2u8; 32]);
+pub const DAO_ACCOUNT: AccountId = AccountId32::new([100u8; 32]);
 
 pub const AUSD_CURRENCY: CurrencyId = Token(TokenSymbol::AUSD);
 pub const ADAO_CURRENCY: CurrencyId = Token(TokenSymbol::ADAO);
 pub const DOT_CURRENCY: CurrencyId = Token(TokenSymbol::DOT);
+pub const FOREIGN_CURRENCY: CurrencyId = CurrencyId::ForeignAsset(0);
+pub const FOREIGN_18_DECIMALS_CURRENCY: CurrencyId = CurrencyId::ForeignAsset(2);
+pub const POOL_TOKEN_CURRENCY: CurrencyId = CurrencyId::StableAssetPoolToken(0);
+pub const ADAO_AUSD_LP: CurrencyId =
+	CurrencyId::DexShare(DexShare::Token(TokenSymbol::AUSD), DexShare::Token(TokenSymbol::ADAO));
 
 parameter_types! {
 	pub const BlockHashCount: u64 = 250;
@@ -133,6 +140,14 @@ impl module_currencies::Config for Runtime {
 thread_local! {
 	static DOT_PRICE: RefCell<Option<Price>> = RefCell::new(Some(Price::one()));
 	static ADAO_PRICE: RefCell<Option<Price>> = RefCell::new(Some(Price::one()));
+	static ORACLE_ADAO_PRICE: RefCell<Option<Price>> = RefCell::new(None);
+}
+
+thread_local! {
+	static PRICE_CALLS: RefCell<u32> = RefCell::new(0);
+	static LAST_PRICE_QUERY: RefCell<Option<CurrencyId>> = RefCell::new(None);
+	static RELATIVE_PRICE_CALLS: RefCell<u32> = RefCell::new(0);
+	static LAST_RELATIVE_PRICE_QUERY: RefCell<Option<(CurrencyId, CurrencyId)>> = RefCell::new(None);
 }
 
 pub struct MockPriceProvider;
@@ -140,35 +155,131 @@ impl MockPriceProvider {
 	pub fn set_price(currency_id: CurrencyId, price: Option<Price>) {
 		match currency_id {
 			DOT_CURRENCY => DOT_PRICE.with(|v| *v.borrow_mut() = price),
+			ADAO_CURRENCY => ADAO_PRICE.with(|v| *v.borrow_mut() = price),
 			_ => {}
 		}
 	}
+
+	/// Set the oracle ADAO price, `None` by default. The DEX ADAO price is set by `set_price`.
+	pub fn set_oracle_adao_price(price: Option<Price>) {
+		ORACLE_ADAO_PRICE.with(|v| *v.borrow_mut() = price);
+	}
+
+	/// Number of price queries made, both `get_price` and DEX `get_relative_price`.
+	pub fn price_calls() -> u32 {
+		PRICE_CALLS.with(|v| *v.borrow()) + RELATIVE_PRICE_CALLS.with(|v| *v.borrow())
+	}
+
+	/// The last `get_price` query.
+	pub fn last_price_query() -> Option<CurrencyId> {
+		LAST_PRICE_QUERY.with(|v| *v.borrow())
+	}
+
+	/// Number of DEX `get_relative_price` queries made.
+	pub fn relative_price_calls() -> u32 {
+		RELATIVE_PRICE_CALLS.with(|v| *v.borrow())
+	}
+
+	/// The last DEX `get_relative_price` query, in `(base, quote)`.
+	pub fn last_relative_price_query() -> Option<(CurrencyId, CurrencyId)> {
+		LAST_RELATIVE_PRICE_QUERY.with(|v| *v.borrow())
+	}
+
+	pub fn reset_calls() {
+		PRICE_CALLS.with(|v| *v.borrow_mut() = 0);
+		LAST_PRICE_QUERY.with(|v| *v.borrow_mut() = None);
+		RELATIVE_PRICE_CALLS.with(|v| *v.borrow_mut() = 0);
+		LAST_RELATIVE_PRICE_QUERY.with(|v| *v.borrow_mut() = None);
+	}
+
+	pub fn assert_no_price_calls() {
+		assert_eq!(Self::price_calls(), 0, "unexpected price queries");
+	}
 }
 impl PriceProvider<CurrencyId> for MockPriceProvider {
 	fn get_price(currency_id: CurrencyId) -> Option<Price> {
+		PRICE_CALLS.with(|v| *v.borrow_mut() += 1);
+		LAST_PRICE_QUERY.with(|v| *v.borrow_mut() = Some(currency_id));
 		match currency_id {
 			AUSD_CURRENCY => Some(Price::one()),
 			DOT_CURRENCY => DOT_PRICE.with(|v| *v.borrow()),
+			ADAO_CURRENCY => ORACLE_ADAO_PRICE.with(|v| *v.borrow()),
 			_ => None,
 		}
 	}
 }
 impl DEXPriceProvider<CurrencyId> for MockPriceProvider {
 	fn get_relative_price(base: CurrencyId, quote: CurrencyId) -> Option<Price> {
-		if quote != AUSD_CURRENCY {
-			return None;
-		}
-
-		match base {
+		RELATIVE_PRICE_CALLS.with(|v| *v.borrow_mut() += 1);
+		LAST_RELATIVE_PRICE_QUERY.with(|v| *v.borrow_mut() = Some((base, quote)));
+		let ausd_price = |currency_id| match currency_id {
+			AUSD_CURRENCY => Some(Price::one()),
 			DOT_CURRENCY => DOT_PRICE.with(|v| *v.borrow()),
 			ADAO_CURRENCY => ADAO_PRICE.with(|v| *v.borrow()),
+			FOREIGN_CURRENCY | FOREIGN_18_DECIMALS_CURRENCY => Some(Price::one()),
+			_ => None,
+		};
+		ausd_price(base)?.checked_div(&ausd_price(quote)?)
+	}
+}
+
+/// Foreign asset `0` has 6 decimals, foreign asset `2` has 18, others are unknown.
+pub struct MockCurrencyDecimals;
+impl CurrencyDecimals for MockCurrencyDecimals {
+	fn decimals(currency_id: CurrencyId) -> Option<u8> {
+		match currency_id {
+			FOREIGN_CURRENCY => Some(6),
+			FOREIGN_18_DECIMALS_CURRENCY => Some(18),
 			_ => None,
 		}
 	}
 }
 
+/// Stable asset pool `0` token, with 10 decimals and priced at 2 AUSD.
+pub struct MockStableAssetValuation;
+impl StableAssetValuation for MockStableAssetValuation {
+	fn decimals(currency_id: CurrencyId) -> Option<u8> {
+		match currency_id {
+			POOL_TOKEN_CURRENCY => Some(10),
+			_ => None,
+		}
+	}
+
+	fn price(currency_id: CurrencyId, quote: CurrencyId) -> Option<Price> {
+		match (currency_id, quote) {
+			(POOL_TOKEN_CURRENCY, AUSD_CURRENCY) => Some(Price::saturating_from_integer(2)),
+			_ => None,
+		}
+	}
+}
+
+thread_local! {
+	static LIQUIDITY_POOL: RefCell<Option<(CurrencyId, CurrencyId, Balance, Balance)>> = RefCell::new(None);
+}
+
+/// A single DEX liquidity pool, empty by default.
+pub struct MockLiquidityPools;
+impl MockLiquidityPools {
+	pub fn set_pool(currency_id_a: CurrencyId, currency_id_b: CurrencyId, reserve_a: Balance, reserve_b: Balance) {
+		LIQUIDITY_POOL.with(|v| *v.borrow_mut() = Some((currency_id_a, currency_id_b, reserve_a, reserve_b)));
+	}
+}
+impl LiquidityPoolProvider for MockLiquidityPools {
+	fn get_liquidity_pool(currency_id_a: CurrencyId, currency_id_b: CurrencyId) -> (Balance, Balance) {
+		match LIQUIDITY_POOL.with(|v| *v.borrow()) {
+			Some((a, b, reserve_a, reserve_b)) if (a, b) == (currency_id_a, currency_id_b) => (reserve_a, reserve_b),
+			Some((a, b, reserve_a, reserve_b)) if (b, a) == (currency_id_a, currency_id_b) => (reserve_b, reserve_a),
+			_ => (0, 0),
+		}
+	}
+}
+
 thread_local! {
 	static MINT_INFO: RefCell<(Balance, BlockNumber)> = RefCell::new((0, 0));
+	static MINT_CALLS: RefCell<u32> = RefCell::new(0);
+	static LAST_MINT_ACCOUNT: RefCell<Option<AccountId>> = RefCell::new(None);
+	static CAN_MINT: RefCell<bool> = RefCell::new(true);
+	static FIXED_SHARE: RefCell<Ratio> = RefCell::new(Ratio::zero());
 }
 
 pub struct MockStakedToken;
@@ -176,16 +287,64 @@ impl MockStakedToken {
 	pub fn minted() -> (Balance, BlockNumber) {
 		MINT_INFO.with(|v| *v.borrow())
 	}
+
+	/// Number of `mint_for_subscription` calls.
+	pub fn mint_calls() -> u32 {
+		MINT_CALLS.with(|v| *v.borrow())
+	}
+
+	/// The account of the last `mint_for_subscription` call.
+	pub fn last_minted_to() -> Option<AccountId> {
+		LAST_MINT_ACCOUNT.with(|v| v.borrow().clone())
+	}
+
+	pub fn set_can_mint(can_mint: bool) {
+		CAN_MINT.with(|v| *v.borrow_mut() = can_mint);
+	}
+
+	/// Set the treasury and DAO share minted on top of subscriptions, zero by default.
+	pub fn set_fixed_share(share: Ratio) {
+		FIXED_SHARE.with(|v| *v.borrow_mut() = share);
+	}
+
+	pub fn assert_minted(who: &AccountId, subscription_amount: Balance, vesting_period: BlockNumber) {
+		assert_eq!(Self::last_minted_to().as_ref(), Some(who));
+		assert_eq!(Self::minted(), (subscription_amount, vesting_period));
+	}
 }
 impl StakedTokenManager<AccountId, BlockNumber> for MockStakedToken {
 	fn mint_for_subscription(
-		_who: &AccountId,
+		who: &AccountId,
 		subscription_amount: Balance,
 		vesting_period: BlockNumber,
 	) -> DispatchResult {
+		MINT_CALLS.with(|v| *v.borrow_mut() += 1);
+		LAST_MINT_ACCOUNT.with(|v| *v.borrow_mut() = Some(who.clone()));
 		MINT_INFO.with(|v| *v.borrow_mut() = (subscription_amount, vesting_period));
 		Ok(())
 	}
+
+	fn preview_mint_for_subscription(subscription_amount: Balance) -> Result<MintPreview, DispatchError> {
+		// mint = amount / (1 - fixed_share)
+		let mint = Ratio::one()
+			.saturating_sub(FIXED_SHARE.with(|v| *v.borrow()))
+			.reciprocal()
+			.ok_or(ArithmeticError::DivisionByZero)?
+			.saturating_mul_int(subscription_amount);
+		Ok(MintPreview {
+			mint,
+			staked: subscription_amount,
+			..Default::default()
+		})
+	}
+
+	fn can_mint_for(_who: &AccountId, _vesting_period: BlockNumber) -> bool {
+		CAN_MINT.with(|v| *v.borrow())
+	}
+
+	fn unstake_for(_who: &AccountId, amount: Balance) -> Result<Balance, DispatchError> {
+		Ok(amount)
+	}
 }
 
 thread_local! {
@@ -206,21 +365,109 @@ impl BlockNumberProvider for MockBlockNumberProvider {
 	}
 }
 
+thread_local! {
+	static PRICE_RECORD_INTERVAL: RefCell<BlockNumber> = RefCell::new(0);
+}
+
+/// ADAO price record interval, zero by default for the spot price.
+pub struct MockPriceRecordInterval;
+impl MockPriceRecordInterval {
+	pub fn set(interval: BlockNumber) {
+		PRICE_RECORD_INTERVAL.with(|v| *v.borrow_mut() = interval);
+	}
+}
+impl Get<BlockNumber> for MockPriceRecordInterval {
+	fn get() -> BlockNumber {
+		PRICE_RECORD_INTERVAL.with(|v| *v.borrow())
+	}
+}
+
+thread_local! {
+	static VALUATION_CURRENCY_ID: RefCell<CurrencyId> = RefCell::new(AUSD_CURRENCY);
+}
+
+pub struct MockValuationCurrencyId;
+impl MockValuationCurrencyId {
+	pub fn set(currency_id: CurrencyId) {
+		VALUATION_CURRENCY_ID.with(|v| *v.borrow_mut() = currency_id);
+	}
+}
+impl Get<CurrencyId> for MockValuationCurrencyId {
+	fn get() -> CurrencyId {
+		VALUATION_CURRENCY_ID.with(|v| *v.borrow())
+	}
+}
+
 parameter_types!(
 	pub const StableCurrencyId: CurrencyId = AUSD_CURRENCY;
 	pub AquaDaoPalletId: PalletId = PalletId(*b"aqua/dao");
+	pub const ProceedsDestination: AccountId = DAO_ACCOUNT;
+	pub const MaxIdleIntervals: u32 = 1_000;
+	pub const BlocksPerEra: BlockNumber = 100;
+	pub const SoldRetentionEras: u32 = 3;
+	pub const MaxBatchSize: u32 = 3;
+	pub const MaxPriceSamples: u32 = 10;
+	pub const MaxActiveSubscriptions: u32 = 4;
 );
 
+thread_local! {
+	static MAX_PRICE_DEVIATION: RefCell<Ratio> = RefCell::new(Ratio::zero());
+}
+
+/// Maximum ADAO price deviation from oracle, zero by default to skip the check.
+pub struct MockMaxPriceDeviation;
+impl MockMaxPriceDeviation {
+	pub fn set(ratio: Ratio) {
+		MAX_PRICE_DEVIATION.with(|v| *v.borrow_mut() = ratio);
+	}
+}
+impl Get<Ratio> for MockMaxPriceDeviation {
+	fn get() -> Ratio {
+		MAX_PRICE_DEVIATION.with(|v| *v.borrow())
+	}
+}
+
+/// Only `ALICE` is eligible to subscribe.
+pub struct MockSubscriberFilter;
+impl Contains<AccountId> for MockSubscriberFilter {
+	fn contains(who: &AccountId) -> bool {
+		*who == ALICE
+	}
+}
+
+/// Only `BOB` is exempted from subscription cooldown.
+pub struct MockCooldownExempt;
+impl Contains<AccountId> for MockCooldownExempt {
+	fn contains(who: &AccountId) -> bool {
+		*who == BOB
+	}
+}
+
 impl Config for Runtime {
 	type Event = Event;
 	type Currency = Currencies;
 	type StableCurrencyId = StableCurrencyId;
+	type ValuationCurrencyId = MockValuationCurrencyId;
 	type UpdateOrigin = EnsureRoot<AccountId>;
+	type SubscriberFilter = MockSubscriberFilter;
+	type CooldownExempt = MockCooldownExempt;
 	type AssetPriceProvider = MockPriceProvider;
 	type AdaoPriceProvider = MockPriceProvider;
+	type LiquidityPools = MockLiquidityPools;
+	type StableAssetValuation = MockStableAssetValuation;
+	type CurrencyDecimals = MockCurrencyDecimals;
 	type BlockNumberProvider = MockBlockNumberProvider;
 	type StakedToken = MockStakedToken;
 	type PalletId = AquaDaoPalletId;
+	type ProceedsDestination = ProceedsDestination;
+	type MaxIdleIntervals = MaxIdleIntervals;
+	type BlocksPerEra = BlocksPerEra;
+	type SoldRetentionEras = SoldRetentionEras;
+	type MaxBatchSize = MaxBatchSize;
+	type PriceRecordInterval = MockPriceRecordInterval;
+	type MaxPriceSamples = MaxPriceSamples;
+	type MaxPriceDeviation = MockMaxPriceDeviation;
+	type MaxActiveSubscriptions = MaxActiveSubscriptions;
 	type WeightInfo = ();
 }
 
@@ -237,17 +484,23 @@ frame_support::construct_runtime!(
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		Tokens: orml_tokens::{Pallet, Storage, Event<T>, Config<T>},
 		Currencies: module_currencies::{Pallet, Call, Event<T>},
-		AquaDao: aqua_dao::{Pallet, Call, Event<T>},
+		AquaDao: aqua_dao::{Pallet, Call, Config<T>, Storage, Event<T>},
 	}
 );
 
+type InitialSubscription = (CurrencyId, BlockNumber, Balance, Ratio, Balance, Discount<BlockNumber>);
+
 pub struct ExtBuilder {
 	balances: Vec<(AccountId, CurrencyId, Balance)>,
+	subscriptions: Vec<InitialSubscription>,
 }
 
 impl Default for ExtBuilder {
 	fn default() -> Self {
-		Self { balances: vec![] }
+		Self {
+			balances: vec![],
+			subscriptions: vec![],
+		}
 	}
 }
 
@@ -257,6 +510,11 @@ impl ExtBuilder {
 		self
 	}
 
+	pub fn subscriptions(mut self, subscriptions: Vec<InitialSubscription>) -> Self {
+		self.subscriptions = subscriptions;
+		self
+	}
+
 	pub fn build(self) -> sp_io::TestExternalities {
 		let mut t = frame_system::GenesisConfig::default()
 			.build_storage::<Runtime>()
@@ -268,6 +526,14 @@ impl ExtBuilder {
 		.assimilate_storage(&mut t)
 		.unwrap();
 
+		GenesisBuild::<Runtime>::assimilate_storage(
+			&aqua_dao::GenesisConfig::<Runtime> {
+				initial_subscriptions: self.subscriptions,
+			},
+			&mut t,
+		)
+		.unwrap();
+
 		t.into()
 	}
 }
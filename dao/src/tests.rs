@@ -43,10 +43,12 @@ fn create_default_subscription() -> DispatchResult {
 		dollar(CurrencyId::Token(ADAO)) * UNITS,
 		Discount {
 			max: DiscountRate::saturating_from_rational(2, 10),
+			min: DiscountRate::saturating_from_integer(-1),
 			interval: 1,
 			inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
 			dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
 		},
+		Default::default(),
 	)
 }
 
@@ -61,19 +63,32 @@ fn create_subscription_works() {
 			subscription: Subscription {
 				currency_id: AUSD_CURRENCY,
 				vesting_period: 1_000,
+				vesting_tiers: Default::default(),
 				min_amount: dollar(ADAO_CURRENCY) * 10,
 				min_ratio: Ratio::saturating_from_rational(1, 10),
 				amount: dollar(CurrencyId::Token(ADAO)) * UNITS,
+				denomination: AmountDenomination::Adao,
 				discount: Discount {
 					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
 					interval: 1,
 					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
 					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
 				},
+				bypass_filter: false,
+				status: SubscriptionStatus::Active,
+				start_block: 0,
+				end_block: None,
+				permissioned: false,
+				payment_dest: DAO_ACCOUNT,
+				per_account_cooldown: None,
 				state: SubscriptionState {
 					total_sold: Zero::zero(),
+					total_payment: Zero::zero(),
 					last_sold_at: 1,
 					last_discount: Zero::zero(),
+					sold_out: false,
+					discount_cap_reached: false,
 				},
 			},
 		}));
@@ -81,6 +96,92 @@ fn create_subscription_works() {
 	});
 }
 
+#[test]
+fn genesis_subscriptions_work() {
+	let discount = Discount {
+		max: DiscountRate::saturating_from_rational(2, 10),
+		min: DiscountRate::saturating_from_integer(-1),
+		interval: 1,
+		inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+		dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+	};
+	ExtBuilder::default()
+		.subscriptions(vec![
+			(
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(ADAO_CURRENCY) * UNITS,
+				discount,
+			),
+			(DOT_CURRENCY, 2_000, 0, Ratio::one(), dollar(ADAO_CURRENCY), discount),
+		])
+		.build()
+		.execute_with(|| {
+			assert_eq!(System::block_number(), 0);
+			assert_eq!(AquaDao::subscription_index(), 2);
+			assert_eq!(AquaDao::active_subscriptions().into_inner(), vec![0, 1]);
+			assert_eq!(
+				AquaDao::subscriptions(0),
+				Some(Subscription {
+					currency_id: AUSD_CURRENCY,
+					vesting_period: 1_000,
+					vesting_tiers: Default::default(),
+					min_amount: dollar(ADAO_CURRENCY) * 10,
+					min_ratio: Ratio::saturating_from_rational(1, 10),
+					amount: dollar(ADAO_CURRENCY) * UNITS,
+					denomination: AmountDenomination::Adao,
+					discount,
+					bypass_filter: false,
+					status: SubscriptionStatus::Active,
+					start_block: 0,
+					end_block: None,
+					permissioned: false,
+					payment_dest: DAO_ACCOUNT,
+					per_account_cooldown: None,
+					state: SubscriptionState {
+						total_sold: Zero::zero(),
+						total_payment: Zero::zero(),
+						// the `BlockNumberProvider` block at genesis
+						last_sold_at: 1,
+						last_discount: Zero::zero(),
+						sold_out: false,
+						discount_cap_reached: false,
+					},
+				})
+			);
+			let subscription = AquaDao::subscriptions(1).unwrap();
+			assert_eq!(subscription.currency_id, DOT_CURRENCY);
+			assert_eq!(subscription.vesting_period, 2_000);
+			assert_eq!(subscription.amount, dollar(ADAO_CURRENCY));
+
+			// created subscriptions follow the genesis ones
+			assert_ok!(create_default_subscription());
+			assert!(AquaDao::subscriptions(2).is_some());
+		});
+}
+
+#[test]
+#[should_panic(expected = "invalid initial subscription parameters")]
+fn genesis_subscriptions_fail_if_invalid_parameters() {
+	ExtBuilder::default()
+		.subscriptions(vec![(AUSD_CURRENCY, 1_000, 0, Ratio::zero(), dollar(ADAO_CURRENCY), Default::default())])
+		.build();
+}
+
+#[test]
+#[should_panic(expected = "unsupported initial subscription payment currency")]
+fn genesis_subscriptions_fail_if_unsupported_payment_currency() {
+	let discount = Discount {
+		interval: 1,
+		..Default::default()
+	};
+	ExtBuilder::default()
+		.subscriptions(vec![(ADAO_CURRENCY, 1_000, 0, Ratio::one(), dollar(ADAO_CURRENCY), discount)])
+		.build();
+}
+
 #[test]
 fn create_subscription_fails_if_not_required_origin() {
 	ExtBuilder::default().build().execute_with(|| {
@@ -94,16 +195,61 @@ fn create_subscription_fails_if_not_required_origin() {
 				dollar(CurrencyId::Token(ADAO)) * UNITS,
 				Discount {
 					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
 					interval: 1,
 					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
 					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
 				},
+				Default::default(),
 			),
 			BadOrigin
 		);
 	});
 }
 
+#[test]
+fn create_subscription_fails_if_unsupported_payment_currency() {
+	ExtBuilder::default().build().execute_with(|| {
+		let create = |currency_id: CurrencyId| {
+			AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				currency_id,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				Default::default(),
+				Default::default(),
+			)
+		};
+
+		for currency_id in [
+			ADAO_CURRENCY,
+			CurrencyId::Token(SDAO),
+			CurrencyId::DexShare(DexShare::Token(SDAO), DexShare::Token(DOT)),
+			CurrencyId::DexShare(DexShare::Token(ADAO), DexShare::Token(SDAO)),
+		] {
+			assert_noop!(create(currency_id), Error::<Runtime>::UnsupportedPaymentCurrency);
+		}
+
+		assert_ok!(create(DOT_CURRENCY));
+		assert_ok!(create(CurrencyId::DexShare(
+			DexShare::Token(AUSD),
+			DexShare::Token(DOT)
+		)));
+		// ADAO LP tokens are priced by the oracle ADAO price
+		assert_noop!(
+			create(CurrencyId::DexShare(DexShare::Token(AUSD), DexShare::Token(ADAO))),
+			Error::<Runtime>::NoPrice
+		);
+		MockPriceProvider::set_oracle_adao_price(Some(Price::one()));
+		assert_ok!(create(CurrencyId::DexShare(
+			DexShare::Token(AUSD),
+			DexShare::Token(ADAO)
+		)));
+	});
+}
+
 #[test]
 pub fn update_subscription_works() {
 	ExtBuilder::default().build().execute_with(|| {
@@ -112,6 +258,7 @@ pub fn update_subscription_works() {
 
 		let new_discount = Discount {
 			max: DiscountRate::one(),
+			min: DiscountRate::saturating_from_integer(-1),
 			interval: 1,
 			inc_on_idle: DiscountRate::one(),
 			dec_per_unit: DiscountRate::one(),
@@ -122,26 +269,130 @@ pub fn update_subscription_works() {
 			Some(1),
 			Some(1),
 			Some(Ratio::one()),
-			Some(0),
+			Some(1),
 			Some(new_discount),
+			SubscriptionUpdate {
+				denomination: Some(AmountDenomination::Stable),
+				bypass_filter: Some(true),
+				end_block: Some(Some(100)),
+				..Default::default()
+			},
 		));
 		assert_eq!(
 			AquaDao::subscriptions(0),
 			Some(Subscription {
 				currency_id: AUSD_CURRENCY,
 				vesting_period: 1,
+				vesting_tiers: Default::default(),
 				min_amount: 1,
 				min_ratio: Ratio::one(),
-				amount: 0,
+				amount: 1,
+				denomination: AmountDenomination::Stable,
 				discount: new_discount,
+				bypass_filter: true,
+				status: SubscriptionStatus::Active,
+				start_block: 0,
+				end_block: Some(100),
+				permissioned: false,
+				payment_dest: DAO_ACCOUNT,
+				per_account_cooldown: None,
 				state: SubscriptionState {
 					total_sold: 0,
+					total_payment: 0,
 					last_sold_at: 1,
 					last_discount: Zero::zero(),
+					sold_out: false,
+					discount_cap_reached: false,
 				},
 			})
 		);
-		System::assert_has_event(Event::AquaDao(crate::Event::SubscriptionUpdated { id: 0 }));
+		System::assert_has_event(Event::AquaDao(crate::Event::SubscriptionUpdated {
+			id: 0,
+			state: AquaDao::subscriptions(0).unwrap().state,
+		}));
+	});
+}
+
+#[test]
+fn update_subscription_discount_resets_idle_period() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(create_default_subscription());
+		let new_discount = Discount {
+			max: DiscountRate::saturating_from_rational(5, 10),
+			min: DiscountRate::zero(),
+			interval: 1,
+			inc_on_idle: DiscountRate::saturating_from_rational(1, 10_000),
+			dec_per_unit: DiscountRate::zero(),
+		};
+
+		// 1_000 idle blocks would accrue 0.1 discount with new parameters
+		MockBlockNumberProvider::set_block_number(1_001);
+		assert_eq!(
+			AquaDao::discount_at(0, 1_001),
+			Some(DiscountRate::saturating_from_rational(2, 10))
+		);
+		assert_ok!(AquaDao::update_subscription(
+			RawOrigin::Root.into(),
+			0,
+			None,
+			None,
+			None,
+			None,
+			Some(new_discount),
+			Default::default(),
+		));
+		let state = AquaDao::subscriptions(0).unwrap().state;
+		assert_eq!(state.last_sold_at, 1_001);
+		assert_eq!(state.last_discount, DiscountRate::zero());
+		System::assert_last_event(Event::AquaDao(crate::Event::SubscriptionUpdated { id: 0, state }));
+		assert_eq!(AquaDao::discount_at(0, 1_001), Some(DiscountRate::zero()));
+		assert_eq!(
+			AquaDao::discount_at(0, 1_101),
+			Some(DiscountRate::saturating_from_rational(1, 100))
+		);
+	});
+}
+
+#[test]
+fn update_subscription_discount_clamps_accumulated_discount() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(create_default_subscription());
+
+		// as if subscribed at block 1_001 with the old max discount 0.2
+		Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
+			let subscription = maybe_subscription.as_mut().unwrap();
+			subscription.state.last_discount = DiscountRate::saturating_from_rational(2, 10);
+			subscription.state.last_sold_at = 1_001;
+		});
+
+		let new_discount = Discount {
+			max: DiscountRate::saturating_from_rational(5, 100),
+			min: DiscountRate::zero(),
+			interval: 1,
+			inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+			dec_per_unit: DiscountRate::zero(),
+		};
+		MockBlockNumberProvider::set_block_number(2_001);
+		assert_ok!(AquaDao::update_subscription(
+			RawOrigin::Root.into(),
+			0,
+			None,
+			None,
+			None,
+			None,
+			Some(new_discount),
+			Default::default(),
+		));
+		let state = AquaDao::subscriptions(0).unwrap().state;
+		assert_eq!(state.last_discount, DiscountRate::saturating_from_rational(5, 100));
+		assert_eq!(state.last_sold_at, 2_001);
+		System::assert_last_event(Event::AquaDao(crate::Event::SubscriptionUpdated { id: 0, state }));
+		assert_eq!(
+			AquaDao::discount_at(0, 2_001),
+			Some(DiscountRate::saturating_from_rational(5, 100))
+		);
 	});
 }
 
@@ -150,12 +401,99 @@ fn update_subscription_fails_if_not_required_origin() {
 	ExtBuilder::default().build().execute_with(|| {
 		assert_ok!(create_default_subscription());
 		assert_noop!(
-			AquaDao::update_subscription(RawOrigin::Signed(ALICE).into(), 0, Some(1), None, None, None, None),
+			AquaDao::update_subscription(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				Some(1),
+				None,
+				None,
+				None,
+				None,
+				Default::default(),
+			),
 			BadOrigin
 		);
 	});
 }
 
+#[test]
+fn create_subscription_fails_if_invalid_discount_parameters() {
+	ExtBuilder::default().build().execute_with(|| {
+		let create = |min_ratio: Ratio, amount: Balance, interval: BlockNumber| {
+			AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				min_ratio,
+				amount,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				Default::default(),
+			)
+		};
+		let min_ratio = Ratio::saturating_from_rational(1, 10);
+		let amount = dollar(ADAO_CURRENCY) * UNITS;
+
+		assert_noop!(
+			create(min_ratio, amount, 0),
+			Error::<Runtime>::InvalidDiscountParameters
+		);
+		assert_noop!(
+			create(Ratio::zero(), amount, 1),
+			Error::<Runtime>::InvalidDiscountParameters
+		);
+		assert_noop!(
+			create(min_ratio, 0, 1),
+			Error::<Runtime>::InvalidDiscountParameters
+		);
+		assert_ok!(create(min_ratio, amount, 1));
+	});
+}
+
+#[test]
+fn update_subscription_fails_if_invalid_discount_parameters() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_default_subscription());
+		let subscription = AquaDao::subscriptions(0).unwrap();
+		let update = |min_ratio: Option<Ratio>, amount: Option<Balance>, discount: Option<Discount<BlockNumber>>| {
+			AquaDao::update_subscription(
+				RawOrigin::Root.into(),
+				0,
+				None,
+				None,
+				min_ratio,
+				amount,
+				discount,
+				Default::default(),
+			)
+		};
+
+		let zero_interval = Discount {
+			interval: 0,
+			..subscription.discount
+		};
+		assert_noop!(
+			update(None, None, Some(zero_interval)),
+			Error::<Runtime>::InvalidDiscountParameters
+		);
+		assert_noop!(
+			update(Some(Ratio::zero()), None, None),
+			Error::<Runtime>::InvalidDiscountParameters
+		);
+		assert_noop!(
+			update(None, Some(0), None),
+			Error::<Runtime>::InvalidDiscountParameters
+		);
+		assert_eq!(AquaDao::subscriptions(0), Some(subscription));
+	});
+}
+
 #[test]
 fn close_subscription_works() {
 	ExtBuilder::default().build().execute_with(|| {
@@ -163,196 +501,2654 @@ fn close_subscription_works() {
 
 		assert_ok!(create_default_subscription());
 		assert_ok!(AquaDao::close_subscription(RawOrigin::Root.into(), 0));
-		System::assert_has_event(Event::AquaDao(crate::Event::SubscriptionClosed { id: 0 }));
+		System::assert_has_event(Event::AquaDao(crate::Event::SubscriptionClosed {
+			id: 0,
+			currency_id: AUSD_CURRENCY,
+			total_sold: 0,
+			total_payment: 0,
+			remaining: dollar(ADAO_CURRENCY) * UNITS,
+		}));
 
 		assert_eq!(AquaDao::subscriptions(0), None);
 	});
 }
 
 #[test]
-fn close_subscription_fails_if_not_required_origin() {
+fn closed_subscription_id_is_not_reused() {
 	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+
 		assert_ok!(create_default_subscription());
-		assert_noop!(
-			AquaDao::close_subscription(RawOrigin::Signed(ALICE).into(), 0),
-			BadOrigin
-		);
+		assert_ok!(AquaDao::close_subscription(RawOrigin::Root.into(), 0));
+		assert_ok!(create_default_subscription());
+		assert_eq!(AquaDao::subscriptions(0), None);
+		assert!(AquaDao::subscriptions(1).is_some());
+		assert_eq!(AquaDao::subscription_index(), 2);
 	});
 }
 
 #[test]
-fn subscribe_works() {
-	ExtBuilder::default()
-		.balances(vec![(
-			AccountId::from(ALICE),
-			AUSD_CURRENCY,
-			2_000_000 * dollar(AUSD_CURRENCY),
-		)])
-		.build()
-		.execute_with(|| {
-			System::set_block_number(1);
+fn active_subscriptions_are_indexed() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
 
+		for _ in 0..4 {
 			assert_ok!(create_default_subscription());
-			Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
-				if let Some(subscription) = maybe_subscription {
-					subscription.state.last_discount = FixedI128::saturating_from_rational(5, 100);
-				}
-			});
+		}
+		assert_eq!(AquaDao::active_subscriptions().into_inner(), vec![0, 1, 2, 3]);
+		assert_noop!(
+			create_default_subscription(),
+			Error::<Runtime>::TooManyActiveSubscriptions
+		);
 
-			let payment_amount = dollar(AUSD_CURRENCY) * 100;
-			assert_ok!(AquaDao::subscribe(
-				RawOrigin::Signed(ALICE).into(),
-				0,
-				payment_amount,
-				0
-			));
+		// closed out of order
+		assert_ok!(AquaDao::close_subscription(RawOrigin::Root.into(), 2));
+		assert_ok!(AquaDao::close_subscription(RawOrigin::Root.into(), 0));
+		assert_eq!(AquaDao::active_subscriptions().into_inner(), vec![1, 3]);
 
-			let new_subscription = AquaDao::subscriptions(0).unwrap();
-			assert_eq!(new_subscription.state.total_sold, 105_260_000_000_000);
-			assert_eq!(new_subscription.state.last_sold_at, 1);
-			assert_eq!(
-				new_subscription.state.last_discount,
-				DiscountRate::saturating_from_rational(5, 100)
-			);
-			assert_eq!(
-				Currencies::free_balance(AUSD_CURRENCY, &ALICE),
-				1_999_900 * dollar(AUSD_CURRENCY)
-			);
-			assert_eq!(MockStakedToken::minted(), (105_260_000_000_000, 1_000));
+		assert_ok!(create_default_subscription());
+		assert_eq!(AquaDao::active_subscriptions().into_inner(), vec![1, 3, 4]);
 
-			System::assert_has_event(Event::AquaDao(crate::Event::Subscribed {
-				who: ALICE,
-				subscription_id: 0,
-				payment_amount,
-				subscription_amount: 105_260_000_000_000,
-			}));
-		});
+		// removed on auto-close
+		assert_ok!(AquaDao::update_subscription(
+			RawOrigin::Root.into(),
+			3,
+			None,
+			None,
+			None,
+			None,
+			None,
+			SubscriptionUpdate {
+				end_block: Some(Some(10)),
+				..Default::default()
+			},
+		));
+		MockBlockNumberProvider::set_block_number(11);
+		AquaDao::on_initialize(11);
+		assert_eq!(AquaDao::subscriptions(3), None);
+		assert_eq!(AquaDao::active_subscriptions().into_inner(), vec![1, 4]);
+	});
 }
 
 #[test]
-fn no_discount_increase_on_subscribe_within_interval() {
+fn sold_out_subscription_leaves_active_subscriptions() {
 	ExtBuilder::default()
 		.balances(vec![(
 			AccountId::from(ALICE),
 			AUSD_CURRENCY,
-			2_000_000 * dollar(AUSD_CURRENCY),
+			1_000 * dollar(AUSD_CURRENCY),
 		)])
 		.build()
 		.execute_with(|| {
 			System::set_block_number(1);
 
+			// capped at 100 ADAO
 			assert_ok!(AquaDao::create_subscription(
 				RawOrigin::Root.into(),
 				AUSD_CURRENCY,
 				1_000,
 				dollar(ADAO_CURRENCY) * 10,
 				Ratio::saturating_from_rational(1, 10),
-				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				dollar(ADAO_CURRENCY) * 100,
 				Discount {
-					max: DiscountRate::saturating_from_rational(1, 2),
-					interval: 1_000,
-					inc_on_idle: DiscountRate::saturating_from_rational(1, 2),
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
 					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
 				},
+				Default::default(),
 			));
+			assert_ok!(create_default_subscription());
+			assert_eq!(AquaDao::active_subscriptions().into_inner(), vec![0, 1]);
 
-			let payment_amount = dollar(AUSD_CURRENCY) * 100;
-			assert_ok!(AquaDao::subscribe(
-				RawOrigin::Signed(ALICE).into(),
-				0,
-				payment_amount,
-				0
-			));
-			System::assert_last_event(Event::AquaDao(crate::Event::Subscribed {
-				who: ALICE,
-				subscription_id: 0,
-				payment_amount,
-				subscription_amount: 99_995_000_000_000,
-			}));
-
-			// no discount on new subscription within interval
-			MockBlockNumberProvider::set_block_number(998);
+			// ADAO price: $0.01, subscribed amount is clamped to 10 ADAO per $1 by `min_ratio`
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_rational(1, 100)));
 			assert_ok!(AquaDao::subscribe(
 				RawOrigin::Signed(ALICE).into(),
 				0,
-				payment_amount,
-				0
-			));
-			System::assert_last_event(Event::AquaDao(crate::Event::Subscribed {
-				who: ALICE,
-				subscription_id: 0,
-				payment_amount,
-				subscription_amount: 99_995_000_000_000,
-			}));
-
-			// discount increases
-			MockBlockNumberProvider::set_block_number(2000);
-			assert_ok!(AquaDao::subscribe(
-				RawOrigin::Signed(ALICE).into(),
+				dollar(AUSD_CURRENCY) * 10,
 				0,
-				payment_amount,
-				0
+				None
 			));
-			System::assert_last_event(Event::AquaDao(crate::Event::Subscribed {
-				who: ALICE,
-				subscription_id: 0,
-				payment_amount,
-				subscription_amount: 199_965_000_000_000,
-			}));
+			assert_eq!(AquaDao::subscriptions(0), None);
+			assert_eq!(AquaDao::active_subscriptions().into_inner(), vec![1]);
 		});
 }
 
 #[test]
-fn subscribe_with_below_min_ratio_works() {
+fn pause_and_resume_subscription_works() {
 	ExtBuilder::default()
 		.balances(vec![(
 			AccountId::from(ALICE),
 			AUSD_CURRENCY,
-			2_000_000 * dollar(AUSD_CURRENCY),
+			1_000 * dollar(AUSD_CURRENCY),
 		)])
 		.build()
 		.execute_with(|| {
 			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+			assert_noop!(
+				AquaDao::resume_subscription(RawOrigin::Root.into(), 0),
+				Error::<Runtime>::SubscriptionNotPaused
+			);
+			assert_noop!(
+				AquaDao::pause_subscription(RawOrigin::Signed(ALICE).into(), 0),
+				BadOrigin
+			);
 
-			// min_ratio is 1
-			assert_ok!(AquaDao::create_subscription(
-				RawOrigin::Root.into(),
-				AUSD_CURRENCY,
-				1_000,
-				dollar(ADAO_CURRENCY) * 10,
-				Ratio::one(),
-				dollar(CurrencyId::Token(ADAO)) * UNITS,
-				Discount {
-					max: DiscountRate::saturating_from_rational(2, 10),
-					interval: 1,
-					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
-					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+			MockBlockNumberProvider::set_block_number(11);
+			assert_ok!(AquaDao::pause_subscription(RawOrigin::Root.into(), 0));
+			System::assert_last_event(Event::AquaDao(crate::Event::SubscriptionPaused { id: 0 }));
+			assert_eq!(
+				AquaDao::subscriptions(0).unwrap().status,
+				SubscriptionStatus::Paused { since: 11 }
+			);
+			assert_noop!(
+				AquaDao::pause_subscription(RawOrigin::Root.into(), 0),
+				Error::<Runtime>::SubscriptionPaused
+			);
+			assert_noop!(
+				AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					dollar(AUSD_CURRENCY) * 100,
+					0,
+					None
+				),
+				Error::<Runtime>::SubscriptionPaused
+			);
+
+			// the paused 100 blocks don't count as idle
+			MockBlockNumberProvider::set_block_number(111);
+			assert_ok!(AquaDao::resume_subscription(RawOrigin::Root.into(), 0));
+			System::assert_last_event(Event::AquaDao(crate::Event::SubscriptionResumed { id: 0 }));
+			let subscription = AquaDao::subscriptions(0).unwrap();
+			assert_eq!(subscription.status, SubscriptionStatus::Active);
+			assert_eq!(subscription.state.last_sold_at, 101);
+
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 100,
+				0,
+				None
+			));
+		});
+}
+
+#[test]
+fn pause_all_blocks_every_subscription() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+			assert_ok!(create_default_subscription());
+			assert_ok!(create_default_subscription());
+			assert_ok!(AquaDao::pause_subscription(RawOrigin::Root.into(), 2));
+			let subscription = |id| AquaDao::subscriptions(id).unwrap();
+			let subscriptions = (subscription(0), subscription(1), subscription(2));
+
+			assert_noop!(AquaDao::pause_all(RawOrigin::Signed(ALICE).into()), BadOrigin);
+			assert_ok!(AquaDao::pause_all(RawOrigin::Root.into()));
+			System::assert_last_event(Event::AquaDao(crate::Event::AllSubscriptionsPaused));
+			assert!(AquaDao::all_subscriptions_paused());
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			for id in [0, 1, 2] {
+				assert_noop!(
+					AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), id, payment_amount, 0, None),
+					Error::<Runtime>::SubscriptionsPaused
+				);
+			}
+			assert_noop!(
+				AquaDao::subscribe_for(RawOrigin::Signed(BOB).into(), ALICE, 0, payment_amount, 0, None),
+				Error::<Runtime>::SubscriptionsPaused
+			);
+			assert_noop!(
+				AquaDao::subscribe_batch(
+					RawOrigin::Signed(ALICE).into(),
+					BoundedVec::try_from(vec![(1, payment_amount, 0)]).unwrap()
+				),
+				Error::<Runtime>::SubscriptionsPaused
+			);
+
+			// cleanup is still allowed
+			assert_ok!(create_default_subscription());
+			assert_ok!(AquaDao::close_subscription(RawOrigin::Root.into(), 3));
+
+			assert_noop!(AquaDao::resume_all(RawOrigin::Signed(ALICE).into()), BadOrigin);
+			assert_ok!(AquaDao::resume_all(RawOrigin::Root.into()));
+			System::assert_last_event(Event::AquaDao(crate::Event::AllSubscriptionsResumed));
+			assert!(!AquaDao::all_subscriptions_paused());
+
+			// per-subscription state untouched
+			assert_eq!((subscription(0), subscription(1), subscription(2)), subscriptions);
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			assert_noop!(
+				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 2, payment_amount, 0, None),
+				Error::<Runtime>::SubscriptionPaused
+			);
+		});
+}
+
+#[test]
+fn mint_cap_limits_subscription_mints() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+			MockStakedToken::set_fixed_share(Ratio::saturating_from_rational(1, 10));
+			let subscribe =
+				|payment_amount| AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, payment_amount, 0, None);
+
+			// the cap applies to the mint grossed up by the treasury and DAO share
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			let (amount, _, preview) = AquaDao::quote_subscription(0, payment_amount).unwrap();
+			assert!(preview.mint > amount);
+
+			assert_noop!(
+				AquaDao::set_mint_cap(RawOrigin::Signed(ALICE).into(), Some(preview.mint)),
+				BadOrigin
+			);
+			assert_ok!(AquaDao::set_mint_cap(RawOrigin::Root.into(), Some(preview.mint - 1)));
+			System::assert_last_event(Event::AquaDao(crate::Event::MintCapSet {
+				cap: Some(preview.mint - 1),
+			}));
+			assert_noop!(subscribe(payment_amount), Error::<Runtime>::MintCapExceeded);
+
+			assert_ok!(AquaDao::set_mint_cap(RawOrigin::Root.into(), Some(preview.mint)));
+			assert_ok!(subscribe(payment_amount));
+			assert_eq!(AquaDao::total_subscription_minted(), preview.mint);
+
+			// cap reached
+			let payment_amount = dollar(AUSD_CURRENCY);
+			let (_, _, next_preview) = AquaDao::quote_subscription(0, payment_amount).unwrap();
+			assert_noop!(subscribe(payment_amount), Error::<Runtime>::MintCapExceeded);
+
+			// no cap
+			assert_ok!(AquaDao::set_mint_cap(RawOrigin::Root.into(), None));
+			System::assert_last_event(Event::AquaDao(crate::Event::MintCapSet { cap: None }));
+			assert_ok!(subscribe(payment_amount));
+			assert_eq!(
+				AquaDao::total_subscription_minted(),
+				preview.mint + next_preview.mint
+			);
+		});
+}
+
+#[test]
+fn subscription_expires_after_end_block() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+			assert_ok!(AquaDao::update_subscription(
+				RawOrigin::Root.into(),
+				0,
+				None,
+				None,
+				None,
+				None,
+				None,
+				SubscriptionUpdate {
+					end_block: Some(Some(10)),
+					..Default::default()
+				},
+			));
+			let subscribe = || {
+				AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					dollar(AUSD_CURRENCY) * 100,
+					0,
+					None,
+				)
+			};
+
+			// partially sold, and still open at the end block
+			assert_ok!(subscribe());
+			MockBlockNumberProvider::set_block_number(10);
+			AquaDao::on_initialize(10);
+			assert_ok!(subscribe());
+			assert!(AquaDao::subscriptions(0).unwrap().state.total_sold > 0);
+
+			MockBlockNumberProvider::set_block_number(11);
+			assert_noop!(subscribe(), Error::<Runtime>::SubscriptionExpired);
+
+			let total_sold = AquaDao::subscriptions(0).unwrap().state.total_sold;
+			AquaDao::on_initialize(11);
+			System::assert_last_event(Event::AquaDao(crate::Event::SubscriptionClosed {
+				id: 0,
+				currency_id: AUSD_CURRENCY,
+				total_sold,
+				total_payment: dollar(AUSD_CURRENCY) * 200,
+				remaining: dollar(ADAO_CURRENCY) * UNITS - total_sold,
+			}));
+			assert_eq!(AquaDao::subscriptions(0), None);
+		});
+}
+
+#[test]
+fn subscription_uses_time_weighted_adao_price() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			MockPriceRecordInterval::set(10);
+			assert_ok!(create_default_subscription());
+
+			// spot price before any sample
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_integer(2)));
+			assert_eq!(AquaDao::adao_price(), Ok(Price::saturating_from_integer(2)));
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::one()));
+
+			// recorded on interval blocks only, no more than `MaxPriceSamples`
+			for block in 1..=120 {
+				MockBlockNumberProvider::set_block_number(block);
+				AquaDao::on_initialize(block);
+			}
+			assert_eq!(AquaDao::adao_price_samples().len(), 10);
+			assert_eq!(AquaDao::adao_price(), Ok(Price::one()));
+
+			// a spot price spike within the block doesn't move the subscription price
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_integer(10)));
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 100,
+				0,
+				None
+			));
+			assert!(System::events().iter().any(|record| matches!(
+				record.event,
+				Event::AquaDao(crate::Event::Subscribed { adao_price, .. }) if adao_price == Price::one()
+			)));
+
+			// recorded as one sample, the spike is averaged out, and the oldest sample dropped
+			MockBlockNumberProvider::set_block_number(130);
+			AquaDao::on_initialize(130);
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::one()));
+			assert_eq!(AquaDao::adao_price_samples().len(), 10);
+			assert_eq!(AquaDao::adao_price(), Ok(Price::saturating_from_rational(19, 10)));
+
+			// no sample recorded without a price
+			MockPriceProvider::set_price(ADAO_CURRENCY, None);
+			MockBlockNumberProvider::set_block_number(140);
+			AquaDao::on_initialize(140);
+			assert_eq!(AquaDao::adao_price(), Ok(Price::saturating_from_rational(19, 10)));
+		});
+}
+
+#[test]
+fn subscribe_fails_if_price_deviates_from_oracle() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+			MockMaxPriceDeviation::set(Ratio::saturating_from_rational(1, 10));
+			let subscribe = || {
+				AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					dollar(AUSD_CURRENCY) * 10,
+					0,
+					None,
+				)
+			};
+
+			// no oracle price, check skipped
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_integer(2)));
+			assert_ok!(subscribe());
+
+			MockPriceProvider::set_oracle_adao_price(Some(Price::one()));
+			// DEX price in percent of the oracle price, deviating up and down
+			for (percent, ok) in [(105, true), (110, true), (111, false), (95, true), (89, false)] {
+				MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_rational(percent, 100)));
+				if ok {
+					assert_ok!(subscribe());
+				} else {
+					assert_noop!(subscribe(), Error::<Runtime>::PriceOutOfBounds);
+				}
+			}
+
+			// disabled with zero deviation limit
+			MockMaxPriceDeviation::set(Ratio::zero());
+			assert_ok!(subscribe());
+		});
+}
+
+#[test]
+fn permissioned_subscription_requires_whitelist() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+			assert_ok!(AquaDao::update_subscription(
+				RawOrigin::Root.into(),
+				0,
+				None,
+				None,
+				None,
+				None,
+				None,
+				SubscriptionUpdate {
+					permissioned: Some(true),
+					..Default::default()
+				},
+			));
+			let subscribe = || {
+				AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					dollar(AUSD_CURRENCY) * 100,
+					0,
+					None,
+				)
+			};
+			assert_noop!(subscribe(), Error::<Runtime>::NotWhitelisted);
+
+			assert_noop!(
+				AquaDao::add_to_whitelist(RawOrigin::Signed(ALICE).into(), 0, vec![ALICE]),
+				BadOrigin
+			);
+			assert_noop!(
+				AquaDao::add_to_whitelist(RawOrigin::Root.into(), 1, vec![ALICE]),
+				Error::<Runtime>::SubscriptionNotFound
+			);
+			assert_ok!(AquaDao::add_to_whitelist(RawOrigin::Root.into(), 0, vec![ALICE]));
+			System::assert_last_event(Event::AquaDao(crate::Event::WhitelistAdded {
+				subscription_id: 0,
+				accounts: vec![ALICE],
+			}));
+			assert_ok!(subscribe());
+
+			assert_ok!(AquaDao::remove_from_whitelist(RawOrigin::Root.into(), 0, vec![ALICE]));
+			System::assert_last_event(Event::AquaDao(crate::Event::WhitelistRemoved {
+				subscription_id: 0,
+				accounts: vec![ALICE],
+			}));
+			assert_noop!(subscribe(), Error::<Runtime>::NotWhitelisted);
+
+			// whitelist is removed on close
+			assert_ok!(AquaDao::add_to_whitelist(RawOrigin::Root.into(), 0, vec![ALICE]));
+			assert_ok!(AquaDao::close_subscription(RawOrigin::Root.into(), 0));
+			assert!(!SubscriptionWhitelist::<Runtime>::contains_key(0, ALICE));
+		});
+}
+
+#[test]
+fn close_subscription_fails_if_not_required_origin() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_default_subscription());
+		assert_noop!(
+			AquaDao::close_subscription(RawOrigin::Signed(ALICE).into(), 0),
+			BadOrigin
+		);
+	});
+}
+
+#[test]
+fn subscribe_works() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(create_default_subscription());
+			Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
+				if let Some(subscription) = maybe_subscription {
+					subscription.state.last_discount = FixedI128::saturating_from_rational(5, 100);
+				}
+			});
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+
+			let new_subscription = AquaDao::subscriptions(0).unwrap();
+			assert_eq!(new_subscription.state.total_sold, 105_260_000_000_000);
+			assert_eq!(new_subscription.state.last_sold_at, 1);
+			assert_eq!(
+				new_subscription.state.last_discount,
+				DiscountRate::saturating_from_rational(5, 100)
+			);
+			assert_eq!(
+				Currencies::free_balance(AUSD_CURRENCY, &ALICE),
+				1_999_900 * dollar(AUSD_CURRENCY)
+			);
+			assert_eq!(Currencies::free_balance(AUSD_CURRENCY, &DAO_ACCOUNT), payment_amount);
+			MockStakedToken::assert_minted(&ALICE, 105_260_000_000_000, 1_000);
+			assert_eq!(MockStakedToken::mint_calls(), 1);
+			assert_eq!(
+				MockPriceProvider::last_relative_price_query(),
+				Some((ADAO_CURRENCY, AUSD_CURRENCY))
+			);
+
+			System::assert_has_event(Event::AquaDao(crate::Event::Subscribed {
+				who: ALICE,
+				beneficiary: ALICE,
+				payment_dest: DAO_ACCOUNT,
+				subscription_id: 0,
+				payment_amount,
+				subscription_amount: 105_260_000_000_000,
+				remaining_after: dollar(ADAO_CURRENCY) * UNITS - 105_260_000_000_000,
+				total_payment: payment_amount,
+				adao_price: Price::one(),
+				payment_price: Price::one(),
+			}));
+		});
+}
+
+#[test]
+fn no_discount_increase_on_subscribe_within_interval() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				Discount {
+					max: DiscountRate::saturating_from_rational(1, 2),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1_000,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 2),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				Default::default(),
+			));
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			System::assert_last_event(Event::AquaDao(crate::Event::Subscribed {
+				who: ALICE,
+				beneficiary: ALICE,
+				payment_dest: DAO_ACCOUNT,
+				subscription_id: 0,
+				payment_amount,
+				subscription_amount: 99_995_000_000_000,
+				remaining_after: dollar(ADAO_CURRENCY) * UNITS - 99_995_000_000_000,
+				total_payment: payment_amount,
+				adao_price: Price::one(),
+				payment_price: Price::one(),
+			}));
+
+			// no discount on new subscription within interval
+			MockBlockNumberProvider::set_block_number(998);
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			System::assert_last_event(Event::AquaDao(crate::Event::Subscribed {
+				who: ALICE,
+				beneficiary: ALICE,
+				payment_dest: DAO_ACCOUNT,
+				subscription_id: 0,
+				payment_amount,
+				subscription_amount: 99_995_000_000_000,
+				remaining_after: dollar(ADAO_CURRENCY) * UNITS - 2 * 99_995_000_000_000,
+				total_payment: 2 * payment_amount,
+				adao_price: Price::one(),
+				payment_price: Price::one(),
+			}));
+
+			// discount increases
+			MockBlockNumberProvider::set_block_number(2000);
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			System::assert_last_event(Event::AquaDao(crate::Event::Subscribed {
+				who: ALICE,
+				beneficiary: ALICE,
+				payment_dest: DAO_ACCOUNT,
+				subscription_id: 0,
+				payment_amount,
+				subscription_amount: 199_965_000_000_000,
+				remaining_after: dollar(ADAO_CURRENCY) * UNITS - 2 * 99_995_000_000_000 - 199_965_000_000_000,
+				total_payment: 3 * payment_amount,
+				adao_price: Price::one(),
+				payment_price: Price::one(),
+			}));
+		});
+}
+
+#[test]
+fn subscribe_with_dex_share_payment_works() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), ADAO_AUSD_LP, 100 * dollar(ADAO_AUSD_LP)),
+			(AccountId::from(ALICE), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			MockPriceProvider::set_oracle_adao_price(Some(Price::one()));
+			let create = |currency_id: CurrencyId| {
+				AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					currency_id,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 100),
+					dollar(ADAO_CURRENCY) * UNITS,
+					Discount {
+						max: DiscountRate::saturating_from_rational(2, 10),
+						min: DiscountRate::saturating_from_integer(-1),
+						interval: 1,
+						inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+						dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+					},
+					Default::default(),
+				)
+			};
+			assert_ok!(create(ADAO_AUSD_LP));
+			assert_ok!(create(AUSD_CURRENCY));
+
+			// $2,000 in the pool for 100 LP, each LP is worth $20
+			MockLiquidityPools::set_pool(
+				ADAO_CURRENCY,
+				AUSD_CURRENCY,
+				1_000 * dollar(ADAO_CURRENCY),
+				1_000 * dollar(AUSD_CURRENCY),
+			);
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(ADAO_AUSD_LP),
+				0,
+				None
+			));
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				1,
+				dollar(AUSD_CURRENCY) * 20,
+				0,
+				None
+			));
+
+			let subscription_amount = AquaDao::subscriptions(0).unwrap().state.total_sold;
+			assert!(subscription_amount > 0);
+			assert_eq!(AquaDao::subscriptions(1).unwrap().state.total_sold, subscription_amount);
+			assert_eq!(
+				Currencies::free_balance(ADAO_AUSD_LP, &DAO_ACCOUNT),
+				dollar(ADAO_AUSD_LP)
+			);
+			System::assert_has_event(Event::AquaDao(crate::Event::Subscribed {
+				who: ALICE,
+				beneficiary: ALICE,
+				payment_dest: DAO_ACCOUNT,
+				subscription_id: 0,
+				payment_amount: dollar(ADAO_AUSD_LP),
+				subscription_amount,
+				remaining_after: dollar(ADAO_CURRENCY) * UNITS - subscription_amount,
+				total_payment: dollar(ADAO_AUSD_LP),
+				adao_price: Price::one(),
+				payment_price: Price::saturating_from_integer(20),
+			}));
+		});
+}
+
+#[test]
+fn dex_share_price_ignores_pool_spot_price() {
+	ExtBuilder::default()
+		.balances(vec![(AccountId::from(ALICE), ADAO_AUSD_LP, 100 * dollar(ADAO_AUSD_LP))])
+		.build()
+		.execute_with(|| {
+			MockPriceProvider::set_oracle_adao_price(Some(Price::one()));
+			let lp_price = || AquaDao::dex_share_price(ADAO_AUSD_LP, ADAO_CURRENCY, AUSD_CURRENCY);
+
+			// $2,000 in the pool for 100 LP, each LP is worth $20
+			MockLiquidityPools::set_pool(
+				ADAO_CURRENCY,
+				AUSD_CURRENCY,
+				1_000 * dollar(ADAO_CURRENCY),
+				1_000 * dollar(AUSD_CURRENCY),
+			);
+			assert_eq!(lp_price(), Ok(Price::saturating_from_integer(20)));
+
+			// swapped within the pool, and the DEX ADAO price moved along, reserves invariant holds
+			MockLiquidityPools::set_pool(
+				ADAO_CURRENCY,
+				AUSD_CURRENCY,
+				4_000 * dollar(ADAO_CURRENCY),
+				250 * dollar(AUSD_CURRENCY),
+			);
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_rational(1, 16)));
+			assert_eq!(lp_price(), Ok(Price::saturating_from_integer(20)));
+
+			// no oracle ADAO price
+			MockPriceProvider::set_oracle_adao_price(None);
+			assert_eq!(lp_price(), Err(Error::<Runtime>::NoPrice.into()));
+		});
+}
+
+#[test]
+fn create_subscription_fails_if_no_payment_price() {
+	ExtBuilder::default().build().execute_with(|| {
+		MockPriceProvider::set_price(DOT_CURRENCY, None);
+		for currency_id in [
+			DOT_CURRENCY,
+			CurrencyId::DexShare(DexShare::Token(AUSD), DexShare::Token(DOT)),
+		] {
+			assert_noop!(
+				AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					currency_id,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 10),
+					dollar(ADAO_CURRENCY) * UNITS,
+					Default::default(),
+					Default::default(),
+				),
+				Error::<Runtime>::NoPrice
+			);
+		}
+		MockPriceProvider::set_price(DOT_CURRENCY, Some(Price::one()));
+	});
+}
+
+#[test]
+fn subscribe_with_foreign_asset_payment_works() {
+	// the foreign asset has 6 decimals, from `CurrencyDecimals`
+	let foreign_unit = 10u128.pow(6);
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), FOREIGN_CURRENCY, 100 * foreign_unit),
+			(AccountId::from(ALICE), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			let create = |currency_id: CurrencyId| {
+				AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					currency_id,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 1_000_000_000),
+					dollar(ADAO_CURRENCY) * UNITS,
+					Discount {
+						max: DiscountRate::saturating_from_rational(2, 10),
+						min: DiscountRate::saturating_from_integer(-1),
+						interval: 1,
+						inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+						dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+					},
+					Default::default(),
+				)
+			};
+			assert_noop!(
+				create(CurrencyId::ForeignAsset(1)),
+				Error::<Runtime>::NoDecimalsInfo
+			);
+			assert_ok!(create(FOREIGN_CURRENCY));
+			assert_ok!(create(AUSD_CURRENCY));
+
+			// both priced at $1
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				20 * foreign_unit,
+				0,
+				None
+			));
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				1,
+				dollar(AUSD_CURRENCY) * 20,
+				0,
+				None
+			));
+
+			let subscription_amount = AquaDao::subscriptions(0).unwrap().state.total_sold;
+			assert!(subscription_amount > 0);
+			assert_eq!(AquaDao::subscriptions(1).unwrap().state.total_sold, subscription_amount);
+			assert_eq!(
+				Currencies::free_balance(FOREIGN_CURRENCY, &ALICE),
+				80 * foreign_unit
+			);
+			assert_eq!(
+				Currencies::free_balance(FOREIGN_CURRENCY, &DAO_ACCOUNT),
+				20 * foreign_unit
+			);
+			MockStakedToken::assert_minted(&ALICE, subscription_amount, 1_000);
+
+			// sold stats are quoted in the valuation currency, not the payment currency
+			for subscription_id in [0, 1] {
+				assert_eq!(
+					AquaDao::sold_series(subscription_id),
+					vec![(0, dollar(AUSD_CURRENCY) * 20, subscription_amount)]
+				);
+			}
+		});
+}
+
+#[test]
+fn subscription_amount_is_independent_of_payment_currency_decimals() {
+	// $100 in each payment currency, DOT has 10 decimals, AUSD 12 and the foreign asset 18
+	let payments = [
+		(DOT_CURRENCY, 100 * 10u128.pow(10)),
+		(AUSD_CURRENCY, 100 * 10u128.pow(12)),
+		(FOREIGN_18_DECIMALS_CURRENCY, 100 * 10u128.pow(18)),
+	];
+	ExtBuilder::default()
+		.balances(payments.iter().map(|(currency_id, amount)| (ALICE, *currency_id, *amount)).collect())
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			for (subscription_id, (currency_id, payment_amount)) in payments.iter().enumerate() {
+				assert_ok!(AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					*currency_id,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 10),
+					dollar(ADAO_CURRENCY) * UNITS,
+					Discount {
+						max: DiscountRate::saturating_from_rational(2, 10),
+						min: DiscountRate::saturating_from_integer(-1),
+						interval: 1,
+						inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+						dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+					},
+					Default::default(),
+				));
+				assert_ok!(AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					subscription_id as SubscriptionId,
+					*payment_amount,
+					0,
+					None
+				));
+			}
+
+			let sold = |subscription_id| AquaDao::subscriptions(subscription_id).unwrap().state.total_sold;
+			// not clamped by `min_ratio`, which allows up to 1_000 ADAO for $100
+			assert!(sold(1) > dollar(ADAO_CURRENCY) * 10);
+			assert!(sold(1) < dollar(ADAO_CURRENCY) * 1_000);
+			assert_eq!(sold(0), sold(1));
+			assert_eq!(sold(2), sold(1));
+		});
+}
+
+#[test]
+fn subscribe_works_with_large_payment_amount() {
+	let unit = 10u128.pow(18);
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			FOREIGN_18_DECIMALS_CURRENCY,
+			3_000_000_000 * unit,
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				FOREIGN_18_DECIMALS_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(ADAO_CURRENCY) * 1_000_000_000_000,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				Default::default(),
+			));
+
+			// 10^24 and 10^27 in balance, beyond `FixedU128` if not normalized first
+			for payment_amount in [1_000_000 * unit, 1_000_000_000 * unit] {
+				let sold = AquaDao::subscriptions(0).unwrap().state.total_sold;
+				assert_ok!(AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					payment_amount,
+					0,
+					None
+				));
+				assert!(AquaDao::subscriptions(0).unwrap().state.total_sold > sold);
+			}
+
+			// flat price, 10^9 ADAO received, beyond `u128` if multiplied by ADAO accuracy before
+			// dividing by `FixedU128` accuracy
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				FOREIGN_18_DECIMALS_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(ADAO_CURRENCY) * 1_000_000_000_000,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::zero(),
+				},
+				Default::default(),
+			));
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				1,
+				1_000_000_000 * unit,
+				0,
+				None
+			));
+			assert!(AquaDao::subscriptions(1).unwrap().state.total_sold > dollar(ADAO_CURRENCY) * 340_000_000);
+		});
+}
+
+#[test]
+fn subscribe_fails_if_payment_value_overflows() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(create_default_subscription());
+
+		// `Balance::MAX` AUSD is far beyond `FixedU128` in value
+		assert_eq!(AquaDao::quote_subscription(0, Balance::MAX), None);
+		assert_noop!(
+			AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, Balance::MAX, 0, None),
+			ArithmeticError::Overflow
+		);
+	});
+}
+
+#[test]
+fn subscribe_with_stable_asset_pool_token_payment_works() {
+	// the pool token has 10 decimals, which `TokenInfo` doesn't know of
+	let pool_token_unit = 10u128.pow(10);
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), POOL_TOKEN_CURRENCY, 100 * pool_token_unit),
+			(AccountId::from(ALICE), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			let create = |currency_id: CurrencyId| {
+				AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					currency_id,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 1_000_000),
+					dollar(ADAO_CURRENCY) * UNITS,
+					Discount {
+						max: DiscountRate::saturating_from_rational(2, 10),
+						min: DiscountRate::saturating_from_integer(-1),
+						interval: 1,
+						inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+						dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+					},
+					Default::default(),
+				)
+			};
+			assert_noop!(
+				create(CurrencyId::StableAssetPoolToken(1)),
+				Error::<Runtime>::NoDecimalsInfo
+			);
+			assert_ok!(create(POOL_TOKEN_CURRENCY));
+			assert_ok!(create(AUSD_CURRENCY));
+
+			// 10 pool tokens are worth 20 AUSD
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				10 * pool_token_unit,
+				0,
+				None
+			));
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				1,
+				dollar(AUSD_CURRENCY) * 20,
+				0,
+				None
+			));
+
+			let subscription_amount = AquaDao::subscriptions(0).unwrap().state.total_sold;
+			assert!(subscription_amount > 0);
+			assert_eq!(AquaDao::subscriptions(1).unwrap().state.total_sold, subscription_amount);
+			assert_eq!(
+				Currencies::free_balance(POOL_TOKEN_CURRENCY, &DAO_ACCOUNT),
+				10 * pool_token_unit
+			);
+			System::assert_has_event(Event::AquaDao(crate::Event::Subscribed {
+				who: ALICE,
+				beneficiary: ALICE,
+				payment_dest: DAO_ACCOUNT,
+				subscription_id: 0,
+				payment_amount: 10 * pool_token_unit,
+				subscription_amount,
+				remaining_after: dollar(ADAO_CURRENCY) * UNITS - subscription_amount,
+				total_payment: 10 * pool_token_unit,
+				adao_price: Price::one(),
+				payment_price: Price::saturating_from_integer(2),
+			}));
+		});
+}
+
+#[test]
+fn subscribe_with_below_min_ratio_works() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			// min_ratio is 1
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::one(),
+				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				Default::default(),
+			));
+			Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
+				if let Some(subscription) = maybe_subscription {
+					subscription.state.last_discount = FixedI128::saturating_from_rational(5, 100);
+				}
+			});
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			System::assert_has_event(Event::AquaDao(crate::Event::Subscribed {
+				who: ALICE,
+				beneficiary: ALICE,
+				payment_dest: DAO_ACCOUNT,
+				subscription_id: 0,
+				payment_amount,
+				subscription_amount: dollar(ADAO_CURRENCY) * 100,
+				remaining_after: dollar(ADAO_CURRENCY) * UNITS - dollar(ADAO_CURRENCY) * 100,
+				total_payment: payment_amount,
+				adao_price: Price::one(),
+				payment_price: Price::one(),
+			}));
+		});
+}
+
+#[test]
+fn subscribe_fails_if_below_min_amount() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(create_default_subscription());
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 1;
+			assert_noop!(
+				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, payment_amount, 0, None),
+				Error::<Runtime>::BelowMinSubscriptionAmount
+			);
+		});
+}
+
+#[test]
+fn subscribe_fails_if_full() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				Default::default(),
+			));
+
+			Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
+				if let Some(subscription) = maybe_subscription {
+					subscription.state.total_sold = dollar(CurrencyId::Token(ADAO)) * UNITS;
+				}
+			});
+
+			MockPriceProvider::reset_calls();
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			assert_noop!(
+				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, payment_amount, 0, None),
+				Error::<Runtime>::SubscriptionIsFull
+			);
+			MockPriceProvider::assert_no_price_calls();
+		});
+}
+
+#[test]
+fn subscribe_with_vesting_tiers_works() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			let discount = Discount {
+				max: DiscountRate::saturating_from_rational(2, 10),
+				min: DiscountRate::saturating_from_integer(-1),
+				interval: 1,
+				inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+				dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+			};
+			let tier = |adao: Balance, vesting_period: BlockNumber| (dollar(ADAO_CURRENCY) * adao, vesting_period);
+			let unsorted = VestingTiers::<BlockNumber>::try_from(vec![tier(1_000, 3_000), tier(100, 2_000)]).unwrap();
+			assert_noop!(
+				AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					AUSD_CURRENCY,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 10),
+					dollar(CurrencyId::Token(ADAO)) * UNITS,
+					discount,
+					SubscriptionParams {
+						vesting_tiers: unsorted,
+						..Default::default()
+					},
+				),
+				Error::<Runtime>::InvalidVestingTiers
+			);
+
+			let tiers = VestingTiers::<BlockNumber>::try_from(vec![tier(100, 2_000), tier(1_000, 3_000)]).unwrap();
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				discount,
+				SubscriptionParams {
+					vesting_tiers: tiers,
+					..Default::default()
+				},
+			));
+
+			// ADAO price: $0.01, subscribed amount is clamped to 10 ADAO per $1 by `min_ratio`
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_rational(1, 100)));
+
+			// below threshold: 99.9 ADAO
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 999 / 100,
+				0,
+				None
+			));
+			MockStakedToken::assert_minted(&ALICE, dollar(ADAO_CURRENCY) * 999 / 10, 1_000);
+
+			// at threshold: 100 ADAO
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 10,
+				0,
+				None
+			));
+			MockStakedToken::assert_minted(&ALICE, dollar(ADAO_CURRENCY) * 100, 2_000);
+
+			// above threshold: 200 ADAO
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 20,
+				0,
+				None
+			));
+			MockStakedToken::assert_minted(&ALICE, dollar(ADAO_CURRENCY) * 200, 2_000);
+		});
+}
+
+#[test]
+fn subscribe_fails_within_per_account_cooldown() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+			(AccountId::from(BOB), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			MockBlockNumberProvider::set_block_number(1);
+			for _ in 0..2 {
+				assert_ok!(AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					AUSD_CURRENCY,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 10),
+					dollar(CurrencyId::Token(ADAO)) * UNITS,
+					Discount {
+						max: DiscountRate::saturating_from_rational(2, 10),
+						min: DiscountRate::saturating_from_integer(-1),
+						interval: 1,
+						inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+						dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+					},
+					SubscriptionParams {
+						per_account_cooldown: Some(10),
+						..Default::default()
+					},
+				));
+			}
+			let subscribe = |who: AccountId, subscription_id: SubscriptionId| {
+				AquaDao::subscribe(
+					RawOrigin::Signed(who).into(),
+					subscription_id,
+					dollar(AUSD_CURRENCY) * 10,
+					0,
+					None,
+				)
+			};
+
+			assert_ok!(subscribe(ALICE, 0));
+			assert_eq!(LastSubscribedAt::<Runtime>::get(0, ALICE), Some(1));
+			assert_noop!(subscribe(ALICE, 0), Error::<Runtime>::CooldownActive);
+
+			// cooldown is per subscription
+			assert_ok!(subscribe(ALICE, 1));
+
+			MockBlockNumberProvider::set_block_number(10);
+			assert_noop!(subscribe(ALICE, 0), Error::<Runtime>::CooldownActive);
+			MockBlockNumberProvider::set_block_number(11);
+			assert_ok!(subscribe(ALICE, 0));
+			assert_eq!(LastSubscribedAt::<Runtime>::get(0, ALICE), Some(11));
+
+			// `BOB` is exempted
+			assert_ok!(AquaDao::update_subscription(
+				RawOrigin::Root.into(),
+				0,
+				None,
+				None,
+				None,
+				None,
+				None,
+				SubscriptionUpdate {
+					bypass_filter: Some(true),
+					..Default::default()
+				},
+			));
+			assert_ok!(subscribe(BOB, 0));
+			assert_ok!(subscribe(BOB, 0));
+
+			// removed on closing
+			assert_ok!(AquaDao::close_subscription(RawOrigin::Root.into(), 0));
+			assert_eq!(LastSubscribedAt::<Runtime>::get(0, ALICE), None);
+		});
+}
+
+#[test]
+fn per_account_cooldown_applies_to_beneficiary() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+			(AccountId::from(BOB), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			MockBlockNumberProvider::set_block_number(1);
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				SubscriptionParams {
+					per_account_cooldown: Some(10),
+					..Default::default()
+				},
+			));
+			let subscribe_for = |payer: AccountId, beneficiary: AccountId| {
+				AquaDao::subscribe_for(
+					RawOrigin::Signed(payer).into(),
+					beneficiary,
+					0,
+					dollar(AUSD_CURRENCY) * 10,
+					0,
+					None,
+				)
+			};
+
+			// paid by the exempted `BOB`, the cooldown still applies to `ALICE`
+			assert_ok!(subscribe_for(BOB, ALICE));
+			assert_eq!(LastSubscribedAt::<Runtime>::get(0, ALICE), Some(1));
+			assert_eq!(LastSubscribedAt::<Runtime>::get(0, BOB), None);
+			assert_noop!(subscribe_for(BOB, ALICE), Error::<Runtime>::CooldownActive);
+			assert_noop!(subscribe_for(ALICE, ALICE), Error::<Runtime>::CooldownActive);
+
+			// `BOB` as beneficiary is exempted, whoever pays
+			assert_ok!(subscribe_for(ALICE, BOB));
+			assert_ok!(subscribe_for(ALICE, BOB));
+
+			MockBlockNumberProvider::set_block_number(11);
+			assert_ok!(subscribe_for(BOB, ALICE));
+		});
+}
+
+#[test]
+fn subscribe_fails_before_start_block() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			MockBlockNumberProvider::set_block_number(1);
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				SubscriptionParams {
+					start_block: 10,
+					..Default::default()
+				},
+			));
+			let subscription = AquaDao::subscriptions(0).unwrap();
+			assert_eq!(subscription.start_block, 10);
+			assert_eq!(subscription.state.last_sold_at, 10);
+			System::assert_last_event(Event::AquaDao(crate::Event::SubscriptionCreated { id: 0, subscription }));
+
+			let subscribe = || {
+				AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					dollar(AUSD_CURRENCY) * 10,
+					0,
+					None,
+				)
+			};
+			MockBlockNumberProvider::set_block_number(9);
+			assert_noop!(subscribe(), Error::<Runtime>::SubscriptionNotStarted);
+
+			// no idle discount accrued before the start
+			MockBlockNumberProvider::set_block_number(10);
+			assert_eq!(AquaDao::discount_at(0, 10), Some(DiscountRate::zero()));
+			assert_ok!(subscribe());
+		});
+}
+
+#[test]
+fn subscribe_accumulates_total_payment() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+			assert_ok!(create_default_subscription());
+			let subscribe = |subscription_id: SubscriptionId, payment_amount: Balance| {
+				AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					subscription_id,
+					payment_amount,
+					0,
+					None,
+				)
+			};
+			let total_payment = |subscription_id| AquaDao::subscriptions(subscription_id).unwrap().state.total_payment;
+
+			assert_ok!(subscribe(0, dollar(AUSD_CURRENCY) * 100));
+			assert_ok!(subscribe(1, dollar(AUSD_CURRENCY) * 30));
+			assert_ok!(subscribe(0, dollar(AUSD_CURRENCY) * 50));
+			assert_eq!(total_payment(0), dollar(AUSD_CURRENCY) * 150);
+			assert_eq!(total_payment(1), dollar(AUSD_CURRENCY) * 30);
+			assert!(matches!(
+				System::events().last().map(|record| record.event.clone()),
+				Some(Event::AquaDao(crate::Event::Subscribed { total_payment, .. }))
+					if total_payment == dollar(AUSD_CURRENCY) * 150
+			));
+
+			let total_sold = AquaDao::subscriptions(0).unwrap().state.total_sold;
+			assert_ok!(AquaDao::close_subscription(RawOrigin::Root.into(), 0));
+			System::assert_last_event(Event::AquaDao(crate::Event::SubscriptionClosed {
+				id: 0,
+				currency_id: AUSD_CURRENCY,
+				total_sold,
+				total_payment: dollar(AUSD_CURRENCY) * 150,
+				remaining: dollar(ADAO_CURRENCY) * UNITS - total_sold,
+			}));
+		});
+}
+
+#[test]
+fn subscribe_pays_to_payment_dest() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				SubscriptionParams {
+					payment_dest: Some(BOB),
+					..Default::default()
+				},
+			));
+			assert_eq!(AquaDao::subscriptions(0).unwrap().payment_dest, BOB);
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			assert_eq!(Currencies::free_balance(AUSD_CURRENCY, &BOB), payment_amount);
+			assert_eq!(Currencies::free_balance(AUSD_CURRENCY, &DAO_ACCOUNT), 0);
+			let subscription_amount = AquaDao::subscriptions(0).unwrap().state.total_sold;
+			System::assert_has_event(Event::AquaDao(crate::Event::Subscribed {
+				who: ALICE,
+				beneficiary: ALICE,
+				payment_dest: BOB,
+				subscription_id: 0,
+				payment_amount,
+				subscription_amount,
+				remaining_after: dollar(ADAO_CURRENCY) * UNITS - subscription_amount,
+				total_payment: payment_amount,
+				adao_price: Price::one(),
+				payment_price: Price::one(),
+			}));
+
+			// payment destination updated
+			assert_ok!(AquaDao::update_subscription(
+				RawOrigin::Root.into(),
+				0,
+				None,
+				None,
+				None,
+				None,
+				None,
+				SubscriptionUpdate {
+					payment_dest: Some(DAO_ACCOUNT),
+					..Default::default()
+				},
+			));
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			assert_eq!(Currencies::free_balance(AUSD_CURRENCY, &BOB), payment_amount);
+			assert_eq!(Currencies::free_balance(AUSD_CURRENCY, &DAO_ACCOUNT), payment_amount);
+		});
+}
+
+#[test]
+fn subscribe_for_mints_to_beneficiary() {
+	ExtBuilder::default()
+		.balances(vec![(AccountId::from(BOB), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY))])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			// eligibility is checked against the beneficiary, not the payer
+			assert_noop!(
+				AquaDao::subscribe_for(RawOrigin::Signed(BOB).into(), BOB, 0, payment_amount, 0, None),
+				Error::<Runtime>::NotEligible
+			);
+			assert_ok!(AquaDao::subscribe_for(
+				RawOrigin::Signed(BOB).into(),
+				ALICE,
+				0,
+				payment_amount,
+				0,
+				None
+			));
+
+			let subscription_amount = AquaDao::subscriptions(0).unwrap().state.total_sold;
+			assert_eq!(
+				Currencies::free_balance(AUSD_CURRENCY, &BOB),
+				900 * dollar(AUSD_CURRENCY)
+			);
+			assert_eq!(Currencies::free_balance(AUSD_CURRENCY, &ALICE), 0);
+			assert_eq!(Currencies::free_balance(AUSD_CURRENCY, &DAO_ACCOUNT), payment_amount);
+			MockStakedToken::assert_minted(&ALICE, subscription_amount, 1_000);
+			assert_eq!(MockStakedToken::mint_calls(), 1);
+			System::assert_last_event(Event::AquaDao(crate::Event::Subscribed {
+				who: BOB,
+				beneficiary: ALICE,
+				payment_dest: DAO_ACCOUNT,
+				subscription_id: 0,
+				payment_amount,
+				subscription_amount,
+				remaining_after: dollar(ADAO_CURRENCY) * UNITS - subscription_amount,
+				total_payment: payment_amount,
+				adao_price: Price::one(),
+				payment_price: Price::one(),
+			}));
+		});
+}
+
+#[test]
+fn subscribe_batch_works() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+			assert_ok!(create_default_subscription());
+
+			assert_ok!(AquaDao::subscribe_batch(
+				RawOrigin::Signed(ALICE).into(),
+				BoundedVec::try_from(vec![(0, dollar(AUSD_CURRENCY) * 100, 0), (1, dollar(AUSD_CURRENCY) * 50, 0)])
+					.unwrap(),
+			));
+
+			assert_eq!(
+				Currencies::free_balance(AUSD_CURRENCY, &ALICE),
+				850 * dollar(AUSD_CURRENCY)
+			);
+			assert_eq!(MockStakedToken::mint_calls(), 2);
+			for (subscription_id, payment_amount) in [(0, 100), (1, 50)] {
+				let subscription_amount = AquaDao::subscriptions(subscription_id).unwrap().state.total_sold;
+				assert!(subscription_amount > 0);
+				System::assert_has_event(Event::AquaDao(crate::Event::Subscribed {
+					who: ALICE,
+					beneficiary: ALICE,
+					payment_dest: DAO_ACCOUNT,
+					subscription_id,
+					payment_amount: dollar(AUSD_CURRENCY) * payment_amount,
+					subscription_amount,
+					remaining_after: dollar(ADAO_CURRENCY) * UNITS - subscription_amount,
+					total_payment: dollar(AUSD_CURRENCY) * payment_amount,
+					adao_price: Price::one(),
+					payment_price: Price::one(),
+				}));
+			}
+			System::assert_last_event(Event::AquaDao(crate::Event::BatchSubscribed {
+				who: ALICE,
+				orders: 2,
+				total_subscription_amount: AquaDao::subscriptions(0).unwrap().state.total_sold
+					+ AquaDao::subscriptions(1).unwrap().state.total_sold,
+			}));
+		});
+}
+
+#[test]
+fn subscribe_batch_rolls_back_if_any_leg_fails() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+			assert_ok!(create_default_subscription());
+
+			// the middle leg is below its minimum target amount
+			assert_noop!(
+				AquaDao::subscribe_batch(
+					RawOrigin::Signed(ALICE).into(),
+					BoundedVec::try_from(vec![
+						(0, dollar(AUSD_CURRENCY) * 100, 0),
+						(1, dollar(AUSD_CURRENCY) * 50, dollar(ADAO_CURRENCY) * UNITS),
+						(0, dollar(AUSD_CURRENCY) * 10, 0),
+					])
+					.unwrap(),
+				),
+				Error::<Runtime>::BelowMinTargetAmount
+			);
+			assert_eq!(
+				Currencies::free_balance(AUSD_CURRENCY, &ALICE),
+				1_000 * dollar(AUSD_CURRENCY)
+			);
+			assert_eq!(AquaDao::subscriptions(0).unwrap().state.total_sold, 0);
+		});
+}
+
+#[test]
+fn subscribe_batch_call_with_too_many_legs_fails_to_decode() {
+	let legs = |n: usize| vec![(0 as SubscriptionId, dollar(AUSD_CURRENCY), 0 as Balance); n];
+	let encoded = |n: usize| {
+		let mut encoded = crate::Call::<Runtime>::subscribe_batch {
+			legs: Default::default(),
+		}
+		.encode();
+		// replace the empty legs with `n` of them
+		encoded.truncate(1);
+		encoded.extend(legs(n).encode());
+		encoded
+	};
+
+	let max = MaxBatchSize::get() as usize;
+	assert_eq!(
+		crate::Call::<Runtime>::decode(&mut &encoded(max)[..]).ok(),
+		Some(crate::Call::subscribe_batch {
+			legs: BoundedVec::try_from(legs(max)).unwrap()
+		})
+	);
+	assert!(crate::Call::<Runtime>::decode(&mut &encoded(max + 1)[..]).is_err());
+}
+
+#[test]
+fn subscribe_fails_if_not_eligible() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+			(AccountId::from(BOB), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			assert_noop!(
+				AquaDao::subscribe(RawOrigin::Signed(BOB).into(), 0, payment_amount, 0, None),
+				Error::<Runtime>::NotEligible
+			);
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+
+			// subscription bypassing the filter is open to all
+			assert_ok!(AquaDao::update_subscription(
+				RawOrigin::Root.into(),
+				0,
+				None,
+				None,
+				None,
+				None,
+				None,
+				SubscriptionUpdate {
+					bypass_filter: Some(true),
+					..Default::default()
+				},
+			));
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(BOB).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			assert_eq!(MockStakedToken::mint_calls(), 2);
+		});
+}
+
+#[test]
+fn discount_preview_works() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_eq!(AquaDao::discount_at(0, 1), None);
+			assert_eq!(AquaDao::blocks_until_discount(0, DiscountRate::zero()), None);
+
+			// discount increases 0.1% per idle block, up to 20%
+			assert_ok!(create_default_subscription());
+			assert_eq!(AquaDao::discount_at(0, 1), Some(DiscountRate::zero()));
+			assert_eq!(
+				AquaDao::discount_at(0, 51),
+				Some(DiscountRate::saturating_from_rational(5, 100))
+			);
+			assert_eq!(
+				AquaDao::discount_at(0, 1_001),
+				Some(DiscountRate::saturating_from_rational(2, 10))
+			);
+			assert_eq!(
+				AquaDao::blocks_until_discount(0, DiscountRate::saturating_from_rational(5, 100)),
+				Some(50)
+			);
+			assert_eq!(
+				AquaDao::blocks_until_discount(0, DiscountRate::saturating_from_rational(3, 10)),
+				None
+			);
+
+			MockBlockNumberProvider::set_block_number(51);
+			assert_eq!(
+				AquaDao::blocks_until_discount(0, DiscountRate::saturating_from_rational(5, 100)),
+				Some(0)
+			);
+			let preview = AquaDao::discount_at(0, 51).unwrap();
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 100,
+				0,
+				None
+			));
+			assert_eq!(AquaDao::subscriptions(0).unwrap().state.last_discount, preview);
+		});
+}
+
+#[test]
+fn quote_subscription_matches_subscribe() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			1_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			assert_eq!(AquaDao::quote_subscription(0, payment_amount), None);
+
+			assert_ok!(create_default_subscription());
+			MockPriceProvider::set_price(ADAO_CURRENCY, None);
+			assert_eq!(AquaDao::quote_subscription(0, payment_amount), None);
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::one()));
+
+			// idle intervals increase the discount
+			MockBlockNumberProvider::set_block_number(51);
+			let (amount, discount, preview) = AquaDao::quote_subscription(0, payment_amount).unwrap();
+			assert_eq!(discount, DiscountRate::saturating_from_rational(5, 100));
+			assert_eq!(preview.staked, amount);
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			MockStakedToken::assert_minted(&ALICE, amount, 1_000);
+			assert_eq!(AquaDao::subscriptions(0).unwrap().state.last_discount, discount);
+		});
+}
+
+#[test]
+fn remaining_capacity_works() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_eq!(AquaDao::remaining_capacity(0), None);
+
+			// capped at 250 ADAO
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(ADAO_CURRENCY) * 250,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				Default::default(),
+			));
+			assert_eq!(AquaDao::remaining_capacity(0), Some(dollar(ADAO_CURRENCY) * 250));
+
+			// ADAO price: $0.01, subscribed amount is clamped to 10 ADAO per $1 by `min_ratio`
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_rational(1, 100)));
+			let subscribe = |payment_amount: Balance| {
+				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, payment_amount, 0, None)
+			};
+
+			for remaining_after in [150, 50] {
+				assert_ok!(subscribe(dollar(AUSD_CURRENCY) * 10));
+				System::assert_last_event(Event::AquaDao(crate::Event::Subscribed {
+					who: ALICE,
+					beneficiary: ALICE,
+					payment_dest: DAO_ACCOUNT,
+					subscription_id: 0,
+					payment_amount: dollar(AUSD_CURRENCY) * 10,
+					subscription_amount: dollar(ADAO_CURRENCY) * 100,
+					remaining_after: dollar(ADAO_CURRENCY) * remaining_after,
+					total_payment: dollar(AUSD_CURRENCY) * (250 - remaining_after) / 10,
+					adao_price: Price::saturating_from_rational(1, 100),
+					payment_price: Price::one(),
+				}));
+				assert_eq!(
+					AquaDao::remaining_capacity(0),
+					Some(dollar(ADAO_CURRENCY) * remaining_after)
+				);
+			}
+
+			assert_noop!(
+				subscribe(dollar(AUSD_CURRENCY) * 10),
+				Error::<Runtime>::SubscriptionIsFull
+			);
+
+			assert_ok!(subscribe(dollar(AUSD_CURRENCY) * 5));
+			System::assert_has_event(Event::AquaDao(crate::Event::Subscribed {
+				who: ALICE,
+				beneficiary: ALICE,
+				payment_dest: DAO_ACCOUNT,
+				subscription_id: 0,
+				payment_amount: dollar(AUSD_CURRENCY) * 5,
+				subscription_amount: dollar(ADAO_CURRENCY) * 50,
+				remaining_after: 0,
+				total_payment: dollar(AUSD_CURRENCY) * 25,
+				adao_price: Price::saturating_from_rational(1, 100),
+				payment_price: Price::one(),
+			}));
+			// closed once fully sold
+			System::assert_has_event(Event::AquaDao(crate::Event::SubscriptionSoldOut {
+				id: 0,
+				total_sold: dollar(ADAO_CURRENCY) * 250,
+				total_payment: dollar(AUSD_CURRENCY) * 25,
+			}));
+			System::assert_last_event(Event::AquaDao(crate::Event::SubscriptionClosed {
+				id: 0,
+				currency_id: AUSD_CURRENCY,
+				total_sold: dollar(ADAO_CURRENCY) * 250,
+				total_payment: dollar(AUSD_CURRENCY) * 25,
+				remaining: 0,
+			}));
+			assert_eq!(AquaDao::subscriptions(0), None);
+			assert_eq!(AquaDao::remaining_capacity(0), None);
+			assert_noop!(
+				subscribe(dollar(AUSD_CURRENCY) * 5),
+				Error::<Runtime>::SubscriptionNotFound
+			);
+			assert_eq!(
+				System::events()
+					.iter()
+					.filter(|record| matches!(
+						record.event,
+						Event::AquaDao(crate::Event::SubscriptionSoldOut { .. })
+					))
+					.count(),
+				1
+			);
+		});
+}
+
+#[test]
+fn lifecycle_events_are_emitted_once() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			// capped at 250 ADAO
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(ADAO_CURRENCY) * 250,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				Default::default(),
+			));
+
+			// ADAO price: $0.01, subscribed amount is clamped to 10 ADAO per $1 by `min_ratio`
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_rational(1, 100)));
+			let subscribe = |payment_amount: Balance| {
+				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, payment_amount, 0, None)
+			};
+			let emitted = |event: crate::Event<Runtime>| {
+				System::events()
+					.iter()
+					.filter(|record| record.event == Event::AquaDao(event.clone()))
+					.count()
+			};
+			let sold_out = crate::Event::SubscriptionSoldOut {
+				id: 0,
+				total_sold: dollar(ADAO_CURRENCY) * 245,
+				total_payment: dollar(AUSD_CURRENCY) * 245 / 10,
+			};
+			let sold_out_count = || {
+				System::events()
+					.iter()
+					.filter(|record| matches!(record.event, Event::AquaDao(crate::Event::SubscriptionSoldOut { .. })))
+					.count()
+			};
+			let discount_cap_reached = crate::Event::DiscountCapReached { id: 0 };
+
+			// idle long enough for the discount to be capped
+			MockBlockNumberProvider::set_block_number(1_000);
+			assert_ok!(subscribe(dollar(AUSD_CURRENCY) * 10));
+			System::assert_last_event(Event::AquaDao(discount_cap_reached.clone()));
+			assert!(AquaDao::subscriptions(0).unwrap().state.discount_cap_reached);
+
+			// capped again, but only emitted the first time
+			MockBlockNumberProvider::set_block_number(2_000);
+			assert_ok!(subscribe(dollar(AUSD_CURRENCY) * 10));
+			assert_eq!(emitted(discount_cap_reached.clone()), 1);
+			assert_eq!(sold_out_count(), 0);
+
+			// remaining 5 ADAO, below `min_amount`
+			assert_ok!(subscribe(dollar(AUSD_CURRENCY) * 45 / 10));
+			System::assert_last_event(Event::AquaDao(sold_out));
+			assert!(AquaDao::subscriptions(0).unwrap().state.sold_out);
+
+			// remaining 1 ADAO, below the lowered `min_amount` again, but only emitted the first time
+			assert_ok!(AquaDao::update_subscription(
+				RawOrigin::Root.into(),
+				0,
+				None,
+				Some(dollar(ADAO_CURRENCY) * 2),
+				None,
+				None,
+				None,
+				Default::default(),
+			));
+			assert_ok!(subscribe(dollar(AUSD_CURRENCY) * 4 / 10));
+			assert_eq!(sold_out_count(), 1);
+			assert_eq!(emitted(discount_cap_reached), 1);
+		});
+}
+
+#[test]
+fn subscribe_fails_if_price_above_limit() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+
+			// ADAO price: $0.01, about 10_000 ADAO for $100, clamped to 1_000 ADAO by `min_ratio`
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_rational(1, 100)));
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			let min_target_amount = dollar(ADAO_CURRENCY) * 500;
+
+			// amount check passes, but the effective price is $0.1
+			assert_noop!(
+				AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					payment_amount,
+					min_target_amount,
+					Some(Price::saturating_from_rational(5, 100))
+				),
+				Error::<Runtime>::PriceAboveLimit
+			);
+
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				min_target_amount,
+				Some(Price::saturating_from_rational(1, 10))
+			));
+			assert_eq!(
+				AquaDao::subscriptions(0).unwrap().state.total_sold,
+				dollar(ADAO_CURRENCY) * 1_000
+			);
+		});
+}
+
+#[test]
+fn subscription_amount_is_independent_of_valuation_currency() {
+	let subscribe_with_valuation = |valuation_currency_id: CurrencyId| -> Balance {
+		MockValuationCurrencyId::set(valuation_currency_id);
+		ExtBuilder::default()
+			.balances(vec![(
+				AccountId::from(ALICE),
+				AUSD_CURRENCY,
+				2_000_000 * dollar(AUSD_CURRENCY),
+			)])
+			.build()
+			.execute_with(|| {
+				System::set_block_number(1);
+				assert_ok!(create_default_subscription());
+
+				assert_ok!(AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					dollar(AUSD_CURRENCY) * 100,
+					0,
+					None
+				));
+				assert_eq!(
+					MockPriceProvider::last_relative_price_query(),
+					Some((ADAO_CURRENCY, valuation_currency_id))
+				);
+				AquaDao::subscriptions(0).unwrap().state.total_sold
+			})
+	};
+
+	// 1 DOT = $0.5, so prices quoted in DOT are twice of those quoted in AUSD
+	MockPriceProvider::set_price(DOT_CURRENCY, Some(Price::saturating_from_rational(1, 2)));
+	let in_ausd = subscribe_with_valuation(AUSD_CURRENCY);
+	let in_dot = subscribe_with_valuation(DOT_CURRENCY);
+
+	assert!(!in_ausd.is_zero());
+	// equal up to the precision of fixed point square root
+	assert!(in_ausd.max(in_dot) - in_ausd.min(in_dot) <= dollar(ADAO_CURRENCY) / 100);
+}
+
+#[test]
+fn subscribe_with_stable_denomination_works() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			// capped at $1M
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(AUSD_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(AUSD_CURRENCY) * UNITS,
+				Discount {
+					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				SubscriptionParams {
+					denomination: AmountDenomination::Stable,
+					..Default::default()
+				},
+			));
+			Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
+				if let Some(subscription) = maybe_subscription {
+					subscription.state.total_sold = dollar(ADAO_CURRENCY) * UNITS;
+				}
+			});
+
+			// ADAO price: $1, 1M ADAO sold
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			assert_noop!(
+				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, payment_amount, 0, None),
+				Error::<Runtime>::SubscriptionIsFull
+			);
+
+			// ADAO price: $0.5, cap is now 2M ADAO
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_rational(1, 2)));
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				payment_amount,
+				0,
+				None
+			));
+			assert!(AquaDao::subscriptions(0).unwrap().state.total_sold > dollar(ADAO_CURRENCY) * UNITS);
+		});
+}
+
+#[test]
+fn sold_series_works() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			let subscribe_at = |n: BlockNumber| -> Balance {
+				MockBlockNumberProvider::set_block_number(n);
+				let sold_before = AquaDao::subscriptions(0).unwrap().state.total_sold;
+				assert_ok!(AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					payment_amount,
+					0,
+					None
+				));
+				AquaDao::subscriptions(0).unwrap().state.total_sold - sold_before
+			};
+
+			// era 0 and 1
+			let sold_0 = subscribe_at(1) + subscribe_at(50);
+			let sold_1 = subscribe_at(150);
+			assert_eq!(
+				AquaDao::sold_series(0),
+				vec![(0, 2 * payment_amount, sold_0), (1, payment_amount, sold_1)]
+			);
+
+			// era 3, era 0 pruned
+			let sold_3 = subscribe_at(350);
+			assert_eq!(
+				AquaDao::sold_series(0),
+				vec![(1, payment_amount, sold_1), (3, payment_amount, sold_3)]
+			);
+
+			// era 4, era 1 pruned
+			let sold_4 = subscribe_at(450);
+			assert_eq!(
+				AquaDao::sold_series(0),
+				vec![(3, payment_amount, sold_3), (4, payment_amount, sold_4)]
+			);
+		});
+}
+
+#[test]
+fn subscribe_fails_if_vesting_slots_exhausted() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(create_default_subscription());
+
+			MockStakedToken::set_can_mint(false);
+			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			assert_noop!(
+				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, payment_amount, 0, None),
+				Error::<Runtime>::VestingSlotsExhausted
+			);
+			assert_eq!(
+				Currencies::free_balance(AUSD_CURRENCY, &ALICE),
+				2_000_000 * dollar(AUSD_CURRENCY)
+			);
+			assert_eq!(MockStakedToken::mint_calls(), 0);
+		});
+}
+
+#[test]
+fn subscribe_fails_if_below_target_amount() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(create_default_subscription());
+			Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
+				if let Some(subscription) = maybe_subscription {
+					subscription.state.last_discount = FixedI128::saturating_from_rational(5, 100);
 				}
+			});
+
+			let payment_amount = dollar(AUSD_CURRENCY) * 10;
+			assert_noop!(
+				AquaDao::subscribe(
+					RawOrigin::Signed(ALICE).into(),
+					0,
+					payment_amount,
+					dollar(ADAO_CURRENCY) * 100,
+					None
+				),
+				Error::<Runtime>::BelowMinTargetAmount
+			);
+		});
+}
+
+#[test]
+fn idle_discount_is_capped() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(AquaDao::create_subscription(
+				RawOrigin::Root.into(),
+				AUSD_CURRENCY,
+				1_000,
+				dollar(ADAO_CURRENCY) * 10,
+				Ratio::saturating_from_rational(1, 10),
+				dollar(CurrencyId::Token(ADAO)) * UNITS,
+				Discount {
+					max: DiscountRate::saturating_from_rational(1, 2),
+					min: DiscountRate::saturating_from_integer(-1),
+					interval: 1,
+					inc_on_idle: DiscountRate::saturating_from_rational(1, 100_000),
+					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
+				},
+				Default::default(),
 			));
-			Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
-				if let Some(subscription) = maybe_subscription {
-					subscription.state.last_discount = FixedI128::saturating_from_rational(5, 100);
-				}
-			});
 
-			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			// 10_000 idle intervals, capped at `MaxIdleIntervals`
+			MockBlockNumberProvider::set_block_number(10_001);
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 100,
+				0,
+				None
+			));
+			assert_eq!(
+				AquaDao::subscriptions(0).unwrap().state.last_discount,
+				DiscountRate::saturating_from_rational(1, 100)
+			);
+		});
+}
+
+#[test]
+fn discount_is_floored_at_min() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			let create = |discount| {
+				AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					AUSD_CURRENCY,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 10),
+					dollar(CurrencyId::Token(ADAO)) * UNITS,
+					discount,
+					Default::default(),
+				)
+			};
+			// 0.1% decrease per ADAO subscribed, floored at 5% premium
+			let discount = Discount {
+				max: DiscountRate::saturating_from_rational(2, 10),
+				min: DiscountRate::saturating_from_rational(-5, 100),
+				interval: 1,
+				inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+				dec_per_unit: DiscountRate::saturating_from_rational(1, 1_000),
+			};
+			assert_noop!(
+				create(Discount {
+					min: DiscountRate::saturating_from_rational(3, 10),
+					..discount
+				}),
+				Error::<Runtime>::InvalidDiscount
+			);
+			assert_ok!(create(discount));
+
+			// 95 ADAO subscribed, the discount would decay to -9.5% without the floor
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 100,
+				0,
+				None
+			));
+			assert_eq!(
+				AquaDao::subscriptions(0).unwrap().state.total_sold / dollar(ADAO_CURRENCY),
+				95
+			);
+			assert_eq!(AquaDao::discount_at(0, 1), Some(discount.min));
+
+			// the next buyer pays at the floor premium
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 20,
+				0,
+				None
+			));
+			assert_eq!(AquaDao::subscriptions(0).unwrap().state.last_discount, discount.min);
+
+			// last discount is clamped into the updated band
+			assert_ok!(AquaDao::update_subscription(
+				RawOrigin::Root.into(),
+				0,
+				None,
+				None,
+				None,
+				None,
+				Some(Discount {
+					min: DiscountRate::zero(),
+					..discount
+				}),
+				Default::default(),
+			));
+			assert_eq!(AquaDao::subscriptions(0).unwrap().state.last_discount, DiscountRate::zero());
+		});
+}
+
+#[test]
+fn negative_discount_charges_premium() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			// 0.1% decrease per ADAO subscribed, no floor
+			let discount = Discount {
+				max: DiscountRate::saturating_from_rational(2, 10),
+				min: DiscountRate::saturating_from_integer(-1),
+				interval: 1,
+				inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+				dec_per_unit: DiscountRate::saturating_from_rational(1, 1_000),
+			};
+			for _ in 0..2 {
+				assert_ok!(AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					AUSD_CURRENCY,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 10),
+					dollar(CurrencyId::Token(ADAO)) * UNITS,
+					discount,
+					Default::default(),
+				));
+			}
+			assert_noop!(
+				AquaDao::update_subscription(
+					RawOrigin::Root.into(),
+					0,
+					None,
+					None,
+					None,
+					None,
+					Some(Discount {
+						max: DiscountRate::saturating_from_rational(11, 10),
+						..discount
+					}),
+					Default::default(),
+				),
+				Error::<Runtime>::InvalidDiscount
+			);
+
+			// 95 ADAO subscribed, pushing the discount of subscription 0 to -9.5%
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				dollar(AUSD_CURRENCY) * 100,
+				0,
+				None
+			));
+			let payment_amount = dollar(AUSD_CURRENCY) * 20;
+			let (premium_amount, premium, _) = AquaDao::quote_subscription(0, payment_amount).unwrap();
+			assert_eq!(premium, DiscountRate::saturating_from_rational(-95, 1_000));
+
+			// subscription 1 is at par, the premium buys less ADAO
+			let (par_amount, par, _) = AquaDao::quote_subscription(1, payment_amount).unwrap();
+			assert_eq!(par, DiscountRate::zero());
+			assert!(premium_amount < par_amount);
+			// at most 20 / 1.095 ADAO
+			assert!(premium_amount < dollar(ADAO_CURRENCY) * 20_000 / 1_095);
+
 			assert_ok!(AquaDao::subscribe(
 				RawOrigin::Signed(ALICE).into(),
 				0,
 				payment_amount,
-				0
+				0,
+				None
 			));
-			System::assert_has_event(Event::AquaDao(crate::Event::Subscribed {
-				who: ALICE,
-				subscription_id: 0,
+			MockStakedToken::assert_minted(&ALICE, premium_amount, 1_000);
+		});
+}
+
+#[test]
+fn negative_dec_per_unit_lowers_price_with_amount() {
+	ExtBuilder::default()
+		.balances(vec![(
+			AccountId::from(ALICE),
+			AUSD_CURRENCY,
+			2_000_000 * dollar(AUSD_CURRENCY),
+		)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			// subscription 0: 0.1% price increase per ADAO subscribed, subscription 1: 0.1% decrease
+			for dec_per_unit in [
+				DiscountRate::saturating_from_rational(1, 1_000),
+				DiscountRate::saturating_from_rational(-1, 1_000),
+			] {
+				assert_ok!(AquaDao::create_subscription(
+					RawOrigin::Root.into(),
+					AUSD_CURRENCY,
+					1_000,
+					dollar(ADAO_CURRENCY) * 10,
+					Ratio::saturating_from_rational(1, 10),
+					dollar(CurrencyId::Token(ADAO)) * UNITS,
+					Discount {
+						max: DiscountRate::saturating_from_rational(2, 10),
+						min: DiscountRate::saturating_from_integer(-1),
+						interval: 1,
+						inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+						dec_per_unit,
+					},
+					Default::default(),
+				));
+			}
+
+			// ADAO price $1, 20 ADAO at a flat price
+			let payment_amount = dollar(AUSD_CURRENCY) * 20;
+			let (rising_amount, _, _) = AquaDao::quote_subscription(0, payment_amount).unwrap();
+			let (falling_amount, _, _) = AquaDao::quote_subscription(1, payment_amount).unwrap();
+			// (sqrt(1 + 2 * 0.001 * 20) - 1) / 0.001 = 19.80 ADAO
+			assert!(rising_amount > dollar(ADAO_CURRENCY) * 1_980 / 100);
+			assert!(rising_amount < dollar(ADAO_CURRENCY) * 20);
+			// (1 - sqrt(1 - 2 * 0.001 * 20)) / 0.001 = 20.20 ADAO
+			assert!(falling_amount > dollar(ADAO_CURRENCY) * 2_020 / 100);
+			assert!(falling_amount < dollar(ADAO_CURRENCY) * 2_021 / 100);
+
+			assert_ok!(AquaDao::subscribe(
+				RawOrigin::Signed(ALICE).into(),
+				1,
 				payment_amount,
-				subscription_amount: dollar(ADAO_CURRENCY) * 100,
-			}));
+				0,
+				None
+			));
+			MockStakedToken::assert_minted(&ALICE, falling_amount, 1_000);
 		});
 }
 
 #[test]
-fn subscribe_fails_if_below_min_amount() {
+fn subscribe_fails_if_zero_payment() {
 	ExtBuilder::default()
 		.balances(vec![(
 			AccountId::from(ALICE),
@@ -365,16 +3161,19 @@ fn subscribe_fails_if_below_min_amount() {
 
 			assert_ok!(create_default_subscription());
 
-			let payment_amount = dollar(AUSD_CURRENCY) * 1;
+			MockPriceProvider::reset_calls();
 			assert_noop!(
-				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, payment_amount, 0),
-				Error::<Runtime>::BelowMinSubscriptionAmount
+				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, 0, 0, None),
+				Error::<Runtime>::ZeroPayment
 			);
+			// no price fetching
+			MockPriceProvider::assert_no_price_calls();
+			assert_eq!(MockStakedToken::mint_calls(), 0);
 		});
 }
 
 #[test]
-fn subscribe_fails_if_full() {
+fn subscribe_fails_if_zero_subscription_amount() {
 	ExtBuilder::default()
 		.balances(vec![(
 			AccountId::from(ALICE),
@@ -385,63 +3184,375 @@ fn subscribe_fails_if_full() {
 		.execute_with(|| {
 			System::set_block_number(1);
 
+			// no minimum subscription amount
 			assert_ok!(AquaDao::create_subscription(
 				RawOrigin::Root.into(),
 				AUSD_CURRENCY,
 				1_000,
-				dollar(ADAO_CURRENCY) * 10,
+				0,
 				Ratio::saturating_from_rational(1, 10),
 				dollar(CurrencyId::Token(ADAO)) * UNITS,
 				Discount {
 					max: DiscountRate::saturating_from_rational(2, 10),
+					min: DiscountRate::saturating_from_integer(-1),
 					interval: 1,
 					inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
 					dec_per_unit: DiscountRate::saturating_from_rational(20, UNITS * 100),
-				}
+				},
+				Default::default(),
 			));
 
-			Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
-				if let Some(subscription) = maybe_subscription {
-					subscription.state.total_sold = dollar(CurrencyId::Token(ADAO)) * UNITS;
-				}
-			});
-
-			let payment_amount = dollar(AUSD_CURRENCY) * 100;
+			// ADAO price: $1000, 1 unit of AUSD buys less than 1 unit of ADAO
+			MockPriceProvider::set_price(ADAO_CURRENCY, Some(Price::saturating_from_integer(1_000)));
 			assert_noop!(
-				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, payment_amount, 0),
-				Error::<Runtime>::SubscriptionIsFull
+				AquaDao::subscribe(RawOrigin::Signed(ALICE).into(), 0, 1, 0, None),
+				Error::<Runtime>::ZeroSubscriptionAmount
 			);
+			assert_eq!(MockStakedToken::mint_calls(), 0);
 		});
 }
 
 #[test]
-fn subscribe_fails_if_below_target_amount() {
+fn pallet_account_cache_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		let account: AccountId = AquaDaoPalletId::get().into_account();
+		assert_eq!(AquaDao::account_id(), account);
+		assert_eq!(AquaDao::pallet_account_cache(), Some(account));
+	});
+}
+
+#[test]
+fn migrate_legacy_proceeds_works() {
+	let pallet_account: AccountId = AquaDaoPalletId::get().into_account();
 	ExtBuilder::default()
-		.balances(vec![(
-			AccountId::from(ALICE),
-			AUSD_CURRENCY,
-			2_000_000 * dollar(AUSD_CURRENCY),
-		)])
+		.balances(vec![
+			(pallet_account.clone(), AUSD_CURRENCY, 1_000 * dollar(AUSD_CURRENCY)),
+			(pallet_account.clone(), DOT_CURRENCY, 10 * dollar(DOT_CURRENCY)),
+		])
 		.build()
 		.execute_with(|| {
 			System::set_block_number(1);
 
-			assert_ok!(create_default_subscription());
-			Subscriptions::<Runtime>::mutate(0, |maybe_subscription| {
-				if let Some(subscription) = maybe_subscription {
-					subscription.state.last_discount = FixedI128::saturating_from_rational(5, 100);
-				}
-			});
-
-			let payment_amount = dollar(AUSD_CURRENCY) * 10;
 			assert_noop!(
-				AquaDao::subscribe(
-					RawOrigin::Signed(ALICE).into(),
-					0,
-					payment_amount,
-					dollar(ADAO_CURRENCY) * 100
-				),
-				Error::<Runtime>::BelowMinTargetAmount
+				AquaDao::migrate_legacy_proceeds(RawOrigin::Signed(ALICE).into(), vec![AUSD_CURRENCY]),
+				BadOrigin
+			);
+
+			assert_ok!(AquaDao::migrate_legacy_proceeds(
+				RawOrigin::Root.into(),
+				vec![AUSD_CURRENCY, DOT_CURRENCY, ADAO_CURRENCY]
+			));
+			assert_eq!(Currencies::free_balance(AUSD_CURRENCY, &pallet_account), 0);
+			assert_eq!(Currencies::free_balance(DOT_CURRENCY, &pallet_account), 0);
+			assert_eq!(
+				Currencies::free_balance(AUSD_CURRENCY, &DAO_ACCOUNT),
+				1_000 * dollar(AUSD_CURRENCY)
+			);
+			assert_eq!(
+				Currencies::free_balance(DOT_CURRENCY, &DAO_ACCOUNT),
+				10 * dollar(DOT_CURRENCY)
+			);
+			System::assert_has_event(Event::AquaDao(crate::Event::LegacyProceedsMigrated {
+				currency_id: AUSD_CURRENCY,
+				amount: 1_000 * dollar(AUSD_CURRENCY),
+			}));
+			System::assert_has_event(Event::AquaDao(crate::Event::LegacyProceedsMigrated {
+				currency_id: DOT_CURRENCY,
+				amount: 10 * dollar(DOT_CURRENCY),
+			}));
+			// zero balance currency skipped
+			assert_eq!(migrated_events(), 2);
+
+			// repeated call is a no-op
+			assert_ok!(AquaDao::migrate_legacy_proceeds(
+				RawOrigin::Root.into(),
+				vec![AUSD_CURRENCY, DOT_CURRENCY]
+			));
+			assert_eq!(
+				Currencies::free_balance(AUSD_CURRENCY, &DAO_ACCOUNT),
+				1_000 * dollar(AUSD_CURRENCY)
+			);
+			assert_eq!(
+				Currencies::free_balance(DOT_CURRENCY, &DAO_ACCOUNT),
+				10 * dollar(DOT_CURRENCY)
 			);
+			assert_eq!(migrated_events(), 2);
 		});
 }
+
+fn migrated_events() -> usize {
+	System::events()
+		.into_iter()
+		.filter(|r| matches!(r.event, Event::AquaDao(crate::Event::LegacyProceedsMigrated { .. })))
+		.count()
+}
+
+#[test]
+fn call_indices_are_stable() {
+	let calls: Vec<(crate::Call<Runtime>, u8)> = vec![
+		(
+			crate::Call::create_subscription {
+				currency_id: AUSD_CURRENCY,
+				vesting_period: 0,
+				min_amount: 0,
+				min_ratio: Ratio::one(),
+				amount: 0,
+				discount: Default::default(),
+				params: Default::default(),
+			},
+			0,
+		),
+		(
+			crate::Call::update_subscription {
+				subscription_id: 0,
+				vesting_period: None,
+				min_amount: None,
+				min_ratio: None,
+				amount: None,
+				discount: None,
+				update: Default::default(),
+			},
+			1,
+		),
+		(crate::Call::close_subscription { subscription_id: 0 }, 2),
+		(
+			crate::Call::subscribe {
+				subscription_id: 0,
+				payment_amount: 0,
+				min_target_amount: 0,
+				max_price: None,
+			},
+			3,
+		),
+		(crate::Call::migrate_legacy_proceeds { currencies: vec![] }, 4),
+		(crate::Call::pause_subscription { subscription_id: 0 }, 5),
+		(crate::Call::resume_subscription { subscription_id: 0 }, 6),
+		(
+			crate::Call::add_to_whitelist {
+				subscription_id: 0,
+				accounts: vec![],
+			},
+			7,
+		),
+		(
+			crate::Call::remove_from_whitelist {
+				subscription_id: 0,
+				accounts: vec![],
+			},
+			8,
+		),
+		(
+			crate::Call::subscribe_for {
+				beneficiary: ALICE,
+				subscription_id: 0,
+				payment_amount: 0,
+				min_target_amount: 0,
+				max_price: None,
+			},
+			9,
+		),
+		(
+			crate::Call::subscribe_batch {
+				legs: Default::default(),
+			},
+			10,
+		),
+		(crate::Call::pause_all {}, 11),
+		(crate::Call::resume_all {}, 12),
+		(crate::Call::set_mint_cap { cap: None }, 13),
+	];
+	for (call, index) in calls {
+		assert_eq!(call.encode()[0], index, "{:?}", call);
+	}
+}
+
+#[test]
+fn storage_is_bounded() {
+	use frame_support::traits::StorageInfoTrait;
+
+	for info in <AquaDao as StorageInfoTrait>::storage_info() {
+		assert!(info.max_size.is_some(), "unbounded storage {:?}", info);
+	}
+
+	assert_eq!(Discount::<BlockNumber>::max_encoded_len(), 4 * 16 + 8);
+	assert_eq!(SubscriptionState::<BlockNumber>::max_encoded_len(), 3 * 16 + 8 + 2);
+	assert_eq!(SubscriptionStatus::<BlockNumber>::max_encoded_len(), 1 + 8);
+	// vesting tiers: compact length and up to 4 `(Balance, BlockNumber)` tiers
+	let vesting_tiers = 1 + 4 * (16 + 8);
+	// vesting_period, min_amount, min_ratio, amount, denomination, bypass_filter, start_block,
+	// end_block, permissioned, payment_dest and per_account_cooldown
+	let others = 8 + 3 * 16 + 1 + 1 + 8 + 9 + 1 + 32 + 9;
+	assert_eq!(
+		SubscriptionOf::<Runtime>::max_encoded_len(),
+		CurrencyId::max_encoded_len() + vesting_tiers + 72 + 9 + 58 + others
+	);
+}
+
+/// A subscription in storage version 0 encoding, and its translation into the current layout.
+fn storage_version_0_subscription() -> (Vec<u8>, SubscriptionOf<Runtime>) {
+	use sp_runtime::traits::Bounded;
+
+	// currency_id, vesting_period, min_amount, min_ratio, amount, discount and state
+	let discount = (
+		DiscountRate::saturating_from_rational(2, 10),
+		10 as BlockNumber,
+		DiscountRate::saturating_from_rational(1, 1_000),
+		DiscountRate::saturating_from_rational(-1, 1_000_000),
+	);
+	let state = (
+		dollar(ADAO_CURRENCY) * 5,
+		20 as BlockNumber,
+		DiscountRate::saturating_from_rational(1, 100),
+	);
+	let raw = (
+		AUSD_CURRENCY,
+		1_000 as BlockNumber,
+		dollar(ADAO_CURRENCY) * 10,
+		Ratio::saturating_from_rational(1, 10),
+		dollar(ADAO_CURRENCY) * UNITS,
+		discount,
+		state,
+	)
+		.encode();
+
+	let subscription = Subscription {
+		currency_id: AUSD_CURRENCY,
+		vesting_period: 1_000,
+		vesting_tiers: Default::default(),
+		min_amount: dollar(ADAO_CURRENCY) * 10,
+		min_ratio: Ratio::saturating_from_rational(1, 10),
+		amount: dollar(ADAO_CURRENCY) * UNITS,
+		denomination: AmountDenomination::Adao,
+		discount: Discount {
+			max: DiscountRate::saturating_from_rational(2, 10),
+			min: DiscountRate::min_value(),
+			interval: 10,
+			inc_on_idle: DiscountRate::saturating_from_rational(1, 1_000),
+			dec_per_unit: DiscountRate::saturating_from_rational(-1, 1_000_000),
+		},
+		bypass_filter: false,
+		status: SubscriptionStatus::Active,
+		start_block: 0,
+		end_block: None,
+		permissioned: false,
+		payment_dest: DAO_ACCOUNT,
+		per_account_cooldown: None,
+		state: SubscriptionState {
+			total_sold: dollar(ADAO_CURRENCY) * 5,
+			total_payment: 0,
+			last_sold_at: 20,
+			last_discount: DiscountRate::saturating_from_rational(1, 100),
+			sold_out: false,
+			discount_cap_reached: false,
+		},
+	};
+	(raw, subscription)
+}
+
+#[test]
+fn translate_storage_version_0_subscription_works() {
+	use codec::DecodeAll;
+
+	let (raw, subscription) = storage_version_0_subscription();
+	let old = migrations::OldSubscription::<BlockNumber>::decode_all(&mut &raw[..]).unwrap();
+	assert_eq!(migrations::translate_subscription::<Runtime>(old), subscription);
+
+	// the current layout doesn't decode the old encoding
+	assert!(SubscriptionOf::<Runtime>::decode_all(&mut &raw[..]).is_err());
+}
+
+#[test]
+fn index_active_subscriptions_works() {
+	use frame_support::{
+		storage::{storage_prefix, unhashed},
+		traits::PalletInfoAccess,
+		StorageHasher,
+	};
+
+	ExtBuilder::default().build().execute_with(|| {
+		let (raw, _) = storage_version_0_subscription();
+		let prefix = storage_prefix(AquaDao::name().as_bytes(), b"Subscriptions");
+		for id in [3u32, 1] {
+			unhashed::put_raw(&[&prefix[..], &Twox64Concat::hash(&id.encode())].concat(), &raw);
+		}
+		StorageVersion::new(0).put::<AquaDao>();
+
+		migrations::v1::migrate::<Runtime>();
+
+		assert_eq!(StorageVersion::get::<AquaDao>(), 1);
+		assert_eq!(
+			unhashed::get::<Vec<u32>>(&ActiveSubscriptions::<Runtime>::hashed_key()),
+			Some(vec![1, 3])
+		);
+	});
+}
+
+#[test]
+fn index_active_subscriptions_skipped_if_exceeding_bound() {
+	use frame_support::{
+		storage::{storage_prefix, unhashed},
+		traits::PalletInfoAccess,
+		StorageHasher,
+	};
+
+	ExtBuilder::default().build().execute_with(|| {
+		let (raw, _) = storage_version_0_subscription();
+		let prefix = storage_prefix(AquaDao::name().as_bytes(), b"Subscriptions");
+		for id in 0..=MaxActiveSubscriptions::get() {
+			unhashed::put_raw(&[&prefix[..], &Twox64Concat::hash(&id.encode())].concat(), &raw);
+		}
+		StorageVersion::new(0).put::<AquaDao>();
+
+		migrations::v1::migrate::<Runtime>();
+		migrations::v2::migrate::<Runtime>();
+
+		// nothing is migrated, instead of dropping subscriptions from the index
+		assert_eq!(StorageVersion::get::<AquaDao>(), 0);
+		assert!(!unhashed::exists(&ActiveSubscriptions::<Runtime>::hashed_key()));
+		assert_eq!(unhashed::get_raw(&[&prefix[..], &Twox64Concat::hash(&0u32.encode())].concat()), Some(raw));
+	});
+}
+
+#[test]
+fn migrate_subscription_id_to_u64_works() {
+	use frame_support::{
+		storage::{migration::put_storage_value, storage_prefix, unhashed},
+		traits::PalletInfoAccess,
+		StorageHasher,
+	};
+
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let (raw, subscription) = storage_version_0_subscription();
+
+		// v1 storage with `u32` subscription ids, and subscriptions in storage version 0 layout
+		let module = AquaDao::name().as_bytes();
+		let old_key = |id: u32| Twox64Concat::hash(&id.encode());
+		let old_subscription_key = |id: u32| [&storage_prefix(module, b"Subscriptions")[..], &old_key(id)].concat();
+		unhashed::put_raw(&old_subscription_key(7), &raw);
+		// not decodable, in neither layout
+		unhashed::put_raw(&old_subscription_key(9), &[1, 2, 3]);
+		let mut whitelist_key = old_key(7);
+		whitelist_key.extend(Twox64Concat::hash(&ALICE.encode()));
+		put_storage_value(module, b"SubscriptionWhitelist", &whitelist_key, ());
+		unhashed::put(&SubscriptionIndex::<Runtime>::hashed_key(), &8u32);
+		unhashed::put(&ActiveSubscriptions::<Runtime>::hashed_key(), &vec![7u32]);
+		StorageVersion::new(1).put::<AquaDao>();
+
+		migrations::v2::migrate::<Runtime>();
+
+		assert_eq!(StorageVersion::get::<AquaDao>(), 2);
+		assert_eq!(AquaDao::subscriptions(7), Some(subscription));
+		assert!(!unhashed::exists(&old_subscription_key(7)));
+		// undecodable entries are kept as is, rather than dropped
+		assert_eq!(unhashed::get_raw(&old_subscription_key(9)), Some(vec![1, 2, 3]));
+		assert!(!Subscriptions::<Runtime>::contains_key(9));
+		assert!(SubscriptionWhitelist::<Runtime>::contains_key(7, ALICE));
+		assert_eq!(AquaDao::subscription_index(), 8);
+		assert_eq!(AquaDao::active_subscriptions().into_inner(), vec![7]);
+
+		// the next subscription takes a fresh id
+		assert_ok!(create_default_subscription());
+		assert!(AquaDao::subscriptions(8).is_some());
+	});
+}
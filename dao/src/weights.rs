@@ -26,41 +26,118 @@ use sp_std::marker::PhantomData;
 
 /// Weight functions needed for ecosystem_aqua_dao.
 pub trait WeightInfo {
-	fn create_subscription() -> Weight;
+	fn create_subscription(n: u32) -> Weight;
 	fn update_subscription() -> Weight;
-	fn close_subscription() -> Weight;
+	fn close_subscription(n: u32) -> Weight;
 	fn subscribe() -> Weight;
+	fn migrate_legacy_proceeds(n: u32) -> Weight;
+	fn pause_subscription() -> Weight;
+	fn resume_subscription() -> Weight;
+	fn on_initialize(n: u32, m: u32) -> Weight;
+	fn add_to_whitelist(n: u32) -> Weight;
+	fn remove_from_whitelist(n: u32) -> Weight;
+	fn subscribe_for() -> Weight;
+	fn record_adao_price() -> Weight;
+	fn pause_all() -> Weight;
+	fn resume_all() -> Weight;
+	fn set_mint_cap() -> Weight;
 }
 
 /// Weights for ecosystem_aqua_dao using the Acala node and recommended hardware.
 pub struct AcalaWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for AcalaWeight<T> {
-	fn create_subscription() -> Weight {
+	fn create_subscription(_n: u32) -> Weight {
 		0
 	}
 	fn update_subscription() -> Weight {
 		0
 	}
-	fn close_subscription() -> Weight {
+	fn close_subscription(_n: u32) -> Weight {
 		0
 	}
 	fn subscribe() -> Weight {
 		0
 	}
+	fn migrate_legacy_proceeds(_n: u32) -> Weight {
+		0
+	}
+	fn pause_subscription() -> Weight {
+		0
+	}
+	fn resume_subscription() -> Weight {
+		0
+	}
+	fn on_initialize(_n: u32, _m: u32) -> Weight {
+		0
+	}
+	fn add_to_whitelist(_n: u32) -> Weight {
+		0
+	}
+	fn remove_from_whitelist(_n: u32) -> Weight {
+		0
+	}
+	fn subscribe_for() -> Weight {
+		0
+	}
+	fn record_adao_price() -> Weight {
+		0
+	}
+	fn pause_all() -> Weight {
+		0
+	}
+	fn resume_all() -> Weight {
+		0
+	}
+	fn set_mint_cap() -> Weight {
+		0
+	}
 }
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
-	fn create_subscription() -> Weight {
+	fn create_subscription(_n: u32) -> Weight {
 		0
 	}
 	fn update_subscription() -> Weight {
 		0
 	}
-	fn close_subscription() -> Weight {
+	fn close_subscription(_n: u32) -> Weight {
 		0
 	}
 	fn subscribe() -> Weight {
 		0
 	}
+	fn migrate_legacy_proceeds(_n: u32) -> Weight {
+		0
+	}
+	fn pause_subscription() -> Weight {
+		0
+	}
+	fn resume_subscription() -> Weight {
+		0
+	}
+	fn on_initialize(_n: u32, _m: u32) -> Weight {
+		0
+	}
+	fn add_to_whitelist(_n: u32) -> Weight {
+		0
+	}
+	fn remove_from_whitelist(_n: u32) -> Weight {
+		0
+	}
+	fn subscribe_for() -> Weight {
+		0
+	}
+	fn record_adao_price() -> Weight {
+		0
+	}
+	fn pause_all() -> Weight {
+		0
+	}
+	fn resume_all() -> Weight {
+		0
+	}
+	fn set_mint_cap() -> Weight {
+		0
+	}
 }
@@ -0,0 +1,50 @@
+// This file is part of Acala.
+
+// Copyright (C) 2022 Acala Foundation.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Runtime API definition for Aqua Staked Token module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The `too_many_arguments` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::too_many_arguments)]
+// The `unnecessary_mut_passed` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::unnecessary_mut_passed)]
+
+use acala_primitives::Balance;
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait AquaStakedTokenApi<AccountId, MintReason, MintPreview> where
+		AccountId: Codec,
+		MintReason: Codec,
+		MintPreview: Codec,
+	{
+		/// The pallet account which holds staked ADAO.
+		fn staked_token_pallet_account() -> AccountId;
+
+		/// Cumulative ADAO minted by the pallet, by reason.
+		fn mint_ledger() -> Vec<(MintReason, Balance)>;
+
+		/// Total SDAO locked in vesting.
+		fn total_vesting() -> Balance;
+
+		/// Preview the ADAO minted and SDAO received of a subscription mint, at the current
+		/// exchange rate.
+		fn preview_mint_for_subscription(subscription_amount: Balance) -> Option<MintPreview>;
+	}
+}
@@ -21,9 +21,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use frame_support::{
+	log,
 	pallet_prelude::*,
 	parameter_types,
-	traits::{EnsureOrigin, Get, LockIdentifier},
+	storage::with_transaction_result,
+	traits::{Contains, EnsureOrigin, Get, LockIdentifier},
 	transactional, PalletId,
 };
 use frame_system::pallet_prelude::*;
@@ -31,7 +33,7 @@ use sp_runtime::{
 	traits::{AccountIdConversion, BlockNumberProvider, CheckedAdd, CheckedSub, One, Saturating, Zero},
 	ArithmeticError, FixedPointNumber,
 };
-use sp_std::result::Result;
+use sp_std::{prelude::*, result::Result};
 
 use orml_traits::{Happened, MultiCurrency, MultiLockableCurrency};
 
@@ -41,7 +43,7 @@ use acala_primitives::{
 	CurrencyId::{self, Token},
 	TokenSymbol::*,
 };
-use ecosystem_aqua_dao::StakedTokenManager;
+use ecosystem_aqua_dao::{MintPreview, StakedTokenManager};
 use module_support::{Rate, Ratio};
 
 mod mock;
@@ -52,6 +54,36 @@ pub use weights::WeightInfo;
 
 pub use module::*;
 
+/// The reason of protocol ADAO minting.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum MintReason {
+	/// Periodic inflation.
+	Inflation,
+	/// Minted for DAO subscriptions.
+	Subscription,
+}
+
+/// Where the non-burned part of unstake fees goes.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum FeeRouting {
+	/// Transfer the fee as ADAO to `FeeDestAccount`.
+	ToAccount,
+	/// Keep the fee ADAO in the pool and mint the equivalent SDAO as treasury staking reward.
+	ToTreasuryStaking,
+}
+
+/// Treasury staking deposit, for auto restaking claimed SDAO.
+pub trait TreasuryStakingDeposit<AccountId> {
+	/// Deposit `amount` of SDAO of `who` into treasury staking, credited to `who`.
+	fn deposit(who: &AccountId, amount: Balance) -> DispatchResult;
+}
+
+impl<AccountId> TreasuryStakingDeposit<AccountId> for () {
+	fn deposit(_who: &AccountId, _amount: Balance) -> DispatchResult {
+		Err(DispatchError::Other("No treasury staking"))
+	}
+}
+
 #[frame_support::pallet]
 pub mod module {
 	use super::*;
@@ -72,6 +104,10 @@ pub mod module {
 		/// Inflate rate per `n` block: (n, rate)
 		type InflationRatePerNBlock: Get<(Self::BlockNumber, Rate)>;
 
+		/// No inflation before this block.
+		#[pallet::constant]
+		type InflationStartBlock: Get<Self::BlockNumber>;
+
 		/// Treasury share of minted/inflated ADAO token.
 		#[pallet::constant]
 		type TreasuryShare: Get<Ratio>;
@@ -91,6 +127,10 @@ pub mod module {
 		#[pallet::constant]
 		type FeeDestAccount: Get<Self::AccountId>;
 
+		/// Where the non-burned part of unstake fees goes.
+		#[pallet::constant]
+		type FeeRouting: Get<FeeRouting>;
+
 		/// DAO account.
 		#[pallet::constant]
 		type DaoAccount: Get<Self::AccountId>;
@@ -111,6 +151,15 @@ pub mod module {
 		/// inflation. The reward amount is based on `T::TreasuryShare`.
 		type OnDepositReward: Happened<(CurrencyId, Balance)>;
 
+		/// ADAO minted by other pallets, e.g. on POL rebalance. Used in the issuance invariant.
+		type ExternalMinted: Get<Balance>;
+
+		/// Treasury staking, where claimed SDAO is deposited to if auto restake enabled.
+		type TreasuryStaking: TreasuryStakingDeposit<Self::AccountId>;
+
+		/// Accounts exempt from `MaxUnstakePerCall`.
+		type UnstakeLimitExempt: Contains<Self::AccountId>;
+
 		type WeightInfo: WeightInfo;
 	}
 
@@ -119,6 +168,51 @@ pub mod module {
 	#[pallet::getter(fn unstake_fee_rate)]
 	pub type UnstakeFeeRate<T> = StorageValue<_, Rate, ValueQuery>;
 
+	/// The ratio of unstake fee to be burned. The rest goes to fee destination account.
+	#[pallet::storage]
+	#[pallet::getter(fn fee_burn_ratio)]
+	pub type FeeBurnRatio<T> = StorageValue<_, Ratio, ValueQuery>;
+
+	/// Cumulative ADAO minted by this pallet, by reason.
+	///
+	/// MintLedger: map MintReason => Balance
+	#[pallet::storage]
+	#[pallet::getter(fn mint_ledger)]
+	pub type MintLedger<T> = StorageMap<_, Twox64Concat, MintReason, Balance, ValueQuery>;
+
+	/// Cumulative ADAO burned by this pallet.
+	#[pallet::storage]
+	#[pallet::getter(fn total_burned)]
+	pub type TotalBurned<T> = StorageValue<_, Balance, ValueQuery>;
+
+	/// Whether the pallet is paused. Staking, unstaking, claiming and inflation are disabled if
+	/// paused.
+	#[pallet::storage]
+	#[pallet::getter(fn paused)]
+	pub type Paused<T> = StorageValue<_, bool, ValueQuery>;
+
+	/// ADAO total issuance at genesis.
+	#[pallet::storage]
+	#[pallet::getter(fn genesis_issuance)]
+	pub type GenesisIssuance<T> = StorageValue<_, Balance, ValueQuery>;
+
+	/// Total SDAO in vesting, the sum of all vesting ledger totals.
+	#[pallet::storage]
+	#[pallet::getter(fn total_vesting)]
+	pub type TotalVesting<T> = StorageValue<_, Balance, ValueQuery>;
+
+	/// Whether claimed SDAO of an account is restaked into treasury staking automatically.
+	///
+	/// AutoRestake: map AccountId => bool
+	#[pallet::storage]
+	#[pallet::getter(fn auto_restake)]
+	pub type AutoRestake<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, bool, ValueQuery>;
+
+	/// Maximum SDAO amount of a single unstake, zero means unlimited.
+	#[pallet::storage]
+	#[pallet::getter(fn max_unstake_per_call)]
+	pub type MaxUnstakePerCall<T> = StorageValue<_, Balance, ValueQuery>;
+
 	/// The Bonding ledger.
 	pub type BondingLedgerOf<T> = bonding::BondingLedgerOf<Pallet<T>>;
 
@@ -137,6 +231,16 @@ pub mod module {
 		MaxVestingChunkExceeded,
 		/// Below min Vesting amount.
 		BelowMinVestingAmount,
+		/// Invalid ratio, must not be greater than one.
+		InvalidRatio,
+		/// The pallet is paused.
+		Paused,
+		/// Vesting not matured yet.
+		VestingNotMatured,
+		/// Unstake amount exceeds `MaxUnstakePerCall`.
+		UnstakeTooLarge,
+		/// No SDAO staked to distribute to.
+		NoStakers,
 	}
 
 	#[pallet::event]
@@ -159,10 +263,74 @@ pub mod module {
 		UnstakeFeeRateUpdated {
 			rate: Rate,
 		},
+		FeeBurnRatioUpdated {
+			ratio: Ratio,
+		},
+		FeeCharged {
+			who: T::AccountId,
+			burned: Balance,
+			to_fee_dest: Balance,
+		},
+		/// Unstake fee kept in the pool, and `staked` SDAO minted as treasury staking reward.
+		FeeRoutedToTreasuryStaking {
+			who: T::AccountId,
+			amount: Balance,
+			staked: Balance,
+		},
 		VestingAdded {
 			who: T::AccountId,
 			amount: Balance,
 		},
+		PausedSet {
+			paused: bool,
+		},
+		PoolToppedUp {
+			amount: Balance,
+			new_rate: Rate,
+		},
+		AutoRestakeSet {
+			who: T::AccountId,
+			enabled: bool,
+		},
+		/// Claimed SDAO deposited into treasury staking.
+		Restaked {
+			who: T::AccountId,
+			amount: Balance,
+		},
+		/// Unstake payout clamped to keep the pallet account alive. The unpaid `redeem - paid`
+		/// ADAO stays in the pallet account as dust.
+		UnstakeClamped {
+			who: T::AccountId,
+			redeem: Balance,
+			paid: Balance,
+		},
+		MaxUnstakePerCallSet {
+			amount: Balance,
+		},
+		/// `amount` of ADAO from `from` distributed to all SDAO holders.
+		DistributedToStakers {
+			from: T::AccountId,
+			amount: Balance,
+			new_rate: Rate,
+		},
+		/// A matured vesting chunk of `amount` SDAO unlocking at `unlock_block` was claimed. Emitted
+		/// for every chunk alongside the aggregate `Claimed` event.
+		VestingChunkReleased {
+			who: T::AccountId,
+			amount: Balance,
+			unlock_block: T::BlockNumber,
+		},
+	}
+
+	#[pallet::genesis_config]
+	#[derive(Default)]
+	pub struct GenesisConfig {}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			GenesisIssuance::<T>::put(T::Currency::total_issuance(Token(ADAO)));
+		}
 	}
 
 	#[pallet::pallet]
@@ -171,23 +339,46 @@ pub mod module {
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
-		/// Inflating ADAO tokens periodically.
+		/// Inflating ADAO tokens periodically, from `T::InflationStartBlock` and if not paused.
 		fn on_initialize(now: T::BlockNumber) -> Weight {
+			if now < T::InflationStartBlock::get() || Self::paused() {
+				return <T as Config>::WeightInfo::on_initialize_without_inflation();
+			}
+
 			let (n, rate) = T::InflationRatePerNBlock::get();
 			// `rem_euclid` should be preferred but not supported by `BlockNumber`. `n`
 			// can't be zero in runtime config so it's safe to use modulo `%`.
-			if (now % n).is_zero() {
-				let total = T::Currency::total_issuance(Token(ADAO));
-				if let Some(inflation_amount) = rate.checked_mul_int(total) {
-					let _ = Self::inflate(inflation_amount);
+			if rate.is_zero() || !(now % n).is_zero() {
+				return <T as Config>::WeightInfo::on_initialize_without_inflation();
+			}
+
+			let total = T::Currency::total_issuance(Token(ADAO));
+			// a failed inflation reverts its partial mints, and is retried on the next period
+			let maybe_work = rate.checked_mul_int(total).and_then(|inflation_amount| {
+				with_transaction_result(|| Self::inflate(inflation_amount))
+					.map_err(|e| log::warn!(target: "aqua-staked-token", "Inflation failed: {:?}", e))
+					.ok()
+			});
+			let mut weight = <T as Config>::WeightInfo::on_initialize();
+			if let Some((reward_deposited, new_accounts)) = maybe_work {
+				if reward_deposited {
+					weight = weight.saturating_add(<T as Config>::WeightInfo::on_deposit_reward());
 				}
-				<T as Config>::WeightInfo::on_initialize()
-			} else {
-				<T as Config>::WeightInfo::on_initialize_without_inflation()
+				weight = weight.saturating_add(
+					<T as Config>::WeightInfo::on_initialize_new_account().saturating_mul(new_accounts.into()),
+				);
 			}
+			weight
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			Self::do_try_state()
 		}
 	}
 
+	// Call indices follow declaration order, and are pinned by the `call_indices_are_stable` test.
+	// Append new calls at the end, never insert or reorder.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Stake given `amount` of ADAO tokens and receive SDAO tokens.
@@ -195,6 +386,7 @@ pub mod module {
 		#[transactional]
 		pub fn stake(origin: OriginFor<T>, amount: Balance) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(!Self::paused(), Error::<T>::Paused);
 
 			if amount == Zero::zero() {
 				return Ok(());
@@ -213,25 +405,13 @@ pub mod module {
 		#[transactional]
 		pub fn unstake(origin: OriginFor<T>, amount: Balance) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(!Self::paused(), Error::<T>::Paused);
 
 			if amount == Zero::zero() {
 				return Ok(());
 			}
 
-			let redeem = Self::from_staked(amount)?;
-			let fee = Self::unstake_fee_rate()
-				.checked_mul_int(redeem)
-				.ok_or(ArithmeticError::Overflow)?;
-			let received = redeem.checked_sub(fee).ok_or(ArithmeticError::Underflow)?;
-
-			// destroy SDAO
-			T::Currency::withdraw(Token(SDAO), &who, amount)?;
-			// payback ADAO
-			T::Currency::transfer(Token(ADAO), &Self::account_id(), &who, received)?;
-			// fee goes to treasury
-			T::Currency::transfer(Token(ADAO), &Self::account_id(), &T::FeeDestAccount::get(), fee)?;
-
-			Self::deposit_event(Event::<T>::Unstaked { who, amount, received });
+			Self::do_unstake(&who, amount)?;
 			Ok(())
 		}
 
@@ -240,14 +420,22 @@ pub mod module {
 		#[transactional]
 		pub fn claim(origin: OriginFor<T>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(!Self::paused(), Error::<T>::Paused);
 
 			let now = T::BlockNumberProvider::current_block_number();
+			// bounded by `MaxVestingChunks`
+			let matured: Vec<_> = Self::ledger(&who)
+				.map(|ledger| ledger.unlocking().into_iter().filter(|(_, at)| *at <= now).collect())
+				.unwrap_or_default();
 			let maybe_change = <Self as BondingController>::withdraw_unbonded(&who, now)?;
 			if let Some(change) = maybe_change {
+				TotalVesting::<T>::mutate(|total| *total = total.saturating_sub(change.change));
+				Self::deposit_chunks_released(&who, matured);
 				Self::deposit_event(Event::<T>::Claimed {
-					who,
+					who: who.clone(),
 					amount: change.change,
 				});
+				Self::try_auto_restake(&who, change.change);
 			}
 			Ok(())
 		}
@@ -261,6 +449,136 @@ pub mod module {
 			Self::deposit_event(Event::<T>::UnstakeFeeRateUpdated { rate });
 			Ok(())
 		}
+
+		/// Claim the matured SDAO token vesting chunk(s) unlocking at `unlock_at`. Other chunks are
+		/// left untouched.
+		#[pallet::weight(<T as Config>::WeightInfo::claim_chunk())]
+		#[transactional]
+		pub fn claim_chunk(origin: OriginFor<T>, unlock_at: T::BlockNumber) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!Self::paused(), Error::<T>::Paused);
+
+			let now = T::BlockNumberProvider::current_block_number();
+			ensure!(unlock_at <= now, Error::<T>::VestingNotMatured);
+
+			let ledger = VestingLedger::<T>::get(&who).ok_or(Error::<T>::VestingNotFound)?;
+			let (claimed, remaining): (Vec<_>, Vec<_>) =
+				ledger.unlocking().into_iter().partition(|(_, at)| *at == unlock_at);
+			let amount = claimed
+				.iter()
+				.fold(Zero::zero(), |acc: Balance, (value, _)| acc.saturating_add(*value));
+			ensure!(!amount.is_zero(), Error::<T>::VestingNotFound);
+
+			// rebuild the ledger with remaining chunks only
+			let remaining_total = remaining
+				.iter()
+				.fold(ledger.active(), |acc, (value, _)| acc.saturating_add(*value));
+			let new_ledger = remaining.into_iter().try_fold(
+				BondingLedgerOf::<T>::default()
+					.bond(remaining_total)
+					.map_err(<Self as BondingController>::convert_error)?,
+				|l, (value, at)| {
+					l.unbond(value, at)
+						.map(|(l, _)| l)
+						.map_err(<Self as BondingController>::convert_error)
+				},
+			)?;
+
+			<Self as BondingController>::apply_ledger(&who, &new_ledger)?;
+			if new_ledger.is_empty() {
+				VestingLedger::<T>::remove(&who);
+			} else {
+				VestingLedger::<T>::insert(&who, new_ledger);
+			}
+			TotalVesting::<T>::mutate(|total| *total = total.saturating_sub(amount));
+
+			Self::deposit_chunks_released(&who, claimed);
+			Self::deposit_event(Event::<T>::Claimed { who: who.clone(), amount });
+			Self::try_auto_restake(&who, amount);
+			Ok(())
+		}
+
+		/// Enable or disable auto restaking claimed SDAO into treasury staking.
+		#[pallet::weight(<T as Config>::WeightInfo::set_auto_restake())]
+		#[transactional]
+		pub fn set_auto_restake(origin: OriginFor<T>, enabled: bool) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			if enabled {
+				AutoRestake::<T>::insert(&who, true);
+			} else {
+				AutoRestake::<T>::remove(&who);
+			}
+			Self::deposit_event(Event::<T>::AutoRestakeSet { who, enabled });
+			Ok(())
+		}
+
+		/// Pause or unpause the pallet. Requires `T::UpdateParamsOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::set_paused())]
+		#[transactional]
+		pub fn set_paused(origin: OriginFor<T>, paused: bool) -> DispatchResult {
+			T::UpdateParamsOrigin::ensure_origin(origin)?;
+			Paused::<T>::put(paused);
+			Self::deposit_event(Event::<T>::PausedSet { paused });
+			Ok(())
+		}
+
+		/// Update the ratio of unstake fee to be burned. Requires `T::UpdateParamsOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::update_fee_burn_ratio())]
+		#[transactional]
+		pub fn update_fee_burn_ratio(origin: OriginFor<T>, ratio: Ratio) -> DispatchResult {
+			T::UpdateParamsOrigin::ensure_origin(origin)?;
+			ensure!(ratio <= Ratio::one(), Error::<T>::InvalidRatio);
+			FeeBurnRatio::<T>::put(ratio);
+			Self::deposit_event(Event::<T>::FeeBurnRatioUpdated { ratio });
+			Ok(())
+		}
+
+		/// Top up the staking pool with `amount` of ADAO from `T::DaoAccount`, raising the ADAO/SDAO
+		/// exchange rate. Requires `T::UpdateParamsOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::top_up_pool())]
+		#[transactional]
+		pub fn top_up_pool(origin: OriginFor<T>, amount: Balance) -> DispatchResult {
+			T::UpdateParamsOrigin::ensure_origin(origin)?;
+			T::Currency::transfer(Token(ADAO), &T::DaoAccount::get(), &Self::account_id(), amount)?;
+			Self::deposit_event(Event::<T>::PoolToppedUp {
+				amount,
+				new_rate: Self::exchange_rate(),
+			});
+			Ok(())
+		}
+
+		/// Set the maximum SDAO amount of a single unstake, zero means unlimited. Requires
+		/// `T::UpdateParamsOrigin` origin.
+		#[pallet::weight(<T as Config>::WeightInfo::set_max_unstake_per_call())]
+		#[transactional]
+		pub fn set_max_unstake_per_call(origin: OriginFor<T>, amount: Balance) -> DispatchResult {
+			T::UpdateParamsOrigin::ensure_origin(origin)?;
+			MaxUnstakePerCall::<T>::put(amount);
+			Self::deposit_event(Event::<T>::MaxUnstakePerCallSet { amount });
+			Ok(())
+		}
+
+		/// Distribute `amount` of ADAO to all SDAO holders pro rata, by transferring it into the
+		/// staking pool to raise the ADAO/SDAO exchange rate. Callable by anyone, for instance to
+		/// share protocol revenue.
+		#[pallet::weight(<T as Config>::WeightInfo::distribute_to_stakers())]
+		#[transactional]
+		pub fn distribute_to_stakers(origin: OriginFor<T>, amount: Balance) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			// with no SDAO issued, the ADAO would go to the next staker alone
+			ensure!(
+				!T::Currency::total_issuance(Token(SDAO)).is_zero(),
+				Error::<T>::NoStakers
+			);
+
+			T::Currency::transfer(Token(ADAO), &who, &Self::account_id(), amount)?;
+			Self::deposit_event(Event::<T>::DistributedToStakers {
+				from: who,
+				amount,
+				new_rate: Self::exchange_rate(),
+			});
+			Ok(())
+		}
 	}
 }
 
@@ -268,7 +586,9 @@ type BalanceResult = Result<Balance, DispatchError>;
 
 impl<T: Config> Pallet<T> {
 	/// Inflate DAO token.
-	fn inflate(amount: Balance) -> DispatchResult {
+	/// Mint inflation of `amount` ADAO. Returns whether the treasury reward was deposited, and the
+	/// number of accounts created by the deposits.
+	fn inflate(amount: Balance) -> Result<(bool, u32), DispatchError> {
 		// fixed_share = treasury_share + dao_share
 		let fixed_share = T::TreasuryShare::get()
 			.checked_add(&T::DaoShare::get())
@@ -288,20 +608,32 @@ impl<T: Config> Pallet<T> {
 		let dao_mint = T::DaoShare::get()
 			.checked_mul_int(mint)
 			.ok_or(ArithmeticError::Overflow)?;
-		let treasury_staked = Self::to_staked(treasury_mint)?;
-		let dao_staked = Self::to_staked(dao_mint)?;
+		// convert all shares by the pre-mint exchange rate
+		let rate = Self::exchange_rate();
+		let treasury_staked = Self::to_staked_at(rate, treasury_mint)?;
+		let dao_staked = Self::to_staked_at(rate, dao_mint)?;
+
+		let is_new = |currency_id: CurrencyId, who: &T::AccountId, amount: Balance| -> u32 {
+			(!amount.is_zero() && T::Currency::total_balance(currency_id, who).is_zero()).into()
+		};
+		let new_accounts = is_new(Token(ADAO), &Self::account_id(), mint)
+			.saturating_add(is_new(Token(SDAO), &T::DaoAccount::get(), dao_staked))
+			.saturating_add(is_new(Token(SDAO), &T::RewardDestAccount::get(), treasury_staked));
 
 		// mint
-		T::Currency::deposit(Token(ADAO), &Self::account_id(), mint)?;
+		Self::mint_adao(MintReason::Inflation, &Self::account_id(), mint)?;
 
 		// stake the treasury and DAO share
 		T::Currency::deposit(Token(SDAO), &T::DaoAccount::get(), dao_staked)?;
 		T::Currency::deposit(Token(SDAO), &T::RewardDestAccount::get(), treasury_staked)?;
-		T::OnDepositReward::happened(&(Token(SDAO), treasury_staked));
+		let reward_deposited = !treasury_staked.is_zero();
+		if reward_deposited {
+			T::OnDepositReward::happened(&(Token(SDAO), treasury_staked));
+		}
 
 		//TODO: add treasury principle
 
-		Ok(())
+		Ok((reward_deposited, new_accounts))
 	}
 
 	pub fn exchange_rate() -> Rate {
@@ -316,8 +648,12 @@ impl<T: Config> Pallet<T> {
 
 	/// Get SDAO token amount from given ADAO `amount`, based on exchange rate.
 	fn to_staked(amount: Balance) -> BalanceResult {
-		Self::exchange_rate()
-			.reciprocal()
+		Self::to_staked_at(Self::exchange_rate(), amount)
+	}
+
+	/// Get SDAO token amount from given ADAO `amount`, based on given exchange `rate`.
+	fn to_staked_at(rate: Rate, amount: Balance) -> BalanceResult {
+		rate.reciprocal()
 			.unwrap_or_else(|| T::DefaultExchangeRate::get().reciprocal().unwrap())
 			.checked_mul_int(amount)
 			.ok_or_else(|| ArithmeticError::Overflow.into())
@@ -330,9 +666,133 @@ impl<T: Config> Pallet<T> {
 			.ok_or_else(|| ArithmeticError::Overflow.into())
 	}
 
+	/// Emit `VestingChunkReleased` for each claimed `(amount, unlock_block)` chunk of `who`.
+	fn deposit_chunks_released(who: &T::AccountId, chunks: Vec<(Balance, T::BlockNumber)>) {
+		for (amount, unlock_block) in chunks {
+			Self::deposit_event(Event::<T>::VestingChunkReleased {
+				who: who.clone(),
+				amount,
+				unlock_block,
+			});
+		}
+	}
+
+	/// Deposit claimed `amount` of SDAO into treasury staking if `who` enabled auto restake. The
+	/// claimed SDAO stays liquid if the deposit fails.
+	fn try_auto_restake(who: &T::AccountId, amount: Balance) {
+		if !Self::auto_restake(who) {
+			return;
+		}
+		match with_transaction_result(|| T::TreasuryStaking::deposit(who, amount)) {
+			Ok(()) => Self::deposit_event(Event::<T>::Restaked {
+				who: who.clone(),
+				amount,
+			}),
+			Err(e) => log::warn!(target: "aqua-staked-token", "Auto restake failed: {:?}", e),
+		}
+	}
+
+	/// Unstake `amount` of SDAO tokens of `who`, and pay back ADAO tokens after fee.
+	fn do_unstake(who: &T::AccountId, amount: Balance) -> BalanceResult {
+		let max = Self::max_unstake_per_call();
+		ensure!(
+			max.is_zero() || amount <= max || T::UnstakeLimitExempt::contains(who),
+			Error::<T>::UnstakeTooLarge
+		);
+
+		// keep the pallet account alive, the last unstaker leaves the existential deposit as dust
+		let rate = Self::exchange_rate();
+		let redeem = Self::from_staked(amount)?;
+		let payable = T::Currency::free_balance(Token(ADAO), &Self::account_id())
+			.saturating_sub(T::Currency::minimum_balance(Token(ADAO)));
+		if redeem > payable {
+			Self::deposit_event(Event::<T>::UnstakeClamped {
+				who: who.clone(),
+				redeem,
+				paid: payable,
+			});
+		}
+		let redeem = redeem.min(payable);
+		let fee = Self::unstake_fee_rate()
+			.checked_mul_int(redeem)
+			.ok_or(ArithmeticError::Overflow)?;
+		let received = redeem.checked_sub(fee).ok_or(ArithmeticError::Underflow)?;
+		let burned = Self::fee_burn_ratio().saturating_mul_int(fee);
+		let fee_rest = fee.saturating_sub(burned);
+
+		// destroy SDAO
+		T::Currency::withdraw(Token(SDAO), who, amount)?;
+		// payback ADAO
+		T::Currency::transfer(Token(ADAO), &Self::account_id(), who, received)?;
+		// burn part of the fee, the rest goes to treasury
+		T::Currency::withdraw(Token(ADAO), &Self::account_id(), burned)?;
+		TotalBurned::<T>::mutate(|total| *total = total.saturating_add(burned));
+		let to_fee_dest = match T::FeeRouting::get() {
+			FeeRouting::ToAccount => {
+				T::Currency::transfer(Token(ADAO), &Self::account_id(), &T::FeeDestAccount::get(), fee_rest)?;
+				fee_rest
+			}
+			FeeRouting::ToTreasuryStaking => {
+				// the fee ADAO stays in the pool, minting SDAO at the pre-unstake rate keeps the rate
+				let staked = Self::to_staked_at(rate, fee_rest)?;
+				T::Currency::deposit(Token(SDAO), &T::RewardDestAccount::get(), staked)?;
+				T::OnDepositReward::happened(&(Token(SDAO), staked));
+				Self::deposit_event(Event::<T>::FeeRoutedToTreasuryStaking {
+					who: who.clone(),
+					amount: fee_rest,
+					staked,
+				});
+				Zero::zero()
+			}
+		};
+
+		Self::deposit_event(Event::<T>::Unstaked {
+			who: who.clone(),
+			amount,
+			received,
+		});
+		Self::deposit_event(Event::<T>::FeeCharged {
+			who: who.clone(),
+			burned,
+			to_fee_dest,
+		});
+		Ok(received)
+	}
+
 	pub fn account_id() -> T::AccountId {
 		T::PalletId::get().into_account()
 	}
+
+	/// Mint ADAO to `to` and record it in the mint ledger. All protocol ADAO mints must go through
+	/// here.
+	fn mint_adao(reason: MintReason, to: &T::AccountId, amount: Balance) -> DispatchResult {
+		T::Currency::deposit(Token(ADAO), to, amount)?;
+		MintLedger::<T>::mutate(reason, |total| *total = total.saturating_add(amount));
+		Ok(())
+	}
+
+	/// Check that ADAO total issuance equals genesis issuance plus recorded mints, minus burns, and
+	/// that total vesting equals the sum of vesting ledgers.
+	#[cfg(any(feature = "try-runtime", test))]
+	pub fn do_try_state() -> Result<(), &'static str> {
+		let minted = MintLedger::<T>::iter_values().fold(T::ExternalMinted::get(), |acc, x| acc.saturating_add(x));
+		let expected = Self::genesis_issuance()
+			.saturating_add(minted)
+			.saturating_sub(Self::total_burned());
+		ensure!(
+			T::Currency::total_issuance(Token(ADAO)) == expected,
+			"ADAO total issuance doesn't match the mint ledger"
+		);
+
+		let vesting = VestingLedger::<T>::iter_values().fold(Zero::zero(), |acc: Balance, ledger| {
+			acc.saturating_add(ledger.total())
+		});
+		ensure!(
+			Self::total_vesting() == vesting,
+			"Total vesting doesn't match vesting ledgers"
+		);
+		Ok(())
+	}
 }
 
 impl<T: Config> StakedTokenManager<T::AccountId, T::BlockNumber> for Pallet<T> {
@@ -340,30 +800,14 @@ impl<T: Config> StakedTokenManager<T::AccountId, T::BlockNumber> for Pallet<T> {
 	/// and received SDAO token will be in vesting.
 	#[transactional]
 	fn mint_for_subscription(who: &T::AccountId, amount: Balance, vesting_period: T::BlockNumber) -> DispatchResult {
-		// fixed_share = treasury_share + dao_share
-		let fixed_share = T::TreasuryShare::get()
-			.checked_add(&T::DaoShare::get())
-			.ok_or(ArithmeticError::Overflow)?;
-		// mint = amount / (1 - fixed_share)
-		let mint = Rate::one()
-			.checked_sub(&fixed_share)
-			.ok_or(ArithmeticError::Underflow)?
-			.reciprocal()
-			.ok_or(ArithmeticError::DivisionByZero)?
-			.checked_mul_int(amount)
-			.ok_or(ArithmeticError::Overflow)?;
-
-		let treasury_mint = T::TreasuryShare::get()
-			.checked_mul_int(mint)
-			.ok_or(ArithmeticError::Overflow)?;
-		let dao_mint = T::DaoShare::get()
-			.checked_mul_int(mint)
-			.ok_or(ArithmeticError::Overflow)?;
-		let treasury_staked = Self::to_staked(treasury_mint)?;
-		let dao_staked = Self::to_staked(dao_mint)?;
-		let staked = Self::to_staked(amount)?;
+		let MintPreview {
+			mint,
+			staked,
+			treasury_staked,
+			dao_staked,
+		} = Self::preview_mint_for_subscription(amount)?;
 
-		T::Currency::deposit(Token(ADAO), &Self::account_id(), mint)?;
+		Self::mint_adao(MintReason::Subscription, &Self::account_id(), mint)?;
 
 		// mint & stake the treasury and DAO share
 		T::Currency::deposit(Token(SDAO), who, staked)?;
@@ -375,6 +819,7 @@ impl<T: Config> StakedTokenManager<T::AccountId, T::BlockNumber> for Pallet<T> {
 		let change = <Self as BondingController>::bond(who, staked)?;
 		let unlock_at = T::BlockNumberProvider::current_block_number().saturating_add(vesting_period);
 		let _ = <Self as BondingController>::unbond(who, staked, unlock_at)?;
+		TotalVesting::<T>::mutate(|total| *total = total.saturating_add(staked));
 		if let Some(change) = change {
 			Self::deposit_event(Event::VestingAdded {
 				who: who.clone(),
@@ -386,6 +831,60 @@ impl<T: Config> StakedTokenManager<T::AccountId, T::BlockNumber> for Pallet<T> {
 
 		Ok(())
 	}
+
+	/// Preview the subscription mint of `amount` at the current exchange rate, without storage
+	/// writes. `mint_for_subscription` mints and deposits exactly these amounts.
+	fn preview_mint_for_subscription(amount: Balance) -> Result<MintPreview, DispatchError> {
+		// fixed_share = treasury_share + dao_share
+		let fixed_share = T::TreasuryShare::get()
+			.checked_add(&T::DaoShare::get())
+			.ok_or(ArithmeticError::Overflow)?;
+		// mint = amount / (1 - fixed_share)
+		let mint = Rate::one()
+			.checked_sub(&fixed_share)
+			.ok_or(ArithmeticError::Underflow)?
+			.reciprocal()
+			.ok_or(ArithmeticError::DivisionByZero)?
+			.checked_mul_int(amount)
+			.ok_or(ArithmeticError::Overflow)?;
+
+		let treasury_mint = T::TreasuryShare::get()
+			.checked_mul_int(mint)
+			.ok_or(ArithmeticError::Overflow)?;
+		let dao_mint = T::DaoShare::get()
+			.checked_mul_int(mint)
+			.ok_or(ArithmeticError::Overflow)?;
+		// convert all shares by the pre-mint exchange rate
+		let rate = Self::exchange_rate();
+		Ok(MintPreview {
+			mint,
+			staked: Self::to_staked_at(rate, amount)?,
+			treasury_staked: Self::to_staked_at(rate, treasury_mint)?,
+			dao_staked: Self::to_staked_at(rate, dao_mint)?,
+		})
+	}
+
+	/// Returns `true` if the vesting chunk unlocking in `vesting_period` blocks can be added for
+	/// `who`, either as a new chunk or merged into the last one.
+	fn can_mint_for(who: &T::AccountId, vesting_period: T::BlockNumber) -> bool {
+		let ledger = match VestingLedger::<T>::get(who) {
+			Some(ledger) => ledger,
+			None => return true,
+		};
+		let unlock_at = T::BlockNumberProvider::current_block_number().saturating_add(vesting_period);
+		let chunks = ledger.unlocking();
+		chunks.len() < T::MaxVestingChunks::get() as usize || chunks.last().map(|(_, at)| *at) == Some(unlock_at)
+	}
+
+	/// Unstake given `amount` of SDAO tokens for `who`, and pay back ADAO tokens after fee.
+	#[transactional]
+	fn unstake_for(who: &T::AccountId, amount: Balance) -> Result<Balance, DispatchError> {
+		ensure!(!Self::paused(), Error::<T>::Paused);
+		if amount.is_zero() {
+			return Ok(Zero::zero());
+		}
+		Self::do_unstake(who, amount)
+	}
 }
 
 parameter_types! {
@@ -401,11 +900,15 @@ impl<T: Config> BondingController for Pallet<T> {
 
 	type Ledger = VestingLedger<T>;
 
+	// Locks of other pallets, for instance voting, overlap with the vesting lock instead of stacking,
+	// so they are not subtracted here. Vested SDAO can be locked by them at the same time, and
+	// transfers are still limited by the strictest lock.
 	fn available_balance(who: &Self::AccountId, ledger: &BondingLedgerOf<T>) -> Balance {
 		let free_balance = T::Currency::free_balance(Token(SDAO), who);
 		free_balance.saturating_sub(ledger.total())
 	}
 
+	// Only the lock of `T::LockIdentifier` is set or removed, locks of other pallets are kept.
 	fn apply_ledger(who: &Self::AccountId, ledger: &BondingLedgerOf<T>) -> DispatchResult {
 		if ledger.is_empty() {
 			T::Currency::remove_lock(T::LockIdentifier::get(), Token(SDAO), who)
@@ -24,7 +24,7 @@ use super::*;
 use acala_primitives::{Amount, TokenSymbol};
 use frame_support::{
 	parameter_types,
-	traits::{Everything, Nothing},
+	traits::{Contains, Everything, Nothing},
 };
 use frame_system::EnsureRoot;
 use module_support::mocks::MockAddressMapping;
@@ -81,9 +81,24 @@ impl frame_system::Config for Runtime {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+thread_local! {
+	static ADAO_EXISTENTIAL_DEPOSIT: RefCell<Balance> = RefCell::new(0);
+}
+
+pub struct MockAdaoExistentialDeposit;
+impl MockAdaoExistentialDeposit {
+	pub fn set(amount: Balance) {
+		ADAO_EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = amount);
+	}
+}
+
 parameter_type_with_key! {
-	pub ExistentialDeposits: |_currency_id: CurrencyId| -> Balance {
-		Default::default()
+	pub ExistentialDeposits: |currency_id: CurrencyId| -> Balance {
+		if *currency_id == ADAO_CURRENCY {
+			ADAO_EXISTENTIAL_DEPOSIT.with(|v| *v.borrow())
+		} else {
+			Default::default()
+		}
 	};
 }
 
@@ -146,12 +161,29 @@ parameter_types!(
 	pub RewardDestAccount: AccountId = REWARD_ACCOUNT;
 	pub StakedTokenLockIdentifier: LockIdentifier = *b"aqu/vest";
 	pub MaxVestingChunks: u32 = 5;
+	pub ExternalMinted: Balance = 0;
 );
 
 thread_local! {
 	static CURRENT_BLOCK_NUMBER: RefCell<BlockNumber> = RefCell::new(1);
 }
 
+thread_local! {
+	static INFLATION_START_BLOCK: RefCell<BlockNumber> = RefCell::new(0);
+}
+
+pub struct MockInflationStartBlock;
+impl MockInflationStartBlock {
+	pub fn set(n: BlockNumber) {
+		INFLATION_START_BLOCK.with(|v| *v.borrow_mut() = n);
+	}
+}
+impl Get<BlockNumber> for MockInflationStartBlock {
+	fn get() -> BlockNumber {
+		INFLATION_START_BLOCK.with(|v| *v.borrow())
+	}
+}
+
 pub struct MockBlockNumberProvider;
 impl MockBlockNumberProvider {
 	pub fn set_block_number(n: BlockNumber) {
@@ -168,6 +200,7 @@ impl BlockNumberProvider for MockBlockNumberProvider {
 
 thread_local! {
 	static DEPOSIT_REWARD: RefCell<(CurrencyId, Balance)> = RefCell::new((SDAO_CURRENCY, 0));
+	static DEPOSIT_REWARD_CALLS: RefCell<u32> = RefCell::new(0);
 }
 
 pub struct MockOnDepositReward;
@@ -175,29 +208,98 @@ impl MockOnDepositReward {
 	pub fn deposit_reward() -> (CurrencyId, Balance) {
 		DEPOSIT_REWARD.with(|v| *v.borrow())
 	}
+
+	/// Number of `OnDepositReward` calls.
+	pub fn deposit_reward_calls() -> u32 {
+		DEPOSIT_REWARD_CALLS.with(|v| *v.borrow())
+	}
+
+	pub fn assert_deposit_reward(calls: u32, info: (CurrencyId, Balance)) {
+		assert_eq!(Self::deposit_reward_calls(), calls);
+		assert_eq!(Self::deposit_reward(), info);
+	}
 }
 impl Happened<(CurrencyId, Balance)> for MockOnDepositReward {
 	fn happened(info: &(CurrencyId, Balance)) {
+		DEPOSIT_REWARD_CALLS.with(|v| *v.borrow_mut() += 1);
 		DEPOSIT_REWARD.with(|v| *v.borrow_mut() = *info);
 	}
 }
 
+thread_local! {
+	static TREASURY_STAKING_FAILS: RefCell<bool> = RefCell::new(false);
+}
+
+/// Treasury staking holding deposited SDAO in `TREASURY_ACCOUNT`.
+pub struct MockTreasuryStaking;
+impl MockTreasuryStaking {
+	pub fn set_fails(fails: bool) {
+		TREASURY_STAKING_FAILS.with(|v| *v.borrow_mut() = fails);
+	}
+}
+impl TreasuryStakingDeposit<AccountId> for MockTreasuryStaking {
+	fn deposit(who: &AccountId, amount: Balance) -> DispatchResult {
+		<Currencies as MultiCurrency<AccountId>>::transfer(SDAO_CURRENCY, who, &TREASURY_ACCOUNT, amount)?;
+		if TREASURY_STAKING_FAILS.with(|v| *v.borrow()) {
+			return Err(DispatchError::Other("Treasury staking failed"));
+		}
+		Ok(())
+	}
+}
+
+thread_local! {
+	static FEE_ROUTING: RefCell<FeeRouting> = RefCell::new(FeeRouting::ToAccount);
+}
+
+pub struct MockFeeRouting;
+impl MockFeeRouting {
+	pub fn set(routing: FeeRouting) {
+		FEE_ROUTING.with(|v| *v.borrow_mut() = routing);
+	}
+}
+impl Get<FeeRouting> for MockFeeRouting {
+	fn get() -> FeeRouting {
+		FEE_ROUTING.with(|v| *v.borrow())
+	}
+}
+
+thread_local! {
+	static UNSTAKE_LIMIT_EXEMPT: RefCell<Vec<AccountId>> = RefCell::new(vec![]);
+}
+
+pub struct MockUnstakeLimitExempt;
+impl MockUnstakeLimitExempt {
+	pub fn add(who: AccountId) {
+		UNSTAKE_LIMIT_EXEMPT.with(|v| v.borrow_mut().push(who));
+	}
+}
+impl Contains<AccountId> for MockUnstakeLimitExempt {
+	fn contains(who: &AccountId) -> bool {
+		UNSTAKE_LIMIT_EXEMPT.with(|v| v.borrow().contains(who))
+	}
+}
+
 impl Config for Runtime {
 	type Event = Event;
 	type Currency = Currencies;
 	type UpdateParamsOrigin = EnsureRoot<AccountId>;
 	type BlockNumberProvider = MockBlockNumberProvider;
 	type InflationRatePerNBlock = InflationRatePerNBlock;
+	type InflationStartBlock = MockInflationStartBlock;
 	type TreasuryShare = TreasuryShare;
 	type DaoShare = DaoShare;
 	type DefaultExchangeRate = DaoDefaultExchangeRate;
 	type PalletId = AquaStakedTokenPalletId;
 	type FeeDestAccount = FeeDestAccount;
+	type FeeRouting = MockFeeRouting;
 	type DaoAccount = DaoAccount;
 	type RewardDestAccount = RewardDestAccount;
 	type LockIdentifier = StakedTokenLockIdentifier;
 	type MaxVestingChunks = MaxVestingChunks;
 	type OnDepositReward = MockOnDepositReward;
+	type ExternalMinted = ExternalMinted;
+	type TreasuryStaking = MockTreasuryStaking;
+	type UnstakeLimitExempt = MockUnstakeLimitExempt;
 	type WeightInfo = ();
 }
 
@@ -214,7 +316,7 @@ frame_support::construct_runtime!(
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		Tokens: orml_tokens::{Pallet, Storage, Event<T>, Config<T>},
 		Currencies: module_currencies::{Pallet, Call, Event<T>},
-		AquaStakedToken: aqua_staked_token::{Pallet, Call, Event<T>},
+		AquaStakedToken: aqua_staked_token::{Pallet, Call, Config, Storage, Event<T>},
 	}
 );
 
@@ -245,6 +347,8 @@ impl ExtBuilder {
 		.assimilate_storage(&mut t)
 		.unwrap();
 
+		GenesisBuild::<Runtime>::assimilate_storage(&aqua_staked_token::GenesisConfig::default(), &mut t).unwrap();
+
 		t.into()
 	}
 }
@@ -88,6 +88,312 @@ fn unstake_works() {
 		});
 }
 
+#[test]
+fn unstake_for_works() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), SDAO_CURRENCY, 20),
+			(AccountId::from(BOB), SDAO_CURRENCY, 30),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 500),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(AquaStakedToken::update_unstake_fee_rate(
+				RawOrigin::Root.into(),
+				Rate::saturating_from_rational(1, 10)
+			));
+
+			assert_eq!(AquaStakedToken::unstake_for(&ALICE, 0), Ok(0));
+			assert_eq!(AquaStakedToken::unstake_for(&ALICE, 10), Ok(90));
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &ALICE), 10);
+			assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &ALICE), 90);
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::Unstaked {
+				who: ALICE,
+				amount: 10,
+				received: 90,
+			}));
+		});
+}
+
+#[test]
+fn unstake_fee_burn_works() {
+	for (burn_ratio, burned, to_fee_dest) in [
+		(Ratio::zero(), 0, 10),
+		(Ratio::saturating_from_rational(1, 2), 5, 5),
+		(Ratio::one(), 10, 0),
+	] {
+		ExtBuilder::default()
+			.balances(vec![
+				(AccountId::from(ALICE), SDAO_CURRENCY, 20),
+				(AccountId::from(BOB), SDAO_CURRENCY, 30),
+				(AquaStakedToken::account_id(), ADAO_CURRENCY, 500),
+			])
+			.build()
+			.execute_with(|| {
+				System::set_block_number(1);
+
+				assert_ok!(AquaStakedToken::update_unstake_fee_rate(
+					RawOrigin::Root.into(),
+					Rate::saturating_from_rational(1, 10)
+				));
+				assert_ok!(AquaStakedToken::update_fee_burn_ratio(
+					RawOrigin::Root.into(),
+					burn_ratio
+				));
+
+				assert_ok!(AquaStakedToken::unstake(RawOrigin::Signed(ALICE).into(), 10));
+				assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &ALICE), 90);
+				assert_eq!(
+					Currencies::free_balance(ADAO_CURRENCY, &FeeDestAccount::get()),
+					to_fee_dest
+				);
+				assert_eq!(Currencies::total_issuance(ADAO_CURRENCY), 500 - burned);
+				assert_eq!(AquaStakedToken::total_burned(), burned);
+				assert_ok!(AquaStakedToken::do_try_state());
+				System::assert_has_event(Event::AquaStakedToken(crate::Event::FeeCharged {
+					who: ALICE,
+					burned,
+					to_fee_dest,
+				}));
+			});
+	}
+}
+
+#[test]
+fn unstake_fee_to_treasury_staking_works() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), SDAO_CURRENCY, 200),
+			(AccountId::from(BOB), SDAO_CURRENCY, 300),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 5_000),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			MockFeeRouting::set(FeeRouting::ToTreasuryStaking);
+
+			assert_ok!(AquaStakedToken::update_unstake_fee_rate(
+				RawOrigin::Root.into(),
+				Rate::saturating_from_rational(1, 10)
+			));
+			assert_ok!(AquaStakedToken::update_fee_burn_ratio(
+				RawOrigin::Root.into(),
+				Ratio::saturating_from_rational(1, 2)
+			));
+			let rate = AquaStakedToken::exchange_rate();
+
+			// redeem 1000 ADAO, 100 fee: 50 burned, 50 kept in the pool as 5 SDAO reward
+			assert_ok!(AquaStakedToken::unstake(RawOrigin::Signed(ALICE).into(), 100));
+			assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &ALICE), 900);
+			assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &FeeDestAccount::get()), 0);
+			assert_eq!(
+				Currencies::free_balance(ADAO_CURRENCY, &AquaStakedToken::account_id()),
+				5_000 - 900 - 50
+			);
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &RewardDestAccount::get()), 5);
+			MockOnDepositReward::assert_deposit_reward(1, (SDAO_CURRENCY, 5));
+			assert_eq!(AquaStakedToken::exchange_rate(), rate);
+			assert_ok!(AquaStakedToken::do_try_state());
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::FeeCharged {
+				who: ALICE,
+				burned: 50,
+				to_fee_dest: 0,
+			}));
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::FeeRoutedToTreasuryStaking {
+				who: ALICE,
+				amount: 50,
+				staked: 5,
+			}));
+		});
+}
+
+#[test]
+fn max_unstake_per_call_works() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), SDAO_CURRENCY, 20),
+			(AccountId::from(BOB), SDAO_CURRENCY, 30),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 500),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			// unlimited by default
+			assert_eq!(AquaStakedToken::max_unstake_per_call(), 0);
+			assert_ok!(AquaStakedToken::unstake(RawOrigin::Signed(ALICE).into(), 10));
+
+			assert_noop!(
+				AquaStakedToken::set_max_unstake_per_call(RawOrigin::Signed(ALICE).into(), 5),
+				BadOrigin
+			);
+			assert_ok!(AquaStakedToken::set_max_unstake_per_call(RawOrigin::Root.into(), 5));
+			System::assert_last_event(Event::AquaStakedToken(crate::Event::MaxUnstakePerCallSet { amount: 5 }));
+
+			assert_noop!(
+				AquaStakedToken::unstake(RawOrigin::Signed(ALICE).into(), 6),
+				Error::<Runtime>::UnstakeTooLarge
+			);
+			assert_noop!(AquaStakedToken::unstake_for(&ALICE, 6), Error::<Runtime>::UnstakeTooLarge);
+			assert_ok!(AquaStakedToken::unstake(RawOrigin::Signed(ALICE).into(), 5));
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &ALICE), 5);
+
+			// exempt accounts are not limited
+			MockUnstakeLimitExempt::add(BOB);
+			assert_ok!(AquaStakedToken::unstake(RawOrigin::Signed(BOB).into(), 30));
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &BOB), 0);
+		});
+}
+
+#[test]
+fn last_unstake_keeps_pallet_account_alive() {
+	MockAdaoExistentialDeposit::set(10);
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), SDAO_CURRENCY, 20),
+			(AccountId::from(BOB), SDAO_CURRENCY, 30),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 500),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(AquaStakedToken::unstake(RawOrigin::Signed(BOB).into(), 30));
+			assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &BOB), 300);
+
+			// the last unstaker is paid up to the existential deposit
+			assert_ok!(AquaStakedToken::unstake(RawOrigin::Signed(ALICE).into(), 20));
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &ALICE), 0);
+			assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &ALICE), 190);
+			assert_eq!(
+				Currencies::free_balance(ADAO_CURRENCY, &AquaStakedToken::account_id()),
+				10
+			);
+			assert_eq!(Currencies::total_issuance(SDAO_CURRENCY), 0);
+			assert_ok!(AquaStakedToken::do_try_state());
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::UnstakeClamped {
+				who: ALICE,
+				redeem: 200,
+				paid: 190,
+			}));
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::Unstaked {
+				who: ALICE,
+				amount: 20,
+				received: 190,
+			}));
+		});
+}
+
+#[test]
+fn update_fee_burn_ratio_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+
+		assert_noop!(
+			AquaStakedToken::update_fee_burn_ratio(RawOrigin::Signed(ALICE).into(), Ratio::one()),
+			BadOrigin
+		);
+		assert_noop!(
+			AquaStakedToken::update_fee_burn_ratio(RawOrigin::Root.into(), Ratio::saturating_from_rational(11, 10)),
+			Error::<Runtime>::InvalidRatio
+		);
+
+		assert_ok!(AquaStakedToken::update_fee_burn_ratio(
+			RawOrigin::Root.into(),
+			Ratio::one()
+		));
+		assert_eq!(AquaStakedToken::fee_burn_ratio(), Ratio::one());
+		System::assert_has_event(Event::AquaStakedToken(crate::Event::FeeBurnRatioUpdated {
+			ratio: Ratio::one(),
+		}));
+	});
+}
+
+#[test]
+fn top_up_pool_works() {
+	ExtBuilder::default()
+		// exchange rate: 1 SDAO = 10 ADAO
+		.balances(vec![
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 100),
+			(DAO_ACCOUNT, ADAO_CURRENCY, 50),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_eq!(AquaStakedToken::exchange_rate(), Rate::saturating_from_integer(10));
+
+			assert_noop!(
+				AquaStakedToken::top_up_pool(RawOrigin::Signed(ALICE).into(), 50),
+				BadOrigin
+			);
+
+			assert_ok!(AquaStakedToken::top_up_pool(RawOrigin::Root.into(), 50));
+			// rate: 150 / 10
+			assert_eq!(AquaStakedToken::exchange_rate(), Rate::saturating_from_integer(15));
+			assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &DAO_ACCOUNT), 0);
+			assert_eq!(
+				Currencies::free_balance(ADAO_CURRENCY, &AquaStakedToken::account_id()),
+				150
+			);
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::PoolToppedUp {
+				amount: 50,
+				new_rate: Rate::saturating_from_integer(15),
+			}));
+		});
+}
+
+#[test]
+fn distribute_to_stakers_works() {
+	ExtBuilder::default()
+		// exchange rate: 1 SDAO = 2.5 ADAO
+		.balances(vec![
+			(AccountId::from(ALICE), ADAO_CURRENCY, 40),
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(DAO_ACCOUNT, SDAO_CURRENCY, 30),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 100),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_eq!(AquaStakedToken::exchange_rate(), Rate::saturating_from_rational(5, 2));
+
+			assert_ok!(AquaStakedToken::distribute_to_stakers(
+				RawOrigin::Signed(ALICE).into(),
+				40
+			));
+			// rate: 140 / 40
+			assert_eq!(AquaStakedToken::exchange_rate(), Rate::saturating_from_rational(7, 2));
+			assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &ALICE), 0);
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::DistributedToStakers {
+				from: ALICE,
+				amount: 40,
+				new_rate: Rate::saturating_from_rational(7, 2),
+			}));
+
+			// each holder gets a 1/4 and 3/4 share of the distribution
+			assert_ok!(AquaStakedToken::unstake(RawOrigin::Signed(BOB).into(), 10));
+			assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &BOB), 25 + 10);
+			assert_ok!(AquaStakedToken::unstake(RawOrigin::Signed(DAO_ACCOUNT).into(), 30));
+			assert_eq!(Currencies::free_balance(ADAO_CURRENCY, &DAO_ACCOUNT), 75 + 30);
+		});
+}
+
+#[test]
+fn distribute_to_stakers_fails_if_no_stakers() {
+	ExtBuilder::default()
+		.balances(vec![(AccountId::from(ALICE), ADAO_CURRENCY, 40)])
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				AquaStakedToken::distribute_to_stakers(RawOrigin::Signed(ALICE).into(), 40),
+				Error::<Runtime>::NoStakers
+			);
+		});
+}
+
 #[test]
 fn claim_works() {
 	ExtBuilder::default()
@@ -100,17 +406,210 @@ fn claim_works() {
 		.execute_with(|| {
 			System::set_block_number(1);
 
+			// chunks: 10 SDAO unlock at 11, 9 SDAO unlock at 12
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 10));
+			MockBlockNumberProvider::set_block_number(2);
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 10));
+
+			MockBlockNumberProvider::set_block_number(12);
+			assert_ok!(AquaStakedToken::claim(RawOrigin::Signed(ALICE).into()));
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &ALICE), 19);
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::VestingChunkReleased {
+				who: ALICE,
+				amount: 10,
+				unlock_block: 11,
+			}));
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::VestingChunkReleased {
+				who: ALICE,
+				amount: 9,
+				unlock_block: 12,
+			}));
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::Claimed { who: ALICE, amount: 19 }));
+
+			assert_noop!(
+				AquaStakedToken::claim(RawOrigin::Signed(ALICE).into()),
+				Error::<Runtime>::VestingNotFound
+			);
+		});
+}
+
+#[test]
+fn vesting_lock_coexists_with_other_locks() {
+	ExtBuilder::default()
+		// exchange rate: 1 SDAO = 10 ADAO
+		.balances(vec![
+			(AccountId::from(ALICE), SDAO_CURRENCY, 5),
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 150),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			let other_lock: LockIdentifier = *b"othrlock";
+			assert_ok!(Currencies::set_lock(other_lock, SDAO_CURRENCY, &ALICE, 5));
+			let transfer =
+				|amount: Balance| Currencies::transfer(RawOrigin::Signed(ALICE).into(), BOB, SDAO_CURRENCY, amount);
+
+			// 10 SDAO vested, overlapping with the other lock
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 10));
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &ALICE), 15);
+			assert_noop!(transfer(6), orml_tokens::Error::<Runtime>::LiquidityRestrictions);
+
+			// claiming removes the vesting lock only
+			MockBlockNumberProvider::set_block_number(11);
+			assert_ok!(AquaStakedToken::claim(RawOrigin::Signed(ALICE).into()));
+			assert_eq!(
+				Tokens::locks(ALICE, SDAO_CURRENCY)
+					.iter()
+					.map(|lock| (lock.id, lock.amount))
+					.collect::<Vec<_>>(),
+				vec![(other_lock, 5)]
+			);
+			assert_noop!(transfer(11), orml_tokens::Error::<Runtime>::LiquidityRestrictions);
+			assert_ok!(transfer(10));
+			assert_noop!(transfer(1), orml_tokens::Error::<Runtime>::LiquidityRestrictions);
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &ALICE), 5);
+		});
+}
+
+#[test]
+fn claim_with_auto_restake_works() {
+	ExtBuilder::default()
+		// exchange rate: 1 SDAO = 10 ADAO
+		.balances(vec![
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 100),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(AquaStakedToken::set_auto_restake(RawOrigin::Signed(ALICE).into(), true));
+			assert!(AquaStakedToken::auto_restake(ALICE));
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::AutoRestakeSet {
+				who: ALICE,
+				enabled: true,
+			}));
+
 			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 10));
+			MockBlockNumberProvider::set_block_number(11);
+			assert_ok!(AquaStakedToken::claim(RawOrigin::Signed(ALICE).into()));
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &ALICE), 0);
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &TREASURY_ACCOUNT), 10);
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::Restaked { who: ALICE, amount: 10 }));
+		});
+}
 
+#[test]
+fn claim_with_auto_restake_falls_back_if_deposit_fails() {
+	ExtBuilder::default()
+		// exchange rate: 1 SDAO = 10 ADAO
+		.balances(vec![
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 100),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_ok!(AquaStakedToken::set_auto_restake(RawOrigin::Signed(ALICE).into(), true));
+			MockTreasuryStaking::set_fails(true);
+
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 10));
 			MockBlockNumberProvider::set_block_number(11);
 			assert_ok!(AquaStakedToken::claim(RawOrigin::Signed(ALICE).into()));
+			// deposit reverted, claimed SDAO is liquid
 			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &ALICE), 10);
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &TREASURY_ACCOUNT), 0);
+			assert_ok!(Currencies::transfer(RawOrigin::Signed(ALICE).into(), BOB, SDAO_CURRENCY, 10));
 			System::assert_has_event(Event::AquaStakedToken(crate::Event::Claimed { who: ALICE, amount: 10 }));
+			assert!(!System::events()
+				.iter()
+				.any(|r| matches!(r.event, Event::AquaStakedToken(crate::Event::Restaked { .. }))));
+		});
+}
+
+#[test]
+fn total_vesting_works() {
+	ExtBuilder::default()
+		// exchange rate: 1 SDAO = 10 ADAO
+		.balances(vec![
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 100),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			// chunks: 10 SDAO unlock at 11, 9 SDAO unlock at 12
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 10));
+			assert_eq!(AquaStakedToken::total_vesting(), 10);
+			MockBlockNumberProvider::set_block_number(2);
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 10));
+			assert_eq!(AquaStakedToken::total_vesting(), 19);
+			assert_ok!(AquaStakedToken::do_try_state());
+
+			// partial claim
+			MockBlockNumberProvider::set_block_number(11);
+			assert_ok!(AquaStakedToken::claim(RawOrigin::Signed(ALICE).into()));
+			assert_eq!(AquaStakedToken::total_vesting(), 9);
+			assert_ok!(AquaStakedToken::do_try_state());
+
+			// full claim
+			MockBlockNumberProvider::set_block_number(12);
+			assert_ok!(AquaStakedToken::claim_chunk(RawOrigin::Signed(ALICE).into(), 12));
+			assert_eq!(AquaStakedToken::total_vesting(), 0);
+			assert_ok!(AquaStakedToken::do_try_state());
+		});
+}
+
+#[test]
+fn claim_chunk_works() {
+	ExtBuilder::default()
+		// exchange rate: 1 SDAO = 10 ADAO
+		.balances(vec![
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 100),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			// chunks: 10 SDAO unlock at 11, 9 SDAO unlock at 12
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 10));
+			MockBlockNumberProvider::set_block_number(2);
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 10));
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &ALICE), 19);
 
+			MockBlockNumberProvider::set_block_number(11);
 			assert_noop!(
-				AquaStakedToken::claim(RawOrigin::Signed(ALICE).into()),
+				AquaStakedToken::claim_chunk(RawOrigin::Signed(ALICE).into(), 12),
+				Error::<Runtime>::VestingNotMatured
+			);
+
+			MockBlockNumberProvider::set_block_number(12);
+			assert_noop!(
+				AquaStakedToken::claim_chunk(RawOrigin::Signed(ALICE).into(), 10),
 				Error::<Runtime>::VestingNotFound
 			);
+			assert_ok!(AquaStakedToken::claim_chunk(RawOrigin::Signed(ALICE).into(), 11));
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::VestingChunkReleased {
+				who: ALICE,
+				amount: 10,
+				unlock_block: 11,
+			}));
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::Claimed { who: ALICE, amount: 10 }));
+			// the other chunk stays locked
+			assert_eq!(AquaStakedToken::ledger(ALICE).unwrap().total(), 9);
+			assert_ok!(Currencies::transfer(RawOrigin::Signed(ALICE).into(), BOB, SDAO_CURRENCY, 10));
+			assert_noop!(
+				Currencies::transfer(RawOrigin::Signed(ALICE).into(), BOB, SDAO_CURRENCY, 1),
+				orml_tokens::Error::<Runtime>::LiquidityRestrictions
+			);
+
+			assert_ok!(AquaStakedToken::claim_chunk(RawOrigin::Signed(ALICE).into(), 12));
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::Claimed { who: ALICE, amount: 9 }));
+			assert_eq!(AquaStakedToken::ledger(ALICE), None);
 		});
 }
 
@@ -160,6 +659,7 @@ fn inflation_works() {
 			AquaStakedToken::on_initialize(99);
 			// no inflation yet
 			assert_eq!(Currencies::total_issuance(ADAO_CURRENCY), 80);
+			assert_eq!(MockOnDepositReward::deposit_reward_calls(), 0);
 
 			AquaStakedToken::on_initialize(100);
 			// inflation happened
@@ -171,7 +671,109 @@ fn inflation_works() {
 			// treasury, dao shares: 100 * share / exchange_rate = 100 * 0.1 / 3
 			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &RewardDestAccount::get()), 3);
 			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &DaoAccount::get()), 3);
-			assert_eq!(MockOnDepositReward::deposit_reward(), (SDAO_CURRENCY, 3));
+			MockOnDepositReward::assert_deposit_reward(1, (SDAO_CURRENCY, 3));
+			assert_eq!(AquaStakedToken::mint_ledger(MintReason::Inflation), 100);
+			assert_eq!(AquaStakedToken::mint_ledger(MintReason::Subscription), 0);
+			assert_ok!(AquaStakedToken::do_try_state());
+		});
+}
+
+#[test]
+fn on_initialize_weight_reflects_inflation_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), ADAO_CURRENCY, 50),
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 30),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			let without_inflation = AquaStakedToken::on_initialize(99);
+			assert_eq!(without_inflation, <() as WeightInfo>::on_initialize_without_inflation());
+
+			// the treasury reward callback ran, and SDAO deposits created the DAO and reward accounts
+			let first_inflation = AquaStakedToken::on_initialize(100);
+			assert_eq!(
+				first_inflation,
+				<() as WeightInfo>::on_initialize()
+					+ <() as WeightInfo>::on_deposit_reward()
+					+ 2 * <() as WeightInfo>::on_initialize_new_account()
+			);
+			assert!(first_inflation > without_inflation);
+
+			// no account created by the second inflation
+			let second_inflation = AquaStakedToken::on_initialize(200);
+			assert_eq!(
+				second_inflation,
+				<() as WeightInfo>::on_initialize() + <() as WeightInfo>::on_deposit_reward()
+			);
+			assert!(first_inflation > second_inflation);
+		});
+}
+
+#[test]
+fn no_inflation_before_start_block() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), ADAO_CURRENCY, 50),
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 30),
+		])
+		.build()
+		.execute_with(|| {
+			MockInflationStartBlock::set(150);
+
+			AquaStakedToken::on_initialize(100);
+			assert_eq!(Currencies::total_issuance(ADAO_CURRENCY), 80);
+			assert_eq!(MockOnDepositReward::deposit_reward_calls(), 0);
+
+			// resumes from start block
+			AquaStakedToken::on_initialize(200);
+			assert_eq!(Currencies::total_issuance(ADAO_CURRENCY), 180);
+			MockOnDepositReward::assert_deposit_reward(1, (SDAO_CURRENCY, 3));
+		});
+}
+
+#[test]
+fn paused_works() {
+	ExtBuilder::default()
+		.balances(vec![
+			(AccountId::from(ALICE), ADAO_CURRENCY, 50),
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 30),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_noop!(
+				AquaStakedToken::set_paused(RawOrigin::Signed(ALICE).into(), true),
+				BadOrigin
+			);
+			assert_ok!(AquaStakedToken::set_paused(RawOrigin::Root.into(), true));
+			System::assert_has_event(Event::AquaStakedToken(crate::Event::PausedSet { paused: true }));
+
+			AquaStakedToken::on_initialize(100);
+			assert_eq!(Currencies::total_issuance(ADAO_CURRENCY), 80);
+			assert_noop!(
+				AquaStakedToken::stake(RawOrigin::Signed(ALICE).into(), 10),
+				Error::<Runtime>::Paused
+			);
+			assert_noop!(
+				AquaStakedToken::unstake(RawOrigin::Signed(BOB).into(), 1),
+				Error::<Runtime>::Paused
+			);
+			assert_noop!(AquaStakedToken::unstake_for(&BOB, 1), Error::<Runtime>::Paused);
+			assert_noop!(
+				AquaStakedToken::claim(RawOrigin::Signed(ALICE).into()),
+				Error::<Runtime>::Paused
+			);
+
+			assert_ok!(AquaStakedToken::set_paused(RawOrigin::Root.into(), false));
+			AquaStakedToken::on_initialize(200);
+			assert_eq!(Currencies::total_issuance(ADAO_CURRENCY), 180);
 		});
 }
 
@@ -201,7 +803,65 @@ fn mint_for_subscription_works() {
 			// treasury, dao shares: 1_000 * share / exchange_rate = 1000 * 0.1 / 8
 			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &RewardDestAccount::get()), 12);
 			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &DaoAccount::get()), 12);
-			assert_eq!(MockOnDepositReward::deposit_reward(), (SDAO_CURRENCY, 12));
+			MockOnDepositReward::assert_deposit_reward(1, (SDAO_CURRENCY, 12));
+			assert_eq!(AquaStakedToken::mint_ledger(MintReason::Subscription), 1_000);
+			assert_eq!(AquaStakedToken::mint_ledger(MintReason::Inflation), 0);
+			assert_ok!(AquaStakedToken::do_try_state());
+		});
+}
+
+#[test]
+fn mint_for_subscription_converts_by_pre_mint_exchange_rate() {
+	ExtBuilder::default()
+		// exchange rate: 1 SDAO = 10 ADAO
+		.balances(vec![
+			(AccountId::from(BOB), SDAO_CURRENCY, 100),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 1_000),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 8_000, 10));
+			// mint: 8_000 / 0.8 = 10_000, all converted at 10 ADAO per SDAO
+			assert_eq!(Currencies::total_balance(SDAO_CURRENCY, &ALICE), 800);
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &RewardDestAccount::get()), 100);
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &DaoAccount::get()), 100);
+			assert_eq!(AquaStakedToken::exchange_rate(), Rate::saturating_from_integer(10));
+		});
+}
+
+#[test]
+fn preview_mint_for_subscription_matches_mint() {
+	ExtBuilder::default()
+		// exchange rate: 1 SDAO = 8 ADAO
+		.balances(vec![
+			(AccountId::from(BOB), SDAO_CURRENCY, 10),
+			(AquaStakedToken::account_id(), ADAO_CURRENCY, 80),
+		])
+		.build()
+		.execute_with(|| {
+			let adao_issuance = Currencies::total_issuance(ADAO_CURRENCY);
+			let preview = AquaStakedToken::preview_mint_for_subscription(800).unwrap();
+			assert_eq!(
+				preview,
+				MintPreview {
+					mint: 1_000,
+					staked: 100,
+					treasury_staked: 12,
+					dao_staked: 12,
+				}
+			);
+			// no storage writes
+			assert_eq!(Currencies::total_issuance(ADAO_CURRENCY), adao_issuance);
+			assert_eq!(AquaStakedToken::mint_ledger(MintReason::Subscription), 0);
+
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 800, 10));
+			assert_eq!(Currencies::total_issuance(ADAO_CURRENCY), adao_issuance + preview.mint);
+			assert_eq!(Currencies::total_balance(SDAO_CURRENCY, &ALICE), preview.staked);
+			assert_eq!(
+				Currencies::free_balance(SDAO_CURRENCY, &RewardDestAccount::get()),
+				preview.treasury_staked
+			);
+			assert_eq!(Currencies::free_balance(SDAO_CURRENCY, &DaoAccount::get()), preview.dao_staked);
 		});
 }
 
@@ -221,9 +881,51 @@ fn vesting_over_max_chunks_fails() {
 			}
 
 			MockBlockNumberProvider::set_block_number(6);
+			assert!(!AquaStakedToken::can_mint_for(&ALICE, 10));
 			assert_noop!(
 				AquaStakedToken::mint_for_subscription(&ALICE, 100, 10),
 				Error::<Runtime>::MaxVestingChunkExceeded,
 			);
+
+			// merged into the last chunk
+			assert!(AquaStakedToken::can_mint_for(&ALICE, 9));
+			assert_ok!(AquaStakedToken::mint_for_subscription(&ALICE, 100, 9));
+		});
+}
+
+#[test]
+fn try_state_detects_unrecorded_mint() {
+	ExtBuilder::default()
+		.balances(vec![(AccountId::from(ALICE), ADAO_CURRENCY, 100)])
+		.build()
+		.execute_with(|| {
+			assert_eq!(AquaStakedToken::genesis_issuance(), 100);
+			assert_ok!(AquaStakedToken::do_try_state());
+
+			assert_ok!(Currencies::deposit(ADAO_CURRENCY, &ALICE, 1));
+			assert_eq!(
+				AquaStakedToken::do_try_state(),
+				Err("ADAO total issuance doesn't match the mint ledger")
+			);
 		});
 }
+
+#[test]
+fn call_indices_are_stable() {
+	let calls: Vec<(crate::Call<Runtime>, u8)> = vec![
+		(crate::Call::stake { amount: 0 }, 0),
+		(crate::Call::unstake { amount: 0 }, 1),
+		(crate::Call::claim {}, 2),
+		(crate::Call::update_unstake_fee_rate { rate: Rate::zero() }, 3),
+		(crate::Call::claim_chunk { unlock_at: 0 }, 4),
+		(crate::Call::set_auto_restake { enabled: false }, 5),
+		(crate::Call::set_paused { paused: false }, 6),
+		(crate::Call::update_fee_burn_ratio { ratio: Ratio::zero() }, 7),
+		(crate::Call::top_up_pool { amount: 0 }, 8),
+		(crate::Call::set_max_unstake_per_call { amount: 0 }, 9),
+		(crate::Call::distribute_to_stakers { amount: 0 }, 10),
+	];
+	for (call, index) in calls {
+		assert_eq!(call.encode()[0], index, "{:?}", call);
+	}
+}
@@ -28,20 +28,42 @@ use sp_std::marker::PhantomData;
 pub trait WeightInfo {
 	fn on_initialize() -> Weight;
 	fn on_initialize_without_inflation() -> Weight;
+	fn on_deposit_reward() -> Weight;
+	fn on_initialize_new_account() -> Weight;
 	fn stake() -> Weight;
 	fn unstake() -> Weight;
 	fn claim() -> Weight;
+	fn claim_chunk() -> Weight;
 	fn update_unstake_fee_rate() -> Weight;
+	fn update_fee_burn_ratio() -> Weight;
+	fn set_paused() -> Weight;
+	fn top_up_pool() -> Weight;
+	fn set_auto_restake() -> Weight;
+	fn set_max_unstake_per_call() -> Weight;
+	fn distribute_to_stakers() -> Weight;
 }
 
 /// Weights for ecocsystem_aqua_staked_token using the Acala node and recommended hardware.
 pub struct AcalaWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for AcalaWeight<T> {
 	fn on_initialize() -> Weight {
-		0
+		(0 as Weight)
+			.saturating_add(T::DbWeight::get().reads(11 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
 	}
 	fn on_initialize_without_inflation() -> Weight {
-		0
+		(0 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+	}
+	fn on_deposit_reward() -> Weight {
+		(0 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn on_initialize_new_account() -> Weight {
+		(0 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	fn stake() -> Weight {
 		0
@@ -52,18 +74,52 @@ impl<T: frame_system::Config> WeightInfo for AcalaWeight<T> {
 	fn claim() -> Weight {
 		0
 	}
+	fn claim_chunk() -> Weight {
+		0
+	}
 	fn update_unstake_fee_rate() -> Weight {
 		0
 	}
+	fn update_fee_burn_ratio() -> Weight {
+		0
+	}
+	fn set_paused() -> Weight {
+		0
+	}
+	fn top_up_pool() -> Weight {
+		0
+	}
+	fn set_auto_restake() -> Weight {
+		0
+	}
+	fn set_max_unstake_per_call() -> Weight {
+		0
+	}
+	fn distribute_to_stakers() -> Weight {
+		0
+	}
 }
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
 	fn on_initialize() -> Weight {
-		0
+		(0 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(11 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
 	}
 	fn on_initialize_without_inflation() -> Weight {
-		0
+		(0 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+	}
+	fn on_deposit_reward() -> Weight {
+		(0 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn on_initialize_new_account() -> Weight {
+		(0 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
 	}
 	fn stake() -> Weight {
 		0
@@ -74,7 +130,28 @@ impl WeightInfo for () {
 	fn claim() -> Weight {
 		0
 	}
+	fn claim_chunk() -> Weight {
+		0
+	}
 	fn update_unstake_fee_rate() -> Weight {
 		0
 	}
+	fn update_fee_burn_ratio() -> Weight {
+		0
+	}
+	fn set_paused() -> Weight {
+		0
+	}
+	fn top_up_pool() -> Weight {
+		0
+	}
+	fn set_auto_restake() -> Weight {
+		0
+	}
+	fn set_max_unstake_per_call() -> Weight {
+		0
+	}
+	fn distribute_to_stakers() -> Weight {
+		0
+	}
 }